@@ -1,33 +1,64 @@
 use engine::{Board, types::*};
+use engine::bitboard::{get_bishop_attacks, get_king_zone, get_knight_attacks, get_rook_attacks, iterate_bits};
 use crate::piece_square_tables::*;
 use crate::types::*;
-use std::sync::OnceLock;
 
-static PST: OnceLock<PreCalculatedPST> = OnceLock::new();
+/// Separate evaluation terms, all from White's perspective
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EvaluationBreakdown {
+    pub material: i32,
+    pub pst_mg: i32,
+    pub pst_eg: i32,
+    pub game_phase: f32,
+}
+
+pub fn evaluation_breakdown(board: &Board) -> EvaluationBreakdown {
+    EvaluationBreakdown {
+        material: calculate_material_for_color(board, WHITE) - calculate_material_for_color(board, BLACK),
+        pst_mg: pst_score_with(board, &MIDDLEGAME_PST),
+        pst_eg: pst_score_with(board, &ENDGAME_PST),
+        game_phase: game_phase(board),
+    }
+}
+
+/// Blend a middlegame and an endgame score by `game_phase`
+fn taper(board: &Board, mg: i32, eg: i32) -> i32 {
+    let phase = game_phase(board);
+    (mg as f32 * phase + eg as f32 * (1.0 - phase)).round() as i32
+}
 
-fn get_pst() -> &'static PreCalculatedPST {
-    PST.get_or_init(|| PreCalculatedPST::new())
+/// Remaining non-pawn material as a fraction of the starting total: 1.0 = middlegame, 0.0 = endgame
+pub fn game_phase(board: &Board) -> f32 {
+    board.game_phase()
 }
 
 pub fn evaluate_position(board: &Board) -> i32 {
     let legal_moves = board.get_all_legal_moves();
     if legal_moves.is_empty() {
-        return if board.is_in_check() { -MATE_SCORE } else { DRAW_SCORE };
+        return if board.is_in_check() { -CHECKMATE_SCORE } else { DRAW_SCORE };
     }
 
-    let mut score = 0;
-    let material_score = evaluate_material(board);
-    score += material_score;
-    
-    // Uncomment this for PST evaluation
-    let pst_score = evaluate_position_with_pst(board);
-    score += pst_score;
+    // Material and piece-square tables come from the side to move, the other terms from White
+    let sign = if board.current_turn == WHITE { 1 } else { -1 };
+    let mut score = board.static_eval();
+    score += sign * (passed_pawn_score(board)
+        + king_safety(board, WHITE) - king_safety(board, BLACK)
+        + mobility_score(board, WHITE) - mobility_score(board, BLACK)
+        + bishop_score(board)
+        + rook_score(board)
+        + knight_outpost_score(board));
+
+    // In a lost position, steer towards positions where the opponent can stalemate us
+    if score < LOSING_THRESHOLD && legal_moves.len() <= 3 && board.is_stalemate_trap(board.current_turn) {
+        score += STALEMATE_TRAP_BONUS;
+    }
 
     // LOG: Detailed evaluation if logger is available
     if let Some(logger_ref) = &board.logger {
         let material_white = calculate_material_for_color(board, engine::WHITE);
         let material_black = calculate_material_for_color(board, engine::BLACK);
         let game_phase = crate::piece_square_tables::calculate_game_phase(board);
+        let pst_score = sign * pst_score(board);
         
         // Check for game phase transitions
         logger_ref.borrow_mut().check_and_log_phase_transition(game_phase, "position evaluation");
@@ -62,88 +93,245 @@ pub fn evaluate_position(board: &Board) -> i32 {
     score
 }
 
-// Add this helper function
-fn calculate_material_for_color(board: &Board, color: u8) -> i32 {
-    let mut material = 0;
-    for rank in 0..8 {
-        for file in 0..8 {
-            let square = engine::Square::new(file, rank);
-            let piece = board.get_piece(square);
-            if !engine::is_empty(piece) && engine::piece_color(piece) == color {
-                material += match engine::piece_type(piece) {
-                    engine::PAWN => 100,
-                    engine::KNIGHT => 300,
-                    engine::BISHOP => 300,
-                    engine::ROOK => 500,
-                    engine::QUEEN => 900,
-                    engine::KING => 0,
-                    _ => 0,
-                };
-            }
+/// Pawns of `color` with no enemy pawns ahead of them on the same or adjacent files
+fn passed_pawns(board: &Board, color: u8) -> Vec<Square> {
+    let enemy_pawns = board.bitboards.get_pieces(opposite_color(color), PAWN);
+
+    board.bitboards.find_pieces(color, PAWN).into_iter().filter(|square| {
+        let mut files = engine::bitboard::FILE_MASKS[square.file() as usize];
+        if square.file() > 0 {
+            files |= engine::bitboard::FILE_MASKS[square.file() as usize - 1];
+        }
+        if square.file() < 7 {
+            files |= engine::bitboard::FILE_MASKS[square.file() as usize + 1];
+        }
+
+        let ahead = if color == WHITE {
+            !((1u64 << ((square.rank() + 1) * 8)) - 1)
+        } else {
+            (1u64 << (square.rank() * 8)) - 1
+        };
+
+        enemy_pawns & files & ahead == 0
+    }).collect()
+}
+
+pub fn count_passed_pawns(board: &Board, color: u8) -> i32 {
+    passed_pawns(board, color).len() as i32
+}
+
+/// Tapered passed pawn bonus from White's perspective, growing as the pawns advance
+pub fn passed_pawn_score(board: &Board) -> i32 {
+    let mut mg = 0;
+    let mut eg = 0;
+
+    for color in [WHITE, BLACK] {
+        let sign = if color == WHITE { 1 } else { -1 };
+        for square in passed_pawns(board, color) {
+            let rank = if color == WHITE { square.rank() } else { 7 - square.rank() } as usize;
+            mg += sign * MIDDLEGAME_PASSED_PAWN_BONUS[rank];
+            eg += sign * ENDGAME_PASSED_PAWN_BONUS[rank];
         }
     }
-    material
-}
-
-fn evaluate_material(board: &Board) -> i32 {
-    let mut white_material = 0;
-    let mut black_material = 0;
-    
-    // OPTIMIZED: Use bitboard counting instead of nested loops
-    white_material += (board.bitboards.count_pieces(WHITE, PAWN) as i32) * PIECE_VALUES[PAWN as usize];
-    white_material += (board.bitboards.count_pieces(WHITE, KNIGHT) as i32) * PIECE_VALUES[KNIGHT as usize];
-    white_material += (board.bitboards.count_pieces(WHITE, BISHOP) as i32) * PIECE_VALUES[BISHOP as usize];
-    white_material += (board.bitboards.count_pieces(WHITE, ROOK) as i32) * PIECE_VALUES[ROOK as usize];
-    white_material += (board.bitboards.count_pieces(WHITE, QUEEN) as i32) * PIECE_VALUES[QUEEN as usize];
-    
-    black_material += (board.bitboards.count_pieces(BLACK, PAWN) as i32) * PIECE_VALUES[PAWN as usize];
-    black_material += (board.bitboards.count_pieces(BLACK, KNIGHT) as i32) * PIECE_VALUES[KNIGHT as usize];
-    black_material += (board.bitboards.count_pieces(BLACK, BISHOP) as i32) * PIECE_VALUES[BISHOP as usize];
-    black_material += (board.bitboards.count_pieces(BLACK, ROOK) as i32) * PIECE_VALUES[ROOK as usize];
-    black_material += (board.bitboards.count_pieces(BLACK, QUEEN) as i32) * PIECE_VALUES[QUEEN as usize];
-    
-    // Use your elegant mathematical approach
-    (2 * (board.current_turn == WHITE) as i32 - 1) * (white_material - black_material)
-}
-
-fn evaluate_position_with_pst(board: &Board) -> i32 {
-    let pst = get_pst();
-    let pattern = detect_endgame_pattern(board);
-    let phase = calculate_game_phase(board);
-    let mut score = 0;
 
-    // OPTIMIZED: Replace nested loops with bitboard iteration
-    let piece_types = [PAWN, KNIGHT, BISHOP, ROOK, QUEEN, KING];
-    
-    for piece_type in piece_types {
-        if piece_type >= 1 && piece_type <= 6 {
-            let piece_index = (piece_type - 1) as usize;
-            
-            // Process white pieces of this type - only iterate over actual pieces
-            let white_pieces = board.bitboards.find_pieces(WHITE, piece_type);
-            for square in white_pieces {
-                let rank = square.0 / 8;
-                let file = square.0 % 8;
-                let square_index = ((7 - rank) * 8 + file) as usize;
-                let pst_value = pst.get_value(piece_index, pattern, phase, square_index);
-                score += (2 * (board.current_turn == WHITE) as i32 - 1) * pst_value;
-            }
-            
-            // Process black pieces of this type - only iterate over actual pieces
-            let black_pieces = board.bitboards.find_pieces(BLACK, piece_type);
-            for square in black_pieces {
-                let rank = square.0 / 8;
-                let file = square.0 % 8;
-                let square_index = (rank * 8 + file) as usize;
-                let pst_value = pst.get_value(piece_index, pattern, phase, square_index);
-                score -= (2 * (board.current_turn == WHITE) as i32 - 1) * pst_value;
+    taper(board, mg, eg)
+}
+
+/// Middlegame king safety penalty for `color` (zero or negative): missing shield pawns
+/// and enemy pieces attacking the king zone
+pub fn king_safety(board: &Board, color: u8) -> i32 {
+    if game_phase(board) <= KING_SAFETY_MIN_PHASE {
+        return 0;
+    }
+    let king_square = match board.find_king(color) {
+        Some(square) => square,
+        None => return 0,
+    };
+
+    let mut penalty = 0;
+
+    // Pawn shield: the three squares directly in front of the king
+    let shield_rank = if color == WHITE { king_square.rank() as i8 + 1 } else { king_square.rank() as i8 - 1 };
+    if (0..8).contains(&shield_rank) {
+        for file in king_square.file() as i8 - 1..=king_square.file() as i8 + 1 {
+            if (0..8).contains(&file) && board.get_piece(Square::new(file as u8, shield_rank as u8)) != make_piece(PAWN, color) {
+                penalty += PAWN_SHIELD_PENALTY;
             }
         }
     }
-    
+
+    // Each enemy piece hitting the king zone counts once
+    let enemy = opposite_color(color);
+    let mut attackers = 0u64;
+    for square in iterate_bits(get_king_zone(color, king_square.0)) {
+        attackers |= board.attackers_bitboard(Square(square), enemy);
+    }
+    for square in iterate_bits(attackers) {
+        penalty += KING_ATTACKER_PENALTY[piece_type(board.get_piece(Square(square))) as usize];
+    }
+
+    -penalty
+}
+
+/// Mobility bonus for the non-pawn pieces of `color`, counting attacked squares not held by their own side
+pub fn mobility_score(board: &Board, color: u8) -> i32 {
+    let occupancy = board.bitboards.all_pieces;
+    let own_pieces = board.bitboards.get_all_pieces(color);
+
+    let mut score = 0;
+    for piece_type in [KNIGHT, BISHOP, ROOK, QUEEN] {
+        let bonus = &MOBILITY_BONUS[(piece_type - 1) as usize];
+        for square in iterate_bits(board.bitboards.get_pieces(color, piece_type)) {
+            let attacks = match piece_type {
+                KNIGHT => get_knight_attacks(square),
+                BISHOP => get_bishop_attacks(square, occupancy),
+                ROOK => get_rook_attacks(square, occupancy),
+                _ => get_bishop_attacks(square, occupancy) | get_rook_attacks(square, occupancy),
+            };
+            let count = (attacks & !own_pieces).count_ones() as usize;
+            score += bonus[count.min(27)];
+        }
+    }
+
     score
 }
 
+// 0 = dark square (a1), 1 = light square
+fn square_color(square: Square) -> u8 {
+    (square.file() + square.rank()) % 2
+}
+
+pub fn bishop_pair_bonus(board: &Board, color: u8) -> i32 {
+    let bishops = board.bitboards.find_pieces(color, BISHOP);
+    let has_dark = bishops.iter().any(|&square| square_color(square) == 0);
+    let has_light = bishops.iter().any(|&square| square_color(square) == 1);
+
+    if has_dark && has_light { BISHOP_PAIR_BONUS } else { 0 }
+}
+
+/// Penalty (zero or negative) for bishops hemmed in by their own pawns on the same color
+pub fn bad_bishop_penalty(board: &Board, color: u8) -> i32 {
+    let pawns = board.bitboards.find_pieces(color, PAWN);
+    let mut penalty = 0;
+
+    for bishop in board.bitboards.find_pieces(color, BISHOP) {
+        let same_color = pawns.iter().filter(|&&pawn| square_color(pawn) == square_color(bishop)).count() as i32;
+        if same_color * 2 > pawns.len() as i32 {
+            penalty += BAD_BISHOP_PENALTY * same_color;
+        }
+    }
+
+    -penalty
+}
+
+/// Bishop pair and bad bishop terms from White's perspective, counting double in the endgame
+pub fn bishop_score(board: &Board) -> i32 {
+    let eg = bishop_pair_bonus(board, WHITE) - bishop_pair_bonus(board, BLACK) +
+        bad_bishop_penalty(board, WHITE) - bad_bishop_penalty(board, BLACK);
+    let mg = eg / 2;
+
+    taper(board, mg, eg)
+}
+
+/// Open and semi-open file bonuses for the rooks of `color`
+pub fn rook_bonus(board: &Board, color: u8) -> i32 {
+    let own_pawns = board.bitboards.get_pieces(color, PAWN);
+    let enemy_pawns = board.bitboards.get_pieces(opposite_color(color), PAWN);
+
+    let mut bonus = 0;
+    for rook in board.bitboards.find_pieces(color, ROOK) {
+        let file = engine::bitboard::FILE_MASKS[rook.file() as usize];
+        if own_pawns & file == 0 {
+            bonus += if enemy_pawns & file == 0 { ROOK_OPEN_FILE } else { ROOK_SEMI_OPEN_FILE };
+        }
+    }
+
+    bonus
+}
+
+/// Bonus per rook of `color` on the seventh rank while the enemy king is stuck on its back rank
+pub fn rook_seventh_rank(board: &Board, color: u8) -> i32 {
+    let (seventh_rank, enemy_back_rank) = if color == WHITE { (6, 7) } else { (1, 0) };
+    if board.find_king(opposite_color(color)).is_none_or(|square| square.rank() != enemy_back_rank) {
+        return 0;
+    }
+
+    let rooks = board.bitboards.find_pieces(color, ROOK);
+    rooks.iter().filter(|rook| rook.rank() == seventh_rank).count() as i32 * ROOK_SEVENTH_BONUS
+}
+
+/// Bonus when two rooks of `color` share a rank or file with nothing between them
+pub fn rook_connectivity_bonus(board: &Board, color: u8) -> i32 {
+    let rooks = board.bitboards.find_pieces(color, ROOK);
+    let [first, second] = rooks[..] else {
+        return 0;
+    };
+
+    let direction = if first.rank() == second.rank() {
+        ((second.file() as i8 - first.file() as i8).signum(), 0)
+    } else if first.file() == second.file() {
+        (0, (second.rank() as i8 - first.rank() as i8).signum())
+    } else {
+        return 0;
+    };
+
+    if board.is_clear_path(first, second, direction) { CONNECTED_ROOKS_BONUS } else { 0 }
+}
+
+/// Rook placement from White's perspective. File and seventh rank bonuses count double in the
+/// endgame; connected rooks matter most while the back rank is still being cleared
+pub fn rook_score(board: &Board) -> i32 {
+    let placement = rook_bonus(board, WHITE) + rook_seventh_rank(board, WHITE) -
+        rook_bonus(board, BLACK) - rook_seventh_rank(board, BLACK);
+    let connected = rook_connectivity_bonus(board, WHITE) - rook_connectivity_bonus(board, BLACK);
+    taper(board, placement / 2 + connected, placement + connected / 2)
+}
+
+/// Bonus for knights of `color` on outposts: defended by a friendly pawn, and no enemy pawn
+/// on an adjacent file in front of them that could ever advance to attack the square
+pub fn knight_outpost_bonus(board: &Board, color: u8) -> i32 {
+    let own_pawns = board.bitboards.get_pieces(color, PAWN);
+    let enemy_pawns = board.bitboards.get_pieces(opposite_color(color), PAWN);
+
+    let mut bonus = 0;
+    for knight in board.bitboards.find_pieces(color, KNIGHT) {
+        // A pawn of ours defends the square from where an enemy pawn on it would attack
+        if engine::bitboard::get_pawn_attacks(opposite_color(color), knight.0) & own_pawns == 0 {
+            continue;
+        }
+
+        let ahead = (0..8u8)
+            .filter(|&rank| if color == WHITE { rank > knight.rank() } else { rank < knight.rank() })
+            .fold(0u64, |mask, rank| mask | engine::bitboard::RANK_MASKS[rank as usize]);
+        let adjacent_files = [knight.file().wrapping_sub(1), knight.file() + 1].iter()
+            .filter(|&&file| file < 8)
+            .fold(0u64, |mask, &file| mask | engine::bitboard::FILE_MASKS[file as usize]);
+        if enemy_pawns & ahead & adjacent_files != 0 {
+            continue;
+        }
+
+        let table_index = if color == WHITE { (7 - knight.rank()) * 8 + knight.file() } else { knight.rank() * 8 + knight.file() };
+        bonus += KNIGHT_OUTPOST_BONUS * KNIGHT_OUTPOST_PST[table_index as usize] / 100;
+    }
+
+    bonus
+}
+
+/// Knight outposts from White's perspective, worth half as much in the endgame
+pub fn knight_outpost_score(board: &Board) -> i32 {
+    let mg = knight_outpost_bonus(board, WHITE) - knight_outpost_bonus(board, BLACK);
+    taper(board, mg, mg / 2)
+}
+
+// Add this helper function
+fn calculate_material_for_color(board: &Board, color: u8) -> i32 {
+    board.num_pieces(color, PAWN) as i32 * 100 +
+    board.num_pieces(color, KNIGHT) as i32 * 300 +
+    board.num_pieces(color, BISHOP) as i32 * 300 +
+    board.num_pieces(color, ROOK) as i32 * 500 +
+    board.num_pieces(color, QUEEN) as i32 * 900
+}
+
 fn get_enemy_king_penalty(_board: &Board, pattern: EndgamePattern, enemy_king_square: usize) -> i32 {
     match pattern {
         EndgamePattern::KQvsK => {
@@ -3,6 +3,7 @@ pub mod evaluation;
 pub mod search;
 pub mod transposition;
 pub mod piece_square_tables;
+pub mod opening_book;
 
 pub use types::*;
 pub use evaluation::*;
@@ -1,4 +1,6 @@
 use engine::{Board, types::*};
+// The base tables live in the engine so `Board::static_eval` can use them
+pub use engine::pst::*;
 use std::sync::OnceLock;
 
 static PST: OnceLock<PreCalculatedPST> = OnceLock::new();
@@ -41,96 +43,6 @@ pub enum EndgamePattern {
     GeneralEndgame = 8, // General simplified endgame
 }
 
-// Opening PST - Scaled to 5-15% of piece material values
-const OPENING_PAWN_PST: [i32; 64] = [
-     0,  0,  0,  0,  0,  0,  0,  0,
-    12, 12, 12, 12, 12, 12, 12, 12,  // Reduced from 50 to 12 (12% of 100)
-     2,  2,  4,  6,  6,  4,  2,  2,  // Reduced from 10-30 to 2-6
-     1,  1,  2,  5,  5,  2,  1,  1,  // Reduced from 5-25 to 1-5
-     0,  0,  0,  4,  4,  0,  0,  0,  // Reduced from 20 to 4
-     1, -1, -2,  0,  0, -2, -1,  1,
-     1,  2,  2, -4, -4,  2,  2,  1,  // Reduced penalty from -20 to -4
-     0,  0,  0,  0,  0,  0,  0,  0,
-];
-
-const OPENING_KNIGHT_PST: [i32; 64] = [
-   -12,-10, -6, -6, -6, -6,-10,-12,  // Reduced from -50 to -12 (4% of 320)
-   -10, -4,  0,  0,  0,  0, -4,-10,  // Reduced from -20 to -4
-    -6,  0,  2,  4,  4,  2,  0, -6,  // Reduced from 10-15 to 2-4
-    -6,  1,  4,  5,  5,  4,  1, -6,  // Reduced from 15-20 to 4-5 (1.5% of 320)
-    -6,  0,  4,  5,  5,  4,  0, -6,
-    -6,  1,  2,  4,  4,  2,  1, -6,
-   -10, -4,  0,  1,  1,  0, -4,-10,
-   -12,-10, -6, -6, -6, -6,-10,-12,
-];
-
-const OPENING_BISHOP_PST: [i32; 64] = [
-    -4, -2, -2, -2, -2, -2, -2, -4,  // Reduced from -20 to -4 (1% of 330)
-    -2,  0,  0,  0,  0,  0,  0, -2,
-    -2,  0,  1,  2,  2,  1,  0, -2,  // Reduced from 5-10 to 1-2
-    -2,  1,  1,  2,  2,  1,  1, -2,
-    -2,  0,  2,  2,  2,  2,  0, -2,
-    -2,  2,  2,  2,  2,  2,  2, -2,
-    -2,  1,  0,  0,  0,  0,  1, -2,
-    -4, -2, -2, -2, -2, -2, -2, -4,
-];
-
-const OPENING_ROOK_PST: [i32; 64] = [
-     0,  0,  0,  0,  0,  0,  0,  0,
-     1,  2,  2,  2,  2,  2,  2,  1,  // Reduced from 5-10 to 1-2 (0.4% of 500)
-    -1,  0,  0,  0,  0,  0,  0, -1,  // Reduced from -5 to -1
-    -1,  0,  0,  0,  0,  0,  0, -1,
-    -1,  0,  0,  0,  0,  0,  0, -1,
-    -1,  0,  0,  0,  0,  0,  0, -1,
-    -1,  0,  0,  0,  0,  0,  0, -1,
-     0,  0,  0,  1,  1,  0,  0,  0,  // Reduced from 5 to 1
-];
-
-const OPENING_QUEEN_PST: [i32; 64] = [
-    -4, -2, -2, -1, -1, -2, -2, -4,  // Reduced from -20 to -4 (0.4% of 900)
-    -2,  0,  0,  0,  0,  0,  0, -2,
-    -2,  0,  1,  1,  1,  1,  0, -2,  // Reduced from 5 to 1
-    -1,  0,  1,  1,  1,  1,  0, -1,
-     0,  0,  1,  1,  1,  1,  0, -1,
-    -2,  1,  1,  1,  1,  1,  0, -2,
-    -2,  0,  1,  0,  0,  0,  0, -2,
-    -4, -2, -2, -1, -1, -2, -2, -4,
-];
-
-const OPENING_KING_PST: [i32; 64] = [
-    -6, -8, -8,-10,-10, -8, -8, -6,  // Reduced from -30 to -6
-    -6, -8, -8,-10,-10, -8, -8, -6,  // King safety still important but scaled
-    -6, -8, -8,-10,-10, -8, -8, -6,
-    -6, -8, -8,-10,-10, -8, -8, -6,
-    -4, -6, -6, -8, -8, -6, -6, -4,
-    -2, -4, -4, -4, -4, -4, -4, -2,
-     4,  4,  0,  0,  0,  0,  4,  4,  // Reduced from 20 to 4
-     4,  6,  2,  0,  0,  2,  6,  4,  // Reduced from 30 to 6
-];
-
-// Endgame PST - Keep higher values as endgame positioning is more critical
-const ENDGAME_PAWN_PST: [i32; 64] = [
-     0,  0,  0,  0,  0,  0,  0,  0,
-    80, 80, 80, 80, 80, 80, 80, 80,  // Keep high - pawn promotion is huge
-    60, 60, 60, 60, 60, 60, 60, 60,
-    40, 40, 40, 40, 40, 40, 40, 40,
-    20, 20, 20, 20, 20, 20, 20, 20,
-    10, 10, 10, 10, 10, 10, 10, 10,
-    10, 10, 10, 10, 10, 10, 10, 10,
-     0,  0,  0,  0,  0,  0,  0,  0,
-];
-
-const ENDGAME_KING_PST: [i32; 64] = [
-   -50,-40,-30,-20,-20,-30,-40,-50,  // Keep high - king activity crucial in endgame
-   -30,-20,-10,  0,  0,-10,-20,-30,
-   -30,-10, 20, 30, 30, 20,-10,-30,
-   -30,-10, 30, 40, 40, 30,-10,-30,
-   -30,-10, 30, 40, 40, 30,-10,-30,
-   -30,-10, 20, 30, 30, 20,-10,-30,
-   -30,-30,  0,  0,  0,  0,-30,-30,
-   -50,-30,-30,-30,-30,-30,-30,-50,
-];
-
 // KQ vs K - Force enemy king to edge (keep higher values for mating patterns)
 const KQ_VS_K_ENEMY_KING: [i32; 64] = [
    -200,-160,-120, -80, -80,-120,-160,-200,
@@ -199,6 +111,33 @@ const KR_VS_K_ROOK: [i32; 64] = [
     20, 30, 40, 50, 50, 40, 30, 20,
 ];
 
+// Knight outposts - percentage of KNIGHT_OUTPOST_BONUS by square, central files
+// worth the most and nothing in our own half, where an outpost isn't pressing
+pub const KNIGHT_OUTPOST_PST: [i32; 64] = [
+     0,   0,   0,   0,   0,   0,   0,   0,
+    40,  60,  80, 100, 100,  80,  60,  40,
+    40,  60,  80, 100, 100,  80,  60,  40,
+    40,  60,  80, 100, 100,  80,  60,  40,
+     0,   0,   0,   0,   0,   0,   0,   0,
+     0,   0,   0,   0,   0,   0,   0,   0,
+     0,   0,   0,   0,   0,   0,   0,   0,
+     0,   0,   0,   0,   0,   0,   0,   0,
+];
+
+/// Interpolate between middlegame and endgame tables by `game_phase` (1.0 = middlegame, 0.0 = endgame)
+pub fn pst_score_tapered(board: &Board, mg_tables: &PstTables, eg_tables: &PstTables) -> i32 {
+    let phase = board.game_phase();
+    let mg = pst_score_with(board, mg_tables) as f32;
+    let eg = pst_score_with(board, eg_tables) as f32;
+
+    (mg * phase + eg * (1.0 - phase)).round() as i32
+}
+
+/// Tapered PST score with the default tables, from White's perspective
+pub fn pst_score(board: &Board) -> i32 {
+    board.pst_score()
+}
+
 pub struct PreCalculatedPST {
     // [piece_type][pattern][phase_bucket][square]
     tables: Box<[[[[i32; 64]; 16]; 9]; 6]>,
@@ -354,21 +293,21 @@ pub fn calculate_game_phase(board: &Board) -> u8 {
 fn count_pieces(board: &Board) -> ((u8, u8, u8, u8, u8, u8), (u8, u8, u8, u8, u8, u8)) {
     // OPTIMIZED: Direct bitboard queries instead of nested loops
     let white_pieces = (
-        board.bitboards.count_pieces(WHITE, PAWN) as u8,
-        board.bitboards.count_pieces(WHITE, KNIGHT) as u8,
-        board.bitboards.count_pieces(WHITE, BISHOP) as u8,
-        board.bitboards.count_pieces(WHITE, ROOK) as u8,
-        board.bitboards.count_pieces(WHITE, QUEEN) as u8,
-        board.bitboards.count_pieces(WHITE, KING) as u8,
+        board.num_pieces(WHITE, PAWN),
+        board.num_pieces(WHITE, KNIGHT),
+        board.num_pieces(WHITE, BISHOP),
+        board.num_pieces(WHITE, ROOK),
+        board.num_pieces(WHITE, QUEEN),
+        board.num_pieces(WHITE, KING),
     );
     
     let black_pieces = (
-        board.bitboards.count_pieces(BLACK, PAWN) as u8,
-        board.bitboards.count_pieces(BLACK, KNIGHT) as u8,
-        board.bitboards.count_pieces(BLACK, BISHOP) as u8,
-        board.bitboards.count_pieces(BLACK, ROOK) as u8,
-        board.bitboards.count_pieces(BLACK, QUEEN) as u8,
-        board.bitboards.count_pieces(BLACK, KING) as u8,
+        board.num_pieces(BLACK, PAWN),
+        board.num_pieces(BLACK, KNIGHT),
+        board.num_pieces(BLACK, BISHOP),
+        board.num_pieces(BLACK, ROOK),
+        board.num_pieces(BLACK, QUEEN),
+        board.num_pieces(BLACK, KING),
     );
     
     (white_pieces, black_pieces)
@@ -380,17 +319,17 @@ fn total_pieces(pieces: &(u8, u8, u8, u8, u8, u8)) -> u8 {
 
 fn calculate_total_material(board: &Board) -> i32 {
     // OPTIMIZED: Direct bitboard calculations
-    board.bitboards.count_pieces(WHITE, PAWN) as i32 * 100 +
-    board.bitboards.count_pieces(WHITE, KNIGHT) as i32 * 320 +
-    board.bitboards.count_pieces(WHITE, BISHOP) as i32 * 330 +
-    board.bitboards.count_pieces(WHITE, ROOK) as i32 * 500 +
-    board.bitboards.count_pieces(WHITE, QUEEN) as i32 * 900 +
-    board.bitboards.count_pieces(WHITE, KING) as i32 * 20000 +
+    board.num_pieces(WHITE, PAWN) as i32 * 100 +
+    board.num_pieces(WHITE, KNIGHT) as i32 * 320 +
+    board.num_pieces(WHITE, BISHOP) as i32 * 330 +
+    board.num_pieces(WHITE, ROOK) as i32 * 500 +
+    board.num_pieces(WHITE, QUEEN) as i32 * 900 +
+    board.num_pieces(WHITE, KING) as i32 * 20000 +
     
-    board.bitboards.count_pieces(BLACK, PAWN) as i32 * 100 +
-    board.bitboards.count_pieces(BLACK, KNIGHT) as i32 * 320 +
-    board.bitboards.count_pieces(BLACK, BISHOP) as i32 * 330 +
-    board.bitboards.count_pieces(BLACK, ROOK) as i32 * 500 +
-    board.bitboards.count_pieces(BLACK, QUEEN) as i32 * 900 +
-    board.bitboards.count_pieces(BLACK, KING) as i32 * 20000
+    board.num_pieces(BLACK, PAWN) as i32 * 100 +
+    board.num_pieces(BLACK, KNIGHT) as i32 * 320 +
+    board.num_pieces(BLACK, BISHOP) as i32 * 330 +
+    board.num_pieces(BLACK, ROOK) as i32 * 500 +
+    board.num_pieces(BLACK, QUEEN) as i32 * 900 +
+    board.num_pieces(BLACK, KING) as i32 * 20000
 }
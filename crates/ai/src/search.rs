@@ -1,13 +1,37 @@
 use engine::{Board, Move};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc};
+use std::time::{Duration, Instant};
 use crate::{evaluation::*, types::*};
 use crate::transposition::*;
+use crate::opening_book::OpeningBook;
 use crate::piece_square_tables::get_pst;
 
 pub struct SearchEngine {
     pub nodes_searched: u64,
     transposition_table: TranspositionTable,
     logger: Option<std::rc::Rc<std::cell::RefCell<engine::ChessLogger>>>,
-    killer_moves: [[Option<Move>; 2]; 128],
+    killers: Vec<[Option<Move>; 2]>, // Quiet cutoff moves per ply, as deep as the PV table
+    history: [[i32; 64]; 64], // Quiet cutoff scores by [from][to], kept across iterations
+    pub config: SearchConfig,
+    pub razored_nodes: u64,
+    fail_high: u32,
+    fail_low: u32,
+    repetition_stack: Vec<u64>, // Position keys on the current search path, kept apart from the TT
+    deadline: Option<Instant>, // Hard stop for timed searches
+    stopped: bool, // Set once the deadline passes; the running iteration is thrown away
+    stop_flag: Option<Arc<AtomicBool>>, // Raised from another thread to end a ponder search
+    ponder: Option<PonderSearch>,
+    pv_table: Vec<Vec<Move>>, // Triangular PV table: row `ply` holds the best line found from that ply
+    excluded_root_moves: Vec<Move>, // Skipped at the root, so multi-PV can find the next best line
+    opening_book: Option<OpeningBook>,
+}
+
+/// A search running on its own thread while the opponent thinks
+struct PonderSearch {
+    stop_flag: Arc<AtomicBool>,
+    result: mpsc::Receiver<SearchResult>,
+    handle: std::thread::JoinHandle<()>,
 }
 
 impl SearchEngine {
@@ -17,44 +41,297 @@ impl SearchEngine {
             nodes_searched: 0,
             transposition_table: TranspositionTable::new(64),
             logger: None,
-            killer_moves: [[None; 2]; 128],
+            killers: vec![[None; 2]; PV_MAX_PLY],
+            history: [[0; 64]; 64],
+            config: SearchConfig::default(),
+            razored_nodes: 0,
+            fail_high: 0,
+            fail_low: 0,
+            repetition_stack: Vec::new(),
+            deadline: None,
+            stopped: false,
+            stop_flag: None,
+            ponder: None,
+            pv_table: (0..PV_MAX_PLY).map(|_| Vec::with_capacity(PV_MAX_PLY)).collect(),
+            excluded_root_moves: Vec::new(),
+            opening_book: None,
         }
     }
 
+    /// Engine searching with these parameters instead of the defaults
+    pub fn with_config(config: SearchConfig) -> Self {
+        let mut engine = Self::new();
+        engine.config = config;
+        engine
+    }
+
+    /// Play moves from this PolyGlot book while the position is in it, without searching
+    pub fn set_opening_book(&mut self, path: &std::path::Path) -> std::io::Result<()> {
+        self.opening_book = Some(OpeningBook::load(path)?);
+        Ok(())
+    }
+
     pub fn search(&mut self, board: &mut Board, depth: u32) -> SearchResult {
+        if let Some(result) = self.book_result(board) {
+            return result;
+        }
+        self.iterative_deepening(board, depth.max(1), None)
+    }
+
+    /// A book move as a search result, unless multi-PV is asking for the lines after it
+    fn book_result(&mut self, board: &Board) -> Option<SearchResult> {
+        if !self.excluded_root_moves.is_empty() {
+            return None;
+        }
+        let book_move = self.opening_book.as_ref()?.probe(board)?;
         self.nodes_searched = 0;
+        Some(SearchResult {
+            best_move: Some(book_move),
+            evaluation: 0,
+            depth: 0,
+            depth_reached: 0,
+            nodes_searched: 0,
+            fail_high: 0,
+            fail_low: 0,
+            pv_line: vec![book_move],
+        })
+    }
+
+    /// Fixed-depth search measured for speed; `nodes` counts every node of every iteration.
+    /// The transposition table carries over, so use a fresh engine for comparable numbers
+    pub fn bench(&mut self, board: &mut Board, depth: u32) -> BenchResult {
+        let start = Instant::now();
+        let result = self.search(board, depth);
+        BenchResult::new(result.nodes_searched, start.elapsed().as_millis(), self.razored_nodes)
+    }
+
+    /// Iterative deepening within a time budget. Stops starting new iterations once 60% of
+    /// the budget is used and abandons the running one at `time_ms - overhead_ms`,
+    /// returning the deepest completed iteration
+    pub fn search_timed(&mut self, board: &mut Board, time_ms: u64, overhead_ms: u64) -> SearchResult {
+        if let Some(result) = self.book_result(board) {
+            return result;
+        }
+        let start = Instant::now();
+        let budget = Duration::from_millis(time_ms.saturating_sub(overhead_ms).max(1));
+        let mut result = self.iterative_deepening(board, MAX_TIMED_DEPTH, Some((start, budget)));
+        result.depth = result.depth_reached;
+        result
+    }
+
+    /// The `num_pv` best root moves, each searched to `depth` with the moves above it excluded,
+    /// best first. Fewer come back when the position has fewer legal moves
+    pub fn search_multi_pv(&mut self, board: &mut Board, depth: u32, num_pv: usize) -> Vec<SearchResult> {
+        let mut results: Vec<SearchResult> = Vec::with_capacity(num_pv);
+        for _ in 0..num_pv {
+            let result = self.search(board, depth);
+            let Some(best_move) = result.best_move else { break };
+            self.excluded_root_moves.push(best_move);
+            results.push(result);
+        }
+        self.excluded_root_moves.clear();
+        results
+    }
+
+    /// Start searching `board` on a background thread until `stop_ponder` or `ponder_hit`
+    pub fn start_ponder(&mut self, board: Board) {
+        self.stop_ponder();
+
+        // Board isn't Send (it holds the logger), so the thread rebuilds it from the start position and moves
+        let mut replay = board;
+        replay.logger = None;
+        let moves: Vec<Move> = replay.goto_move(0).unwrap_or_default().iter().map(|game_move| game_move.mv).collect();
+        let start_fen = replay.to_fen();
+
+        let config = self.config;
+        let stop_flag = Arc::new(AtomicBool::new(false));
+        let thread_stop_flag = Arc::clone(&stop_flag);
+        let (sender, result) = mpsc::channel();
+        let handle = std::thread::spawn(move || {
+            let Ok(mut board) = Board::from_fen(&start_fen) else { return };
+            for mv in moves {
+                if board.try_make_move(mv).is_err() {
+                    return;
+                }
+            }
+            let mut engine = SearchEngine::with_config(config);
+            engine.stop_flag = Some(thread_stop_flag);
+            let _ = sender.send(engine.search_timed(&mut board, PONDER_TIME_MS, 0));
+        });
+
+        self.ponder = Some(PonderSearch { stop_flag, result, handle });
+    }
+
+    /// Stop the ponder search and return the deepest iteration it completed
+    pub fn stop_ponder(&mut self) -> Option<SearchResult> {
+        let ponder = self.ponder.take()?;
+        ponder.stop_flag.store(true, Ordering::Relaxed);
+        let result = ponder.result.recv().ok();
+        let _ = ponder.handle.join();
+        result
+    }
+
+    /// The opponent played the expected move: keep the ponder search going for `time_ms` more, then stop it
+    pub fn ponder_hit(&mut self, time_ms: u64) -> Option<SearchResult> {
+        let ponder = self.ponder.as_ref()?;
+        if let Ok(result) = ponder.result.recv_timeout(Duration::from_millis(time_ms)) {
+            // Finished on its own (every iteration done, or no legal moves)
+            let _ = self.ponder.take().map(|ponder| ponder.handle.join());
+            return Some(result);
+        }
+        self.stop_ponder()
+    }
+
+    pub fn is_pondering(&self) -> bool {
+        self.ponder.is_some()
+    }
+
+    fn iterative_deepening(&mut self, board: &mut Board, depth: u32, time_budget: Option<(Instant, Duration)>) -> SearchResult {
+        self.nodes_searched = 0;
+        self.razored_nodes = 0;
+        self.fail_high = 0;
+        self.fail_low = 0;
         self.transposition_table.new_search(); // Age increment for new search
-        let (best_move, evaluation) = self.alphabeta_root(board, depth as i32);
+        self.history_decay();
+        // Seed with the game so far so repetitions of earlier positions are seen too
+        self.repetition_stack = board.position_history.clone();
+
+        // Iterative deepening: each iteration searches the previous best move first
+        let mut last_best_move = None;
+        let mut evaluation = 0;
+        let mut depth_reached = 0;
+        let mut pv_line = Vec::new();
+        for current_depth in 1..=depth {
+            // Depth 1 always completes so there is a move to play
+            self.deadline = time_budget.filter(|_| current_depth > 1).map(|(start, budget)| start + budget);
+            self.killers.fill([None; 2]);
+            let (best_move, score) = if self.config.use_aspiration && current_depth >= ASPIRATION_MIN_DEPTH {
+                self.aspiration_search(board, current_depth as i32, last_best_move, evaluation)
+            } else {
+                self.alphabeta_root(board, current_depth as i32, last_best_move, -CHECKMATE_SCORE - 1, CHECKMATE_SCORE + 1)
+            };
+            if self.stopped {
+                break;
+            }
+            evaluation = score;
+            depth_reached = current_depth;
+            if best_move.is_none() {
+                break; // No legal moves, deeper iterations won't change that
+            }
+            last_best_move = best_move;
+            pv_line = self.complete_pv(board, self.pv_table[0].clone(), current_depth);
+
+            // The next iteration takes several times longer, so don't start one we can't finish
+            if let Some((start, budget)) = time_budget {
+                if start.elapsed().as_secs_f64() > budget.as_secs_f64() * TIME_SOFT_LIMIT {
+                    break;
+                }
+            }
+        }
+        self.deadline = None;
+        self.stopped = false;
+
         SearchResult {
-            best_move,
+            best_move: last_best_move,
             evaluation,
             depth,
+            depth_reached,
             nodes_searched: self.nodes_searched,
+            fail_high: self.fail_high,
+            fail_low: self.fail_low,
+            pv_line,
+        }
+    }
+
+    /// The triangular PV stops at transposition cutoffs, so carry it on from the table
+    fn complete_pv(&self, board: &Board, mut pv_line: Vec<Move>, depth: u32) -> Vec<Move> {
+        let mut end = board.clone();
+        end.logger = None;
+        for &mv in &pv_line {
+            if end.try_make_move(mv).is_err() {
+                return pv_line;
+            }
         }
+        let remaining = depth.saturating_sub(pv_line.len() as u32);
+        pv_line.extend(get_principal_variation(&end, &self.transposition_table, remaining));
+        pv_line
+    }
+
+    /// Root search in a narrow window around the previous score, widening the failing side until it holds
+    fn aspiration_search(&mut self, board: &mut Board, depth: i32, previous_best: Option<Move>, previous_score: i32) -> (Option<Move>, i32) {
+        let mut lower_step = 0;
+        let mut upper_step = 0;
+
+        loop {
+            // Past the last window width that side of the window is fully open
+            let width = |step: usize| ASPIRATION_WIDENING.get(step).map(|factor| factor * self.config.aspiration_window);
+            let alpha = width(lower_step).map_or(-CHECKMATE_SCORE - 1, |width| previous_score - width);
+            let beta = width(upper_step).map_or(CHECKMATE_SCORE + 1, |width| previous_score + width);
+            let (best_move, score) = self.alphabeta_root(board, depth, previous_best, alpha, beta);
+
+            if self.stopped {
+                return (best_move, score);
+            } else if score <= alpha && lower_step < ASPIRATION_WIDENING.len() {
+                self.fail_low += 1;
+                lower_step += 1;
+            } else if score >= beta && upper_step < ASPIRATION_WIDENING.len() {
+                self.fail_high += 1;
+                upper_step += 1;
+            } else {
+                return (best_move, score);
+            }
+        }
+    }
+
+    /// Convenience wrapper: set up the position from FEN and return the best move
+    pub fn best_move_for_fen(&mut self, fen: &str, depth: u8) -> Result<Option<Move>, engine::MoveError> {
+        engine::bitboard::initialize_engine();
+        let mut board = Board::from_fen(fen)?;
+        let result = self.search(&mut board, depth as u32);
+        Ok(result.best_move)
     }
 
-    fn alphabeta_root(&mut self, board: &mut Board, depth: i32) -> (Option<Move>, i32) {
+    fn alphabeta_root(&mut self, board: &mut Board, depth: i32, previous_best: Option<Move>, mut alpha: i32, beta: i32) -> (Option<Move>, i32) {
         let mut moves = board.get_all_legal_moves();
         if moves.is_empty() {
-            let eval = if board.is_in_check() { -MATE_SCORE } else { 0 };
+            let eval = if board.is_in_check() { mated_in(0) } else { 0 };
             return (None, eval);
         }
 
-        // Order moves for better alpha-beta efficiency
-        self.order_moves(board, &mut moves, depth);
+        moves.retain(|mv| !self.excluded_root_moves.contains(mv));
+        if moves.is_empty() {
+            return (None, -CHECKMATE_SCORE - 1);
+        }
+
+        // Order moves for better alpha-beta efficiency, previous iteration's best first
+        self.order_moves(board, &mut moves, 0, previous_best);
+        self.pv_table[0].clear();
         if let Some(logger) = &self.logger {
             logger.borrow_mut().log_search_start(depth as u32, moves.len());
         }
 
-        let mut best_score = -MATE_SCORE - 1;
+        let mut best_score = -CHECKMATE_SCORE - 1;
         let mut best_move = None;
-        let mut alpha = -MATE_SCORE - 1;
-        let beta = MATE_SCORE + 1;
 
+        self.repetition_stack.push(board.position_key());
+        let mut first_move = true;
         for (move_num, &mv) in moves.iter().enumerate() {
             if let Ok(_) = board.try_make_move(mv) {
-                let score = -self.alphabeta(board, depth - 1, -beta, -alpha);
+                // PVS: full window for the first move, null window for the rest
+                let mut score;
+                if first_move || !self.config.use_pvs {
+                    score = -self.alphabeta(board, depth - 1, 1, -beta, -alpha);
+                    first_move = false;
+                } else {
+                    score = -self.alphabeta(board, depth - 1, 1, -alpha - 1, -alpha);
+                    if score > alpha && score < beta {
+                        score = -self.alphabeta(board, depth - 1, 1, -beta, -alpha);
+                    }
+                }
                 if let Err(_) = board.undo_move() { break; }
+                if self.stopped {
+                    break;
+                }
 
                 // LOG: Move analysis
                 if let Some(logger) = &self.logger {
@@ -64,6 +341,7 @@ impl SearchEngine {
                 if score > best_score {
                     best_score = score;
                     best_move = Some(mv);
+                    self.update_pv(0, mv);
                     
                     // LOG: Alpha improvement
                     if let Some(logger) = &self.logger {
@@ -74,9 +352,16 @@ impl SearchEngine {
 
                     alpha = alpha.max(score);
                 }
+
+                // Fail high: only possible inside an aspiration window
+                if alpha >= beta {
+                    break;
+                }
             }
         }
 
+        self.repetition_stack.pop();
+
         // LOG: Search complete
         if let Some(logger) = &self.logger {
             logger.borrow_mut().log_search_complete(best_move, best_score, self.nodes_searched);
@@ -85,12 +370,45 @@ impl SearchEngine {
         (best_move, best_score)
     }
 
-    fn alphabeta(&mut self, board: &mut Board, depth: i32, mut alpha: i32, beta: i32) -> i32 {
+    fn alphabeta(&mut self, board: &mut Board, depth: i32, ply: usize, mut alpha: i32, mut beta: i32) -> i32 {
         self.nodes_searched += 1;
+        if let Some(row) = self.pv_table.get_mut(ply) {
+            row.clear();
+        }
+        if self.out_of_time() {
+            return 0;
+        }
+
+        // Mate distance pruning: no line from here beats mating now or being mated now
+        alpha = alpha.max(mated_in(ply));
+        beta = beta.min(CHECKMATE_SCORE - ply as i32);
+        if alpha >= beta {
+            return alpha;
+        }
+
+        // Checked before the TT, whose entries don't know about the clock. Checkmate on the
+        // hundredth half-move still counts as mate
+        if board.is_fifty_move_draw() {
+            return if board.is_checkmate() { mated_in(ply) } else { DRAW_SCORE };
+        }
+
+        // A draw already declared by the move that led here (insufficient material, say) allows
+        // no further moves. The status was set when that move was made, so this costs nothing
+        if let engine::GameStatus::Draw(_) = board.game_status {
+            return DRAW_SCORE;
+        }
+
+        // Third occurrence on the game + search path is a draw
+        let position_key = board.position_key();
+        if self.is_repetition(position_key) {
+            return DRAW_SCORE;
+        }
 
         // Probe transposition table
         let hash = self.transposition_table.get_hash(board);
-        if let Some((tt_score, tt_move)) = self.transposition_table.probe(hash, depth, alpha, beta) {
+        let tt_window = (score_to_tt(alpha, ply), score_to_tt(beta, ply));
+        if let Some((tt_score, tt_move)) = self.transposition_table.probe(hash, depth, tt_window.0, tt_window.1) {
+            let tt_score = score_from_tt(tt_score, ply);
             // LOG: Transposition table hit
             if let Some(logger) = &self.logger {
                 logger.borrow_mut().log_tt_hit(depth, depth, tt_score, tt_move);
@@ -100,28 +418,149 @@ impl SearchEngine {
         }
 
         if depth <= 0 {
+            // So quiescence never stands pat in a mated or drawn position
+            let result = board.get_game_result();
+            let eval = match result {
+                engine::GameResult::InProgress => None,
+                engine::GameResult::Draw(_) => Some(DRAW_SCORE),
+                _ => Some(mated_in(ply)),
+            };
+            if let Some(eval) = eval {
+                // A repetition depends on the path here, so it stays out of the TT
+                if result != engine::GameResult::Draw(engine::DrawReason::Repetition) {
+                    self.transposition_table.store(hash, depth, score_to_tt(eval, ply), None, NodeType::Exact);
+                }
+                return eval;
+            }
+
             // Call quiescence search instead of static evaluation
-            let eval = self.quiescence_search(board, alpha, beta);
-            self.transposition_table.store(hash, depth, eval, None, NodeType::Exact);
+            let eval = self.quiescence_search(board, ply, alpha, beta);
+            // An aborted quiescence score is made up, so it must not reach the TT
+            if self.stopped {
+                return 0;
+            }
+            // Fail-soft quiescence is only exact inside the window
+            let node_type = if eval <= alpha {
+                NodeType::UpperBound
+            } else if eval >= beta {
+                NodeType::LowerBound
+            } else {
+                NodeType::Exact
+            };
+            self.transposition_table.store(hash, depth, score_to_tt(eval, ply), None, node_type);
             return eval;
         }
 
-        let mut moves = board.get_all_legal_moves();
+        let in_check = board.is_in_check();
+
+        // Razoring: a null-window node this far behind only gets a quiescence search, and is
+        // pruned if that can't reach alpha either
+        let null_window = beta - alpha == 1;
+        let mut static_eval = None;
+        if self.config.use_razoring && !in_check && (depth == 1 || depth == 2) && null_window {
+            let margin = if depth == 1 { self.config.razor_margin } else { self.config.razor_margin_d2 };
+            if static_eval.get_or_insert_with(|| evaluate_position(board)).saturating_add(margin) < alpha {
+                let score = self.quiescence_search(board, ply, alpha, beta);
+                if self.stopped {
+                    return 0;
+                }
+                if score <= alpha {
+                    self.razored_nodes += 1;
+                    return score;
+                }
+            }
+        }
+
+        // Null move pruning: if passing still fails high, some real move will too. Not in check,
+        // twice in a row, or with only pawns left, where passing could be the best move (zugzwang)
+        let reduction = self.config.null_move_reduction as i32;
+        let after_null_move = board.move_history.last().is_some_and(|game_move| game_move.is_null_move);
+        if self.config.use_null_move && null_window && depth > reduction && !after_null_move && board.null_move_is_safe() {
+            board.apply_null_move();
+            self.repetition_stack.push(position_key);
+            let score = -self.alphabeta(board, depth - 1 - reduction, ply + 1, -beta, -beta + 1);
+            self.repetition_stack.pop();
+            if board.undo_null_move().is_ok() && !self.stopped && score >= beta {
+                // A pass can't prove a mate
+                return if score > MATE_THRESHOLD { beta } else { score };
+            }
+        }
+
+        // Futility pruning: at the frontier, quiet moves that don't give check can't lift a
+        // position this far behind up to alpha
+        let futility_score = if self.config.use_futility && !in_check && depth == 1 && null_window {
+            let eval = *static_eval.get_or_insert_with(|| evaluate_position(board));
+            Some(eval.saturating_add(self.config.futility_margin)).filter(|&score| score <= alpha)
+        } else {
+            None
+        };
+
+        let mut moves = if in_check {
+            board.generate_check_evasions()
+        } else {
+            board.get_all_legal_moves()
+        };
         if moves.is_empty() {
-            let eval = if board.is_in_check() { -MATE_SCORE } else { 0 };
-            self.transposition_table.store(hash, depth, eval, None, NodeType::Exact);
+            let eval = if in_check { mated_in(ply) } else { DRAW_SCORE };
+            self.transposition_table.store(hash, depth, score_to_tt(eval, ply), None, NodeType::Exact);
             return eval;
         }
 
-        self.order_moves(board, &mut moves, depth);
+        let hash_move = self.transposition_table.probe_move(hash);
+        self.order_moves(board, &mut moves, ply, hash_move);
         let original_alpha = alpha;
         let mut best_move = None;
-        let mut best_score = -MATE_SCORE - 1; // For fail-soft
+        let mut best_score = -CHECKMATE_SCORE - 1; // For fail-soft
+
+        self.repetition_stack.push(position_key);
+        let mut quiet_moves_searched = 0;
+        let mut first_move = true;
+        for (move_index, &mv) in moves.iter().enumerate() {
+            let is_quiet = !is_capture(board, mv) && mv.promotion.is_none();
+            // Late quiet moves that aren't the hash move or a killer get a reduced search
+            let reducible = self.config.use_lmr &&
+                depth >= self.config.lmr_min_depth as i32 &&
+                board.can_apply_lmr(mv, depth as u8, quiet_moves_searched) &&
+                Some(mv) != hash_move &&
+                !self.is_killer(mv, ply);
+            if is_quiet {
+                quiet_moves_searched += 1;
+            }
 
-        for &mv in &moves {
             if let Ok(_) = board.try_make_move(mv) {
-                let score = -self.alphabeta(board, depth - 1, -beta, -alpha);
+                if let Some(futility_score) = futility_score.filter(|_| is_quiet && !first_move && !board.is_in_check()) {
+                    if board.undo_move().is_err() { break; }
+                    best_score = best_score.max(futility_score);
+                    continue;
+                }
+
+                let mut score;
+                if first_move || !self.config.use_pvs {
+                    // PVS: the first move after ordering gets the full window
+                    score = -self.alphabeta(board, depth - 1, ply + 1, -beta, -alpha);
+                    first_move = false;
+                } else {
+                    // Null window [alpha, alpha + 1] only proves the move is no better
+                    score = alpha + 1;
+                    if reducible && !board.is_in_check() {
+                        let reduced_depth = (depth - 1 - engine::bitboard::lmr_reduction(depth, move_index)).max(0);
+                        score = -self.alphabeta(board, reduced_depth, ply + 1, -alpha - 1, -alpha);
+                    }
+                    // Unreduced null-window search if LMR was skipped or looked promising
+                    if score > alpha {
+                        score = -self.alphabeta(board, depth - 1, ply + 1, -alpha - 1, -alpha);
+                    }
+                    // Full-window re-search if the move may be a new best
+                    if score > alpha && score < beta {
+                        score = -self.alphabeta(board, depth - 1, ply + 1, -beta, -alpha);
+                    }
+                }
                 if let Err(_) = board.undo_move() { break; }
+                if self.stopped {
+                    // Scores below an abandoned node are meaningless, keep them out of the TT
+                    self.repetition_stack.pop();
+                    return 0;
+                }
 
                 // Fail-soft: update best_score regardless of alpha/beta bounds
                 if score > best_score {
@@ -131,6 +570,7 @@ impl SearchEngine {
 
                 if score > alpha {
                     alpha = score;
+                    self.update_pv(ply, mv);
                 }
 
                 if alpha >= beta {
@@ -139,18 +579,21 @@ impl SearchEngine {
                         logger.borrow_mut().log_beta_cutoff(beta, score, mv);
                     }
 
-                    let to_piece = board.get_piece(mv.to);
-                    if engine::types::is_empty(to_piece) {
-                        self.store_killer_move(mv, depth);
+                    if !is_capture(board, mv) && mv.promotion.is_none() {
+                        self.store_killer_move(mv, ply);
+                        self.update_history(mv, depth * depth);
                     }
 
                     // Fail-soft: return best_score instead of beta
-                    self.transposition_table.store(hash, depth, best_score, best_move, NodeType::LowerBound);
+                    self.repetition_stack.pop();
+                    self.transposition_table.store(hash, depth, score_to_tt(best_score, ply), best_move, NodeType::LowerBound);
                     return best_score;
                 }
             }
         }
 
+        self.repetition_stack.pop();
+
         let node_type = if alpha <= original_alpha {
             NodeType::UpperBound
         } else {
@@ -158,56 +601,66 @@ impl SearchEngine {
         };
 
         // Fail-soft: return best_score instead of alpha
-        self.transposition_table.store(hash, depth, best_score, best_move, node_type);
+        self.transposition_table.store(hash, depth, score_to_tt(best_score, ply), best_move, node_type);
         best_score
     }
 
-    fn order_moves(&self, board: &Board, moves: &mut Vec<Move>, depth: i32) {
+    fn order_moves(&self, board: &Board, moves: &mut Vec<Move>, ply: usize, hash_move: Option<Move>) {
         moves.sort_by_key(|&mv| {
-            let from_piece = board.get_piece(mv.from);
-            let to_piece = board.get_piece(mv.to);
             let mut score = 0;
 
-            // 1. Hash move (handled separately in search - highest priority)
-
-            // 2. Captures: MVV-LVA (Most Valuable Victim - Least Valuable Attacker)
-            if !engine::types::is_empty(to_piece) {
-                let victim_value = PIECE_VALUES[engine::types::piece_type(to_piece) as usize];
-                let attacker_value = PIECE_VALUES[engine::types::piece_type(from_piece) as usize];
-                score += 10000 + victim_value - attacker_value;
+            // 1. Hash move always first
+            if Some(mv) == hash_move {
+                score += 100000;
             }
-            // 3. Promotions
-            else if engine::types::piece_type(from_piece) == engine::types::PAWN {
-                let from_rank = mv.from.rank();
-                let to_rank = mv.to.rank();
-                if (from_rank == 6 && to_rank == 7) || (from_rank == 1 && to_rank == 0) {
-                    score += 9000;
+            // 2. Promotions, above any capture
+            else if let Some(promotion) = mv.promotion {
+                score += 20000 + PIECE_VALUES[promotion as usize];
+            }
+            // 3. Captures: MVV-LVA (Most Valuable Victim - Least Valuable Attacker);
+            // ones that lose material in the exchange go after the quiet moves
+            else if is_capture(board, mv) {
+                if board.see(mv) >= 0 {
+                    score += 10000 + mvv_lva_score(board, mv);
+                } else {
+                    score += BAD_CAPTURE_SCORE + mvv_lva_score(board, mv);
                 }
             }
             // 4. Killer moves (for non-captures)
-            else if depth >= 0 && depth < 128 {
-                let depth_idx = depth as usize;
-                if let Some(killer1) = self.killer_moves[depth_idx][0] {
-                    if killer1 == mv {
-                        score += 8000; // First killer gets higher priority
-                    }
-                }
-                if let Some(killer2) = self.killer_moves[depth_idx][1] {
-                    if killer2 == mv {
-                        score += 7000; // Second killer gets lower priority
-                    }
+            else if ply < self.killers.len() {
+                if self.killers[ply][0] == Some(mv) {
+                    score += 8000; // First killer gets higher priority
+                } else if self.killers[ply][1] == Some(mv) {
+                    score += 7000; // Second killer gets lower priority
+                } else if self.config.use_history {
+                    score += self.history_score(mv);
                 }
             }
+            // 5. Remaining quiet moves by history
+            else if self.config.use_history {
+                score += self.history_score(mv);
+            }
 
             -score // Negative because sort_by_key sorts ascending, we want descending
         });
     }
 
-    fn quiescence_search(&mut self, board: &mut Board, mut alpha: i32, beta: i32) -> i32 {
+    fn quiescence_search(&mut self, board: &mut Board, ply: usize, mut alpha: i32, beta: i32) -> i32 {
         self.nodes_searched += 1;
+        // Quiescence nodes count towards the check interval too, so the clock is read here as well
+        if self.out_of_time() {
+            return 0;
+        }
+
+        if board.is_fifty_move_draw() {
+            return if board.is_checkmate() { mated_in(ply) } else { DRAW_SCORE };
+        }
 
         // Stand pat - evaluate current position
         let stand_pat = evaluate_position(board);
+        if stand_pat == -CHECKMATE_SCORE {
+            return mated_in(ply);
+        }
         
         // Fail-soft: we can return scores outside [alpha, beta]
         if stand_pat >= beta {
@@ -218,22 +671,42 @@ impl SearchEngine {
             alpha = stand_pat;
         }
 
-        // Get only capture moves
-        let captures = self.get_capture_moves(board);
-
-        // Delta pruning - skip captures that can't improve position significantly
-        let big_delta = 900; // Queen value - largest possible material gain
-        if stand_pat + big_delta < alpha {
+        // Delta pruning - even winning a queen can't reach alpha
+        if DELTA_PRUNING && stand_pat + PIECE_VALUES[engine::types::QUEEN as usize] + DELTA_MARGIN < alpha {
             return stand_pat; // Fail-soft: return actual score
         }
 
+        // Only losing captures left, so the static evaluation stands
+        if board.is_quiet_position() {
+            return stand_pat;
+        }
+
+        // Get only capture moves
+        let captures = self.get_capture_moves(board);
+
         let mut best_score = stand_pat; // For fail-soft
 
         // Search captures
         for &mv in &captures {
+            // Delta pruning per capture - skip if this victim can't raise alpha
+            if DELTA_PRUNING && mv.promotion.is_none() {
+                // The pawn taken en passant isn't on the target square
+                let victim = if board.is_en_passant_move(mv) {
+                    engine::types::PAWN
+                } else {
+                    engine::types::piece_type(board.get_piece(mv.to))
+                };
+                if stand_pat + PIECE_VALUES[victim as usize] + DELTA_MARGIN < alpha {
+                    continue;
+                }
+            }
+
             if let Ok(_) = board.try_make_move(mv) {
-                let score = -self.quiescence_search(board, -beta, -alpha);
+                let score = -self.quiescence_search(board, ply + 1, -beta, -alpha);
                 if let Err(_) = board.undo_move() { break; }
+                if self.stopped {
+                    return 0;
+                }
 
                 // Fail-soft: track best score
                 if score > best_score {
@@ -253,37 +726,130 @@ impl SearchEngine {
         best_score // Fail-soft: return best score found
     }
 
+    /// Captures for quiescence, most valuable victim first. Quiet promotions are left out:
+    /// a free promotion at the horizon makes putting it off look as good as promoting now
     fn get_capture_moves(&self, board: &Board) -> Vec<Move> {
-        board.get_all_legal_moves()
-            .into_iter()
-            .filter(|&mv| {
-                let to_piece = board.get_piece(mv.to);
-                !engine::types::is_empty(to_piece) // Only captures
-            })
-            .collect()
+        let mut captures = board.get_all_captures();
+        captures.sort_by_key(|&mv| -mvv_lva_score(board, mv));
+        captures
     }
 
     pub fn set_logger(&mut self, logger: std::rc::Rc<std::cell::RefCell<engine::ChessLogger>>) {
         self.logger = Some(logger);
     }
 
-    fn store_killer_move(&mut self, mv: Move, depth: i32) {
-        if depth < 0 || depth >= 128 {
+    /// Check the clock (and the stop flag, less often) every few nodes and latch `stopped`
+    /// once the deadline has passed or the flag is raised
+    fn out_of_time(&mut self) -> bool {
+        if !self.stopped && self.nodes_searched.is_multiple_of(TIME_CHECK_INTERVAL) {
+            if let Some(deadline) = self.deadline {
+                self.stopped = Instant::now() >= deadline;
+                if self.nodes_searched.is_multiple_of(STOP_CHECK_INTERVAL) {
+                    self.stopped |= self.stop_flag.as_ref().is_some_and(|flag| flag.load(Ordering::Relaxed));
+                }
+            }
+        }
+        self.stopped
+    }
+
+    /// New best move at `ply`: its line is the move followed by the line found below it
+    fn update_pv(&mut self, ply: usize, mv: Move) {
+        if ply >= self.pv_table.len() {
             return;
         }
+        let (row, rest) = self.pv_table[ply..].split_at_mut(1);
+        row[0].clear();
+        row[0].push(mv);
+        if let Some(child) = rest.first() {
+            row[0].extend_from_slice(child);
+        }
+    }
 
-        let depth_idx = depth as usize;
-        // Don't store if it's already the first killer
-        if let Some(first_killer) = self.killer_moves[depth_idx][0] {
-            if first_killer == mv {
-                return;
+    fn is_repetition(&self, position_key: u64) -> bool {
+        self.repetition_stack.iter().filter(|&&key| key == position_key).count() >= 2
+    }
+
+    /// History score for ordering, kept below the killers by `update_history`
+    fn history_score(&self, mv: Move) -> i32 {
+        self.history[mv.from.0 as usize][mv.to.0 as usize]
+    }
+
+    /// Gravity update: entries move toward the bonus's sign and never leave ±HISTORY_MAX
+    fn update_history(&mut self, mv: Move, bonus: i32) {
+        let bonus = bonus.clamp(-HISTORY_MAX, HISTORY_MAX);
+        let entry = &mut self.history[mv.from.0 as usize][mv.to.0 as usize];
+        *entry += bonus - *entry * bonus.abs() / HISTORY_MAX;
+    }
+
+    /// Halve every history entry so old searches fade
+    fn history_decay(&mut self) {
+        for row in self.history.iter_mut() {
+            for entry in row.iter_mut() {
+                *entry /= 2;
             }
         }
+    }
+
+    fn is_killer(&self, mv: Move, ply: usize) -> bool {
+        ply < self.killers.len() && self.killers[ply].contains(&Some(mv))
+    }
+
+    fn store_killer_move(&mut self, mv: Move, ply: usize) {
+        if !self.config.use_killer_moves || ply >= self.killers.len() {
+            return;
+        }
+
+        // Don't store if it's already the first killer
+        if self.killers[ply][0] == Some(mv) {
+            return;
+        }
+
+        // Shift killers: first becomes second, new move becomes first
+        self.killers[ply][1] = self.killers[ply][0];
+        self.killers[ply][0] = Some(mv);
+    }
+}
+
+/// Expected line from `board`, following the best moves stored in `tt` for up to `max_depth`
+/// plies. Stops at positions without a stored move, game-ending positions and repeated
+/// positions; a stored move that isn't legal here (a key collision) ends the line too
+pub fn get_principal_variation(board: &Board, tt: &TranspositionTable, max_depth: u32) -> Vec<Move> {
+    let mut board = board.clone();
+    board.logger = None;
+    let mut visited = std::collections::HashSet::new();
+    let mut pv = Vec::new();
+
+    while pv.len() < max_depth as usize && visited.insert(tt.get_hash(&board)) {
+        let Some(mv) = tt.probe_move(tt.get_hash(&board)) else { break };
+        if board.is_terminal() || !board.get_all_legal_moves().contains(&mv) || board.try_make_move(mv).is_err() {
+            break;
+        }
+        pv.push(mv);
+    }
+    pv
+}
 
-        // Shift killers: second becomes first, new move becomes first
-        self.killer_moves[depth_idx][1] = self.killer_moves[depth_idx][0];
-        self.killer_moves[depth_idx][0] = Some(mv);
+/// Piece values in pawns, as used for MVV-LVA ordering
+const MVV_LVA_VALUES: [i32; 7] = [0, 1, 3, 3, 5, 9, 0];
+
+/// Capture ordering score: `10 * victim - attacker`, 0 for quiet moves
+pub fn mvv_lva_score(board: &Board, mv: Move) -> i32 {
+    let victim = if board.is_en_passant_move(mv) {
+        engine::types::PAWN
+    } else {
+        engine::types::piece_type(board.get_piece(mv.to))
+    };
+    if victim == engine::types::EMPTY {
+        return 0;
     }
+    let attacker = engine::types::piece_type(board.get_piece(mv.from));
+    10 * MVV_LVA_VALUES[victim as usize] - MVV_LVA_VALUES[attacker as usize]
+}
+
+fn is_capture(board: &Board, mv: Move) -> bool {
+    // Chess960 castling lands on our own rook
+    let target = board.get_piece(mv.to);
+    (!engine::types::is_empty(target) && engine::types::piece_color(target) != board.current_turn) || board.is_en_passant_move(mv)
 }
 
 impl Default for SearchEngine {
@@ -291,3 +857,25 @@ impl Default for SearchEngine {
         Self::new()
     }
 }
+
+// Don't leave a ponder thread searching after its engine is gone
+impl Drop for SearchEngine {
+    fn drop(&mut self) {
+        self.stop_ponder();
+    }
+}
+
+/// Convenience search on a board without managing a `SearchEngine`
+pub trait BestMoveCandidate {
+    fn best_move_candidate(&self, depth: u8) -> Option<Move>;
+}
+
+impl BestMoveCandidate for Board {
+    /// Searches a clone with a fresh engine, so no TT or killer state leaks between calls
+    #[inline(never)]
+    fn best_move_candidate(&self, depth: u8) -> Option<Move> {
+        let mut board = self.clone();
+        let mut search_engine = SearchEngine::new();
+        search_engine.search(&mut board, depth as u32).best_move
+    }
+}
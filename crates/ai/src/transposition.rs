@@ -1,5 +1,5 @@
-use engine::{Board, Move, Square, types::*};
-use std::collections::HashMap;
+use engine::{Board, Move};
+use crate::types::MATE_THRESHOLD;
 
 /// Type of transposition table entry
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -10,125 +10,84 @@ pub enum NodeType {
 }
 
 /// Transposition table entry
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy)]
 pub struct TTEntry {
     pub zobrist_key: u64,
     pub depth: i32,
     pub score: i32,
     pub best_move: Option<Move>,
-    pub node_type: NodeType,
-    pub age: u8, // For replacement strategy
+    pub node_type: NodeType, // Bound type of `score`
+    pub generation: u8,      // Search that stored it, for replacement
 }
 
-/// Zobrist hash keys for position hashing
-pub struct ZobristKeys {
-    pieces: [[[u64; 8]; 8]; 12], // [piece_type][file][rank]
-    side_to_move: u64,
-    castling_rights: [u64; 16],
-    en_passant: [u64; 8], // by file
+/// Two-tier bucket: a depth-preferred slot that keeps valuable deep entries,
+/// and an always-replace slot that always takes whatever the first one rejected
+#[derive(Debug, Clone, Copy, Default)]
+struct TTBucket {
+    depth_preferred: Option<TTEntry>,
+    always_replace: Option<TTEntry>,
 }
 
-impl ZobristKeys {
-    pub fn new() -> Self {
-        
-        let mut keys = ZobristKeys {
-            pieces: [[[0; 8]; 8]; 12],
-            side_to_move: 0,
-            castling_rights: [0; 16],
-            en_passant: [0; 8],
-        };
-        
-        let mut counter = 1u64;
-        
-        // Generate piece keys
-        for piece in 0..12 {
-            for file in 0..8 {
-                for rank in 0..8 {
-                    keys.pieces[piece][file][rank] = counter;
-                    counter = counter.wrapping_mul(1103515245).wrapping_add(12345);
-                }
-            }
-        }
-        
-        // Generate other keys
-        keys.side_to_move = counter;
-        counter = counter.wrapping_mul(1103515245).wrapping_add(12345);
-        
-        for i in 0..16 {
-            keys.castling_rights[i] = counter;
-            counter = counter.wrapping_mul(1103515245).wrapping_add(12345);
-        }
-        
-        for i in 0..8 {
-            keys.en_passant[i] = counter;
-            counter = counter.wrapping_mul(1103515245).wrapping_add(12345);
-        }
-        
-        keys
+/// Mate scores are stored relative to the node rather than the root, so a transposition
+/// reached at a different ply still reports the right distance to mate
+pub fn score_to_tt(score: i32, ply: usize) -> i32 {
+    if score > MATE_THRESHOLD {
+        score + ply as i32
+    } else if score < -MATE_THRESHOLD {
+        score - ply as i32
+    } else {
+        score
     }
-    
-    pub fn hash_position(&self, board: &Board) -> u64 {
-        let mut hash = 0u64;
-        
-        // Hash pieces
-        for rank in 0..8 {
-            for file in 0..8 {
-                let square = Square::new(file, rank);
-                let piece = board.get_piece(square);
-                
-                if !is_empty(piece) {
-                    let piece_type = piece_type(piece);
-                    let piece_color = piece_color(piece);
-                    let piece_index = (piece_type - 1) as usize + if piece_color == WHITE { 0 } else { 6 };
-                    hash ^= self.pieces[piece_index][file as usize][rank as usize];
-                }
-            }
-        }
-        
-        // Hash side to move
-        if board.current_turn == BLACK {
-            hash ^= self.side_to_move;
-        }
-        
-        // Hash castling rights
-        hash ^= self.castling_rights[board.castling_rights as usize & 15];
-        
-        // Hash en passant
-        if let Some(en_passant_square) = board.en_passant_target {
-            hash ^= self.en_passant[en_passant_square.file() as usize];
-        }
-        
-        hash
+}
+
+/// Inverse of `score_to_tt`
+pub fn score_from_tt(score: i32, ply: usize) -> i32 {
+    if score > MATE_THRESHOLD {
+        score - ply as i32
+    } else if score < -MATE_THRESHOLD {
+        score + ply as i32
+    } else {
+        score
     }
 }
 
 /// Transposition Table
 pub struct TranspositionTable {
-    table: HashMap<u64, TTEntry>,
-    zobrist: ZobristKeys,
-    age: u8,
-    max_size: usize,
+    buckets: Vec<TTBucket>,
+    current_generation: u8,
 }
 
 impl TranspositionTable {
     pub fn new(size_mb: usize) -> Self {
-        let entries_per_mb = 1024 * 1024 / std::mem::size_of::<TTEntry>();
-        let max_size = size_mb * entries_per_mb;
+        let buckets_per_mb = 1024 * 1024 / std::mem::size_of::<TTBucket>();
+        let bucket_count = (size_mb * buckets_per_mb).max(1);
         
         Self {
-            table: HashMap::with_capacity(max_size),
-            zobrist: ZobristKeys::new(),
-            age: 0,
-            max_size,
+            buckets: vec![TTBucket::default(); bucket_count],
+            current_generation: 0,
         }
     }
     
+    /// Entries are keyed by the board's incrementally maintained Zobrist hash
     pub fn get_hash(&self, board: &Board) -> u64 {
-        self.zobrist.hash_position(board)
+        board.position_hash
+    }
+
+    fn bucket_index(&self, hash: u64) -> usize {
+        (hash % self.buckets.len() as u64) as usize
+    }
+
+    fn find(&self, hash: u64) -> Option<&TTEntry> {
+        let bucket = &self.buckets[self.bucket_index(hash)];
+        [&bucket.depth_preferred, &bucket.always_replace]
+            .into_iter()
+            .flatten()
+            .find(|entry| entry.zobrist_key == hash)
     }
     
+    /// Score usable at this depth and window: exact, or a bound that already causes a cutoff
     pub fn probe(&self, hash: u64, depth: i32, alpha: i32, beta: i32) -> Option<(i32, Option<Move>)> {
-        if let Some(entry) = self.table.get(&hash) {
+        if let Some(entry) = self.find(hash) {
             if entry.depth >= depth {
                 match entry.node_type {
                     NodeType::Exact => return Some((entry.score, entry.best_move)),
@@ -137,53 +96,71 @@ impl TranspositionTable {
                     _ => {}
                 }
             }
-            // Return best move even if depth is insufficient
-            return Some((entry.score, entry.best_move));
         }
         None
     }
     
+    /// Best move stored for this position at any depth, for move ordering
+    pub fn probe_move(&self, hash: u64) -> Option<Move> {
+        self.find(hash).and_then(|entry| entry.best_move)
+    }
+    
     pub fn store(&mut self, hash: u64, depth: i32, score: i32, best_move: Option<Move>, node_type: NodeType) {
-        // Replacement strategy: always replace if table not full, or replace older/shallower entries
-        let should_replace = if let Some(existing) = self.table.get(&hash) {
-            depth >= existing.depth || existing.age < self.age
-        } else {
-            true
+        let current_generation = self.current_generation;
+        let index = self.bucket_index(hash);
+        let bucket = &mut self.buckets[index];
+
+        // Keep the old move when re-storing a position without one
+        let previous_move = [&bucket.depth_preferred, &bucket.always_replace]
+            .into_iter()
+            .flatten()
+            .find(|entry| entry.zobrist_key == hash)
+            .and_then(|entry| entry.best_move);
+
+        let entry = TTEntry {
+            zobrist_key: hash,
+            depth,
+            score,
+            best_move: best_move.or(previous_move),
+            node_type,
+            generation: current_generation,
         };
-        
-        if should_replace {
-            // Clear old entries if table is getting too large
-            if self.table.len() >= self.max_size {
-                self.clear_old_entries();
+
+        // Depth-preferred slot: take it if empty, same position, stale, or not deeper than us
+        let replace_preferred = match &bucket.depth_preferred {
+            None => true,
+            Some(existing) => {
+                existing.zobrist_key == hash ||
+                existing.generation != current_generation ||
+                depth >= existing.depth
+            }
+        };
+
+        if replace_preferred {
+            bucket.depth_preferred = Some(entry);
+            // Don't leave a stale copy of the same position behind
+            if bucket.always_replace.is_some_and(|other| other.zobrist_key == hash) {
+                bucket.always_replace = None;
             }
-            
-            let entry = TTEntry {
-                zobrist_key: hash,
-                depth,
-                score,
-                best_move,
-                node_type,
-                age: self.age,
-            };
-            
-            self.table.insert(hash, entry);
+        } else {
+            bucket.always_replace = Some(entry);
         }
     }
     
+    /// Start a new search generation; older entries become replaceable
     pub fn new_search(&mut self) {
-        self.age = self.age.wrapping_add(1);
-    }
-    
-    fn clear_old_entries(&mut self) {
-        let old_age = self.age.wrapping_sub(2);
-        self.table.retain(|_, entry| entry.age > old_age);
+        self.current_generation = self.current_generation.wrapping_add(1);
     }
     
     pub fn clear(&mut self) {
-        self.table.clear();
+        self.buckets.fill(TTBucket::default());
+        self.current_generation = 0;
     }
     
+    /// Number of stored entries
     pub fn size(&self) -> usize {
-        self.table.len()
+        self.buckets.iter()
+            .map(|bucket| bucket.depth_preferred.is_some() as usize + bucket.always_replace.is_some() as usize)
+            .sum()
     }
 }
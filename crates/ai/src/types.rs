@@ -6,7 +6,67 @@ pub struct SearchResult {
     pub best_move: Option<Move>,
     pub evaluation: i32,
     pub depth: u32,
+    pub depth_reached: u32, // Deepest completed iteration
     pub nodes_searched: u64,
+    pub fail_high: u32, // Aspiration window re-searches
+    pub fail_low: u32,
+    pub pv_line: Vec<Move>, // Expected continuation, starting with best_move
+}
+
+/// Search parameters, adjustable at run time for tuning. `Default` holds the tuned values
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SearchConfig {
+    pub use_null_move: bool, // Off by default until the pruning has been reviewed on its own
+    pub null_move_reduction: u32,
+    pub use_lmr: bool,
+    pub lmr_min_depth: u32,
+    pub use_futility: bool, // Off by default, like null move
+    pub futility_margin: i32,
+    pub use_razoring: bool,
+    pub razor_margin: i32, // At depth 1
+    pub razor_margin_d2: i32, // At depth 2
+    pub use_aspiration: bool,
+    pub aspiration_window: i32,
+    pub use_killer_moves: bool,
+    pub use_history: bool,
+    pub use_pvs: bool, // LMR rides on the null-window searches, so it goes off with PVS
+}
+
+impl Default for SearchConfig {
+    fn default() -> Self {
+        Self {
+            use_null_move: false,
+            null_move_reduction: NULL_MOVE_REDUCTION,
+            use_lmr: true,
+            lmr_min_depth: LMR_MIN_DEPTH,
+            use_futility: false,
+            futility_margin: FUTILITY_MARGIN,
+            use_razoring: true,
+            razor_margin: RAZOR_MARGIN,
+            razor_margin_d2: RAZOR_MARGIN_D2,
+            use_aspiration: true,
+            aspiration_window: ASPIRATION_WINDOW,
+            use_killer_moves: true,
+            use_history: true,
+            use_pvs: true,
+        }
+    }
+}
+
+/// Search size and speed from `SearchEngine::bench`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BenchResult {
+    pub nodes: u64,
+    pub time_ms: u128,
+    pub nps: u64,
+    pub razored_nodes: u64, // Nodes cut by razoring, for tuning the margins
+}
+
+impl BenchResult {
+    pub fn new(nodes: u64, time_ms: u128, razored_nodes: u64) -> Self {
+        let nps = (nodes as u128 * 1000).checked_div(time_ms).unwrap_or(0) as u64;
+        Self { nodes, time_ms, nps, razored_nodes }
+    }
 }
 
 /// Basic piece values for evaluation
@@ -20,9 +80,107 @@ pub const PIECE_VALUES: [i32; 7] = [
     20000, // King
 ];
 
-/// Evaluation constants
-pub const MATE_SCORE: i32 = 100000;
+/// Evaluation constants. A mate `ply` half-moves from the root scores `CHECKMATE_SCORE - ply`
+pub const CHECKMATE_SCORE: i32 = 1_000_000;
 pub const DRAW_SCORE: i32 = 0;
+/// Scores beyond this are forced mates rather than material
+pub const MATE_THRESHOLD: i32 = 900_000;
+
+/// Score for the side to move when it is checkmated `ply` half-moves from the root
+pub fn mated_in(ply: usize) -> i32 {
+    -(CHECKMATE_SCORE - ply as i32)
+}
+
+/// Full moves until mate for a mate score, negative when the side to move is getting mated
+pub fn mate_in_moves(score: i32) -> Option<i32> {
+    if score.abs() <= MATE_THRESHOLD {
+        return None;
+    }
+    let moves = ((CHECKMATE_SCORE - score.abs() + 1) / 2).max(1);
+    Some(if score < 0 { -moves } else { moves })
+}
+
+/// Below this score the side to move is considered lost and starts looking for stalemate
+pub const LOSING_THRESHOLD: i32 = -500;
+pub const STALEMATE_TRAP_BONUS: i32 = 300;
+
+/// Passed pawn bonuses indexed by rank from the pawn owner's side (0 = own back rank)
+pub const MIDDLEGAME_PASSED_PAWN_BONUS: [i32; 8] = [0, 5, 5, 10, 15, 25, 40, 0];
+pub const ENDGAME_PASSED_PAWN_BONUS: [i32; 8] = [0, 10, 15, 25, 45, 75, 120, 0];
+
+/// King safety: penalty per missing shield pawn, and per enemy attacker of the king's neighbourhood by piece type
+pub const PAWN_SHIELD_PENALTY: i32 = 15;
+pub const KING_ATTACKER_PENALTY: [i32; 7] = [0, 5, 10, 10, 15, 25, 0];
+/// King safety only counts while at least this much of the game phase remains
+pub const KING_SAFETY_MIN_PHASE: f32 = 0.3;
+
+/// Mobility bonus indexed [piece_type - 1][pseudo-legal move count]; pawns and kings score nothing
+pub const MOBILITY_BONUS: [[i32; 28]; 6] = [
+    [0; 28],
+    // Knight: a trapped knight is nearly useless, a centralised one dominates
+    [-25, -15, -6, 0, 5, 10, 14, 18, 21, 21, 21, 21, 21, 21, 21, 21, 21, 21, 21, 21, 21, 21, 21, 21, 21, 21, 21, 21],
+    [-20, -12, -5, 0, 4, 8, 11, 14, 16, 18, 20, 21, 22, 23, 23, 23, 23, 23, 23, 23, 23, 23, 23, 23, 23, 23, 23, 23],
+    [-15, -10, -6, -3, 0, 2, 4, 6, 8, 10, 11, 12, 13, 14, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15],
+    // Queen: mobility matters least relative to its value
+    [-10, -8, -6, -5, -4, -3, -2, -1, 0, 1, 2, 3, 4, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 10, 10, 10, 10],
+    [0; 28],
+];
+
+/// Bonus for owning bishops on both square colors
+pub const BISHOP_PAIR_BONUS: i32 = 50;
+/// Penalty per own pawn sharing a bishop's square color, once most of them do
+pub const BAD_BISHOP_PENALTY: i32 = 4;
+
+/// Knight on a pawn-defended square no enemy pawn can ever attack, scaled by KNIGHT_OUTPOST_PST
+pub const KNIGHT_OUTPOST_BONUS: i32 = 30;
+
+/// Rook bonuses for files without own pawns, the seventh rank, and a pair of rooks that
+/// see each other along a rank or file
+pub const ROOK_OPEN_FILE: i32 = 20;
+pub const ROOK_SEMI_OPEN_FILE: i32 = 10;
+pub const ROOK_SEVENTH_BONUS: i32 = 20;
+pub const CONNECTED_ROOKS_BONUS: i32 = 15;
+
+/// Quiescence delta pruning: skip captures that can't lift the score within this margin of alpha
+pub const DELTA_PRUNING: bool = true;
+pub const DELTA_MARGIN: i32 = 200;
+
+/// Razoring: at depth 1 and 2, a static evaluation this far below alpha goes straight to
+/// quiescence
+pub const RAZOR_MARGIN: i32 = 300;
+pub const RAZOR_MARGIN_D2: i32 = 550;
+
+/// Null move pruning searches the pass this many plies shallower
+pub const NULL_MOVE_REDUCTION: u32 = 2;
+/// Quiet moves at depth 1 are skipped when the static evaluation plus this can't reach alpha
+pub const FUTILITY_MARGIN: i32 = 150;
+/// Late move reductions start at this depth
+pub const LMR_MIN_DEPTH: u32 = 3;
+
+/// History entries never leave this bound, which sits below the second killer's ordering score
+pub const HISTORY_MAX: i32 = 6999;
+
+/// Ordering base for captures that lose material by SEE, below every quiet move's history score
+pub const BAD_CAPTURE_SCORE: i32 = -20000;
+
+/// First aspiration window half-width; failing searches widen it to 3x, then 10x, then fully
+pub const ASPIRATION_WINDOW: i32 = 25;
+pub const ASPIRATION_WIDENING: [i32; 3] = [1, 3, 10];
+/// Shallower iterations always use the full window
+pub const ASPIRATION_MIN_DEPTH: u32 = 4;
 
 /// Maximum search depth
 pub const MAX_DEPTH: u32 = 8;
+
+/// Iteration cap for timed searches, which normally stop on the clock first
+pub const MAX_TIMED_DEPTH: u32 = 64;
+/// Timed searches only start another iteration within this fraction of the budget
+pub const TIME_SOFT_LIMIT: f64 = 0.6;
+/// How many nodes pass between clock checks
+pub const TIME_CHECK_INTERVAL: u64 = 1024;
+/// Plies kept in the principal variation table, enough for the deepest timed iteration
+pub const PV_MAX_PLY: usize = MAX_TIMED_DEPTH as usize + 1;
+/// How many nodes pass between checks of a ponder search's stop flag
+pub const STOP_CHECK_INTERVAL: u64 = 4096;
+/// Budget for ponder searches, which run until they are stopped
+pub const PONDER_TIME_MS: u64 = 24 * 60 * 60 * 1000;
@@ -0,0 +1,218 @@
+use ai::evaluation::*;
+use ai::piece_square_tables::*;
+use engine::Board;
+
+#[test]
+fn test_starting_position_pst_is_symmetric() {
+    engine::bitboard::initialize_engine();
+    let board = Board::new();
+
+    assert_eq!(pst_score(&board), 0);
+    assert_eq!(pst_score_tapered(&board, &MIDDLEGAME_PST, &ENDGAME_PST), 0);
+
+    let breakdown = evaluation_breakdown(&board);
+    assert_eq!(breakdown.pst_mg, 0);
+    assert_eq!(breakdown.pst_eg, 0);
+    assert_eq!(breakdown.material, 0);
+}
+
+#[test]
+fn test_tapered_evaluation_follows_game_phase() {
+    engine::bitboard::initialize_engine();
+
+    let start = Board::new();
+    assert_eq!(game_phase(&start), 1.0);
+    assert_eq!(pst_score_tapered(&start, &MIDDLEGAME_PST, &ENDGAME_PST), pst_score_with(&start, &MIDDLEGAME_PST));
+
+    let queens_only = Board::from_fen("3qk3/8/8/8/8/8/8/3QK3 w - - 0 1").unwrap();
+    assert_eq!(game_phase(&queens_only), 8.0 / 24.0);
+
+    // With only pawns left the king belongs in the centre
+    let central = Board::from_fen("7k/8/8/8/3K4/8/4P3/8 w - - 0 1").unwrap();
+    let cornered = Board::from_fen("7k/8/8/8/8/8/4P3/K7 w - - 0 1").unwrap();
+    assert_eq!(game_phase(&central), 0.0);
+    assert!(pst_score(&central) > pst_score(&cornered));
+
+    // With the pieces still on it should stay home
+    let castled = Board::from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQ1RK1 w kq - 0 1").unwrap();
+    let marching = Board::from_fen("rnbqkbnr/pppppppp/8/8/4K3/8/PPPPPPPP/RNBQ1R2 w kq - 0 1").unwrap();
+    assert!(pst_score(&castled) > pst_score(&marching));
+}
+
+#[test]
+fn test_passed_pawns() {
+    engine::bitboard::initialize_engine();
+
+    // d5 is passed; b2 is blocked by b7 and f2 is guarded by g7
+    let board = Board::from_fen("4k3/1p4p1/8/3P4/8/8/1P3P2/4K3 w - - 0 1").unwrap();
+    assert_eq!(count_passed_pawns(&board, engine::WHITE), 1);
+    assert_eq!(count_passed_pawns(&board, engine::BLACK), 0);
+    assert_eq!(count_passed_pawns(&Board::new(), engine::WHITE), 0);
+
+    // Further advanced passers are worth more
+    let on_fourth = Board::from_fen("4k3/8/8/8/3P4/8/8/4K3 w - - 0 1").unwrap();
+    let on_sixth = Board::from_fen("4k3/8/3P4/8/8/8/8/4K3 w - - 0 1").unwrap();
+    assert!(passed_pawn_score(&on_sixth) > passed_pawn_score(&on_fourth));
+    assert!(passed_pawn_score(&on_fourth) > 0);
+
+    // Mirrored for Black
+    let black_passer = Board::from_fen("4k3/8/8/8/3p4/8/8/4K3 w - - 0 1").unwrap();
+    assert_eq!(count_passed_pawns(&black_passer, engine::BLACK), 1);
+    assert!(passed_pawn_score(&black_passer) < 0);
+
+    // The same passer counts for less with the pieces still on
+    let middlegame = Board::from_fen("r2qk2r/8/3P4/8/8/8/8/R2QK2R w - - 0 1").unwrap();
+    assert!(passed_pawn_score(&middlegame) < passed_pawn_score(&on_sixth));
+}
+
+#[test]
+fn test_king_safety_prefers_castled_king() {
+    engine::bitboard::initialize_engine();
+
+    let castled = Board::from_fen("r1bq1rk1/ppppbppp/2n2n2/4p3/2B1P3/2N2N2/PPPP1PPP/R1BQ1RK1 w - - 0 1").unwrap();
+    let exposed = Board::from_fen("r1bq1rk1/ppppbppp/2n2n2/4p3/2B1P3/2N2N2/PPPPKPPP/R1BQ3R w - - 0 1").unwrap();
+
+    assert_eq!(king_safety(&castled, engine::WHITE), 0);
+    assert!(king_safety(&exposed, engine::WHITE) < 0);
+    assert!(evaluate_position(&castled) > evaluate_position(&exposed));
+
+    // A knight eyeing g3 reaches the zone in front of the king without touching its neighbours
+    let eyed = Board::from_fen("r1bq1rk1/ppppbppp/2n5/4p2n/2B1P3/2N2N2/PPPP1PPP/R1BQ1RK1 w - - 0 1").unwrap();
+    assert_eq!(king_safety(&eyed, engine::WHITE), -ai::KING_ATTACKER_PENALTY[engine::KNIGHT as usize]);
+
+    // Kings are free to walk in the endgame
+    let endgame = Board::from_fen("8/8/4k3/8/8/4K3/8/8 w - - 0 1").unwrap();
+    assert_eq!(king_safety(&endgame, engine::WHITE), 0);
+    assert_eq!(king_safety(&endgame, engine::BLACK), 0);
+}
+
+#[test]
+fn test_mobility_rewards_active_pieces() {
+    engine::bitboard::initialize_engine();
+
+    let start = Board::new();
+    assert_eq!(mobility_score(&start, engine::WHITE), mobility_score(&start, engine::BLACK));
+
+    // A centralised knight beats one stuck in the corner
+    let central = Board::from_fen("4k3/8/8/8/3N4/8/8/4K3 w - - 0 1").unwrap();
+    let cornered = Board::from_fen("4k3/8/8/8/8/8/8/N3K3 w - - 0 1").unwrap();
+    assert!(mobility_score(&central, engine::WHITE) > mobility_score(&cornered, engine::WHITE));
+
+    // Scored the same whichever side is to move
+    let black_to_move = Board::from_fen("4k3/8/8/8/3N4/8/8/4K3 b - - 0 1").unwrap();
+    assert_eq!(mobility_score(&black_to_move, engine::WHITE), mobility_score(&central, engine::WHITE));
+}
+
+#[test]
+fn test_bishop_pair_and_bad_bishop() {
+    engine::bitboard::initialize_engine();
+
+    let start = Board::new();
+    assert_eq!(bishop_pair_bonus(&start, engine::WHITE), ai::BISHOP_PAIR_BONUS);
+    assert_eq!(bishop_score(&start), 0);
+
+    // Two bishops on the same color are not a pair
+    let same_color = Board::from_fen("4k3/8/8/8/8/4B3/8/2B1K3 w - - 0 1").unwrap();
+    let bishop_pair = Board::from_fen("4k3/8/8/8/8/8/8/2B1KB2 w - - 0 1").unwrap();
+    assert_eq!(bishop_pair_bonus(&same_color, engine::WHITE), 0);
+    assert_eq!(bishop_pair_bonus(&bishop_pair, engine::WHITE), ai::BISHOP_PAIR_BONUS);
+    assert!(bishop_score(&bishop_pair) > 0);
+
+    // Dark-squared bishop behind pawns on c3, d4, e5 versus the light-squared one
+    let bad = Board::from_fen("4k3/8/8/4P3/3P4/2P5/8/2B1K3 w - - 0 1").unwrap();
+    let good = Board::from_fen("4k3/8/8/4P3/3P4/2P5/8/3BK3 w - - 0 1").unwrap();
+    assert_eq!(bad_bishop_penalty(&bad, engine::WHITE), -3 * ai::BAD_BISHOP_PENALTY);
+    assert_eq!(bad_bishop_penalty(&good, engine::WHITE), 0);
+}
+
+#[test]
+fn test_rook_bonuses() {
+    engine::bitboard::initialize_engine();
+
+    // Rook on the open d-file, the semi-open c-file, or behind its own f-pawn
+    let open = Board::from_fen("4k3/2p5/8/8/8/8/4P3/3RK3 w - - 0 1").unwrap();
+    let semi_open = Board::from_fen("4k3/2p5/8/8/8/8/4P3/2R1K3 w - - 0 1").unwrap();
+    let closed = Board::from_fen("4k3/2p5/8/8/8/8/4PP2/4KR2 w - - 0 1").unwrap();
+    assert_eq!(rook_bonus(&open, engine::WHITE), ai::ROOK_OPEN_FILE);
+    assert_eq!(rook_bonus(&semi_open, engine::WHITE), ai::ROOK_SEMI_OPEN_FILE);
+    assert_eq!(rook_bonus(&closed, engine::WHITE), 0);
+
+    // The seventh rank only counts while the enemy king is stuck on its back rank
+    let seventh = Board::from_fen("6k1/1R3ppp/8/8/8/8/5PPP/6K1 w - - 0 1").unwrap();
+    let king_out = Board::from_fen("8/1R3ppp/6k1/8/8/8/5PPP/6K1 w - - 0 1").unwrap();
+    assert_eq!(rook_bonus(&seventh, engine::WHITE), ai::ROOK_OPEN_FILE);
+    assert_eq!(rook_seventh_rank(&seventh, engine::WHITE), ai::ROOK_SEVENTH_BONUS);
+    assert_eq!(rook_seventh_rank(&king_out, engine::WHITE), 0);
+    assert!(rook_score(&seventh) > rook_score(&king_out));
+
+    // Mirrored for Black
+    let black_seventh = Board::from_fen("6k1/5ppp/8/8/8/8/1r3PPP/6K1 w - - 0 1").unwrap();
+    assert_eq!(rook_seventh_rank(&black_seventh, engine::BLACK), ai::ROOK_SEVENTH_BONUS);
+    assert!(rook_score(&black_seventh) < 0);
+}
+
+#[test]
+fn test_connected_rooks() {
+    engine::bitboard::initialize_engine();
+
+    // Rooks on a1 and f1 see each other once the king is out of the way
+    let connected = Board::from_fen("4k3/8/8/8/8/8/8/R4RK1 w - - 0 1").unwrap();
+    let blocked = Board::from_fen("4k3/8/8/8/8/8/8/R3KR2 w - - 0 1").unwrap();
+    assert_eq!(rook_connectivity_bonus(&connected, engine::WHITE), ai::CONNECTED_ROOKS_BONUS);
+    assert_eq!(rook_connectivity_bonus(&blocked, engine::WHITE), 0);
+
+    // Doubled on a file counts too; rooks on different lines or a lone rook don't
+    let doubled = Board::from_fen("3rk3/8/8/8/3r4/8/8/4K3 b - - 0 1").unwrap();
+    let apart = Board::from_fen("4k3/8/8/8/8/1R6/8/4K2R w - - 0 1").unwrap();
+    let single = Board::from_fen("4k3/8/8/8/8/8/8/R3K3 w - - 0 1").unwrap();
+    assert_eq!(rook_connectivity_bonus(&doubled, engine::BLACK), ai::CONNECTED_ROOKS_BONUS);
+    assert_eq!(rook_connectivity_bonus(&apart, engine::WHITE), 0);
+    assert_eq!(rook_connectivity_bonus(&single, engine::WHITE), 0);
+    assert!(rook_score(&connected) > rook_score(&blocked));
+}
+
+#[test]
+fn test_knight_outposts() {
+    engine::bitboard::initialize_engine();
+
+    // d5 is defended by the e-pawn and no black pawn can reach c6 or e6
+    let outpost = Board::from_fen("4k3/p6p/8/3N4/4P3/8/8/4K3 w - - 0 1").unwrap();
+    assert_eq!(knight_outpost_bonus(&outpost, engine::WHITE), ai::KNIGHT_OUTPOST_BONUS);
+    assert!(knight_outpost_score(&outpost) > 0);
+
+    // A c-pawn that can still advance, a missing defender, or our own half: no outpost
+    let attackable = Board::from_fen("4k3/p1p4p/8/3N4/4P3/8/8/4K3 w - - 0 1").unwrap();
+    let undefended = Board::from_fen("4k3/p6p/8/3N4/8/8/4P3/4K3 w - - 0 1").unwrap();
+    let own_half = Board::from_fen("4k3/p6p/8/8/3N4/4P3/8/4K3 w - - 0 1").unwrap();
+    assert_eq!(knight_outpost_bonus(&attackable, engine::WHITE), 0);
+    assert_eq!(knight_outpost_bonus(&undefended, engine::WHITE), 0);
+    assert_eq!(knight_outpost_bonus(&own_half, engine::WHITE), 0);
+
+    // Flank outposts are worth less than central ones
+    let flank = Board::from_fen("4k3/7p/8/1N6/P7/8/8/4K3 w - - 0 1").unwrap();
+    let flank_bonus = knight_outpost_bonus(&flank, engine::WHITE);
+    assert!(flank_bonus > 0 && flank_bonus < ai::KNIGHT_OUTPOST_BONUS);
+
+    // Mirrored for Black
+    let black_outpost = Board::from_fen("4k3/8/8/4p3/3n4/8/P6P/4K3 b - - 0 1").unwrap();
+    assert_eq!(knight_outpost_bonus(&black_outpost, engine::BLACK), ai::KNIGHT_OUTPOST_BONUS);
+    assert!(knight_outpost_score(&black_outpost) < 0);
+}
+
+#[test]
+fn test_evaluation_is_color_symmetric() {
+    engine::bitboard::initialize_engine();
+    // Scores from White's point of view must flip sign when the position is mirrored
+    let white_eval = |board: &Board| {
+        let eval = evaluate_position(board);
+        if board.current_turn == engine::WHITE { eval } else { -eval }
+    };
+
+    // Positions from random games, reproducible through fixed seeds
+    for seed in 1..=20 {
+        for board in engine::perft::random_game_positions(&Board::new(), seed, 60) {
+            let mirrored = board.mirror_vertical();
+            assert_eq!(white_eval(&board), -white_eval(&mirrored), "Game {}: {}", seed, board.to_fen());
+        }
+    }
+}
@@ -0,0 +1,113 @@
+use ai::opening_book::{polyglot_key, BookEntry, OpeningBook};
+use ai::SearchEngine;
+use engine::{Board, Move};
+
+fn entry(key: u64, uci: &str, weight: u16) -> BookEntry {
+    let mv = Move::from_uci(uci).unwrap();
+    let promotion = mv.promotion.map_or(0, |piece| piece as u16 - 1);
+    BookEntry { key, raw_move: mv.to.0 as u16 | (mv.from.0 as u16) << 6 | promotion << 12, weight, learn: 0 }
+}
+
+fn book(entries: &[BookEntry]) -> Vec<u8> {
+    entries.iter().flat_map(|entry| entry.to_bytes()).collect()
+}
+
+#[test]
+fn test_book_entries() {
+    let bytes = [0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x03, 0x1C, 0x00, 0x2A, 0x00, 0x00, 0x00, 0x07];
+    let parsed = BookEntry::from_bytes(&bytes);
+    assert_eq!(parsed.key, 0x0102_0304_0506_0708);
+    assert_eq!(parsed.weight, 42);
+    assert_eq!(parsed.learn, 7);
+    assert_eq!(parsed.book_move().to_string(), "e2e4");
+    assert_eq!(parsed.to_bytes(), bytes);
+
+    assert_eq!(entry(0, "a7a8q", 1).book_move().to_string(), "a7a8q");
+    assert_eq!(entry(0, "h2h1n", 1).book_move().to_string(), "h2h1n");
+}
+
+#[test]
+fn test_polyglot_keys() {
+    engine::bitboard::initialize_engine();
+    assert_eq!(polyglot_key(&Board::new()), 0x463b96181691fc9c);
+
+    // The published test sequence from the PolyGlot book format description
+    let mut board = Board::new();
+    let expected = [
+        ("e2e4", 0x823c9b50fd114196),
+        ("d7d5", 0x0756b94461c50fb0),
+        ("e4e5", 0x662fafb965db29d4),
+        ("f7f5", 0x22a48b5a8e47ff78), // e5 pawn can take on f6, so the en passant file counts
+        ("e1e2", 0x652a607ca3f242c1),
+        ("e8f7", 0x00fdd303c946bdd9),
+    ];
+    for (uci, key) in expected {
+        board.try_make_move(Move::from_uci(uci).unwrap()).unwrap();
+        assert_eq!(polyglot_key(&board), key, "after {}", uci);
+    }
+
+    let mut board = Board::new();
+    for uci in ["a2a4", "b7b5", "h2h4", "b5b4", "c2c4"] {
+        board.try_make_move(Move::from_uci(uci).unwrap()).unwrap();
+    }
+    assert_eq!(polyglot_key(&board), 0x3c8123ea7b067637);
+    for uci in ["b4c3", "a1a3"] {
+        board.try_make_move(Move::from_uci(uci).unwrap()).unwrap();
+    }
+    assert_eq!(polyglot_key(&board), 0x5c3f9b829b279560);
+}
+
+#[test]
+fn test_book_lookup() {
+    engine::bitboard::initialize_engine();
+    let board = Board::new();
+    let key = polyglot_key(&board);
+
+    // Stored out of order with neighbouring keys, as a sloppy book writer might
+    let opening_book = OpeningBook::from_bytes(&book(&[
+        entry(key + 1, "a2a3", 1),
+        entry(key, "e2e4", 3),
+        entry(key - 1, "h2h3", 1),
+        entry(key, "d2d4", 1),
+        entry(key, "g1f3", 0),
+    ]));
+    assert_eq!(opening_book.len(), 5);
+    assert_eq!(opening_book.entries_for(key).len(), 3);
+    assert_eq!(opening_book.lookup(key ^ 0xFF00), None);
+
+    let mut e4_count = 0;
+    for _ in 0..400 {
+        let mv = opening_book.lookup(key).unwrap().to_string();
+        assert!(mv == "e2e4" || mv == "d2d4", "weight 0 and other keys are never played, got {}", mv);
+        e4_count += (mv == "e2e4") as u32;
+    }
+    assert!((225..375).contains(&e4_count), "e2e4 has three quarters of the weight, played {} of 400", e4_count);
+
+    // Castling is stored as the king taking the rook
+    let castling = Board::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1").unwrap();
+    let opening_book = OpeningBook::from_bytes(&book(&[entry(polyglot_key(&castling), "e1h1", 1)]));
+    assert_eq!(opening_book.probe(&castling).map(|mv| mv.to_string()), Some("e1g1".to_string()));
+}
+
+#[test]
+fn test_search_plays_book_moves() {
+    engine::bitboard::initialize_engine();
+    let mut board = Board::new();
+    let path = std::env::temp_dir().join(format!("chase_book_{}.bin", std::process::id()));
+    std::fs::write(&path, book(&[entry(polyglot_key(&board), "b1c3", 1)])).unwrap();
+
+    let mut engine = SearchEngine::new();
+    assert!(engine.set_opening_book(&std::env::temp_dir().join("missing_book.bin")).is_err());
+    engine.set_opening_book(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    let result = engine.search(&mut board, 4);
+    assert_eq!(result.best_move.map(|mv| mv.to_string()), Some("b1c3".to_string()));
+    assert_eq!(result.nodes_searched, 0);
+
+    // Out of book the engine searches as usual
+    board.try_make_move(Move::from_uci("e2e4").unwrap()).unwrap();
+    let result = engine.search(&mut board, 2);
+    assert!(result.best_move.is_some());
+    assert!(result.nodes_searched > 0);
+}
@@ -0,0 +1,400 @@
+use ai::transposition::{NodeType, TranspositionTable};
+use ai::{get_principal_variation, mvv_lva_score, BestMoveCandidate, SearchConfig, SearchEngine, SearchResult};
+use engine::{Board, Move};
+
+// (fen, expected best move in UCI notation)
+const TACTICAL_POSITIONS: [(&str, &str); 10] = [
+    // Back rank mate in 1
+    ("6k1/5ppp/8/8/8/8/5PPP/R5K1 w - - 0 1", "a1a8"),
+    // Black back rank mate in 1
+    ("r5k1/5ppp/8/8/8/8/5PPP/6K1 b - - 0 1", "a8a1"),
+    // Scholar's mate
+    ("r1bqkbnr/pppp1ppp/2n5/4p2Q/2B1P3/8/PPPP1PPP/RNB1K1NR w KQkq - 4 4", "h5f7"),
+    // Rook takes the undefended queen
+    ("4k3/8/8/8/8/8/q7/R3K3 w - - 0 1", "a1a2"),
+    // Knight takes the undefended queen
+    ("k7/8/8/3q4/8/4N3/8/K7 w - - 0 1", "e3d5"),
+    // Pawn takes the queen
+    ("4k3/8/8/3q4/4P3/8/8/4K3 w - - 0 1", "e4d5"),
+    // Bishop takes the undefended rook
+    ("4k3/8/8/8/8/2r5/8/B3K3 w - - 0 1", "a1c3"),
+    // Knight fork on king and queen
+    ("6k1/8/2q5/5N2/8/8/8/6K1 w - - 0 1", "f5e7"),
+    // Black knight fork on king and queen
+    ("4k3/8/8/8/3n4/8/8/Q3K3 b - - 0 1", "d4c2"),
+    // Promote to a queen
+    ("8/4P3/8/8/8/8/k7/7K w - - 0 1", "e7e8q"),
+];
+
+/// Search `fen` with the default config passed through `toggle(config, true)` and then
+/// `toggle(config, false)`, returning both results
+fn search_toggled(fen: &str, depth: u32, toggle: impl Fn(&mut SearchConfig, bool)) -> (SearchResult, SearchResult) {
+    let search = |on: bool| {
+        let mut config = SearchConfig::default();
+        toggle(&mut config, on);
+        SearchEngine::with_config(config).search(&mut Board::from_fen(fen).unwrap(), depth)
+    };
+    (search(true), search(false))
+}
+
+#[test]
+fn test_tactical_positions() {
+    for (fen, expected) in TACTICAL_POSITIONS {
+        let mut search_engine = SearchEngine::new();
+        let best_move = search_engine.best_move_for_fen(fen, 4).unwrap();
+        assert_eq!(best_move, Move::from_uci(expected), "Wrong best move for {}", fen);
+    }
+}
+
+#[test]
+fn test_best_move_candidate() {
+    engine::bitboard::initialize_engine();
+    let (fen, expected) = TACTICAL_POSITIONS[0];
+    let board = Board::from_fen(fen).unwrap();
+
+    assert_eq!(board.best_move_candidate(4), Move::from_uci(expected));
+}
+
+#[test]
+fn test_losing_side_claims_repetition() {
+    engine::bitboard::initialize_engine();
+    let mut board = Board::from_fen("4k1n1/8/8/8/8/8/8/3QK3 b - - 0 1").unwrap();
+    // Reach Ng8 with the queen on d2 twice by different routes, so that Ng8 next is the third
+    // occurrence without any earlier position having repeated three times
+    for uci in ["g8f6", "d1d2", "f6g8", "d2d1", "g8h6", "d1d2", "h6g8", "d2c2", "g8f6", "c2d2"] {
+        board.try_make_move(Move::from_uci(uci).unwrap()).unwrap();
+    }
+
+    let mut search_engine = SearchEngine::new();
+    let result = search_engine.search(&mut board, 4);
+    assert_eq!(result.best_move, Move::from_uci("f6g8"));
+    assert_eq!(result.evaluation, 0);
+}
+
+#[test]
+fn test_iterative_deepening_reaches_depth() {
+    let mut board = Board::new();
+    let mut search_engine = SearchEngine::new();
+    let result = search_engine.search(&mut board, 3);
+    assert_eq!(result.depth_reached, 3);
+    assert!(result.best_move.is_some());
+    // Aspiration windows only kick in from depth 4
+    assert_eq!((result.fail_high, result.fail_low), (0, 0));
+
+    // No legal moves: the first iteration already settles it
+    let mut mated = Board::from_fen("R5k1/5ppp/8/8/8/8/5PPP/6K1 b - - 0 1").unwrap();
+    let result = search_engine.search(&mut mated, 4);
+    assert_eq!(result.depth_reached, 1);
+    assert_eq!(result.best_move, None);
+}
+
+#[test]
+fn test_mate_scores_count_distance() {
+    let mut search_engine = SearchEngine::new();
+
+    // Ra7 then Rb8#; Rb8+ at once lets the king out
+    let mut mate_in_two = Board::from_fen("7k/8/8/8/8/8/R7/1R5K w - - 0 1").unwrap();
+    let result = search_engine.search(&mut mate_in_two, 5);
+    assert_eq!(result.evaluation, ai::CHECKMATE_SCORE - 3);
+    assert_eq!(ai::mate_in_moves(result.evaluation), Some(2));
+
+    let mut getting_mated = Board::from_fen("6k1/R7/8/8/8/8/8/1R5K b - - 0 1").unwrap();
+    let result = search_engine.search(&mut getting_mated, 4);
+    assert_eq!(result.evaluation, -(ai::CHECKMATE_SCORE - 2));
+    assert_eq!(ai::mate_in_moves(result.evaluation), Some(-1));
+    assert_eq!(ai::mate_in_moves(250), None);
+}
+
+#[test]
+fn test_mate_on_hundredth_half_move() {
+    // Ra8# is the hundredth half-move without a capture or pawn move, and still mates
+    let mut board = Board::from_fen("6k1/5ppp/8/8/8/8/5PPP/R5K1 w - - 99 80").unwrap();
+    let result = SearchEngine::new().search(&mut board, 3);
+    assert_eq!(result.best_move, Move::from_uci("a1a8"));
+    assert_eq!(result.evaluation, ai::CHECKMATE_SCORE - 1);
+}
+
+#[test]
+fn test_principal_variation_from_tt() {
+    let uci = |line: &[Move]| line.iter().map(|mv| mv.to_string()).collect::<Vec<_>>().join(" ");
+    let mut table = TranspositionTable::new(1);
+    let mut board = Board::new();
+    for mv in ["g1f3", "g8f6", "f3g1", "f6g8"] {
+        let mv = Move::from_uci(mv).unwrap();
+        table.store(table.get_hash(&board), 1, 0, Some(mv), NodeType::Exact);
+        board.try_make_move(mv).unwrap();
+    }
+
+    // The knights' dance comes back to the start position, which ends the line
+    let start = Board::new();
+    assert_eq!(uci(&get_principal_variation(&start, &table, 10)), "g1f3 g8f6 f3g1 f6g8");
+    assert_eq!(uci(&get_principal_variation(&start, &table, 2)), "g1f3 g8f6");
+
+    // A stored move that isn't legal in the position is ignored
+    let mut table = TranspositionTable::new(1);
+    table.store(table.get_hash(&start), 1, 0, Some(Move::from_uci("e2e5").unwrap()), NodeType::Exact);
+    assert!(get_principal_variation(&start, &table, 10).is_empty());
+
+    // The search's own line covers every completed ply
+    let mut search_engine = SearchEngine::new();
+    let result = search_engine.search(&mut Board::new(), 5);
+    assert_eq!(result.pv_line.len(), 5);
+}
+
+#[test]
+fn test_razoring_margins() {
+    let kiwipete = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+
+    let mut search_engine = SearchEngine::new();
+    let razored = search_engine.bench(&mut Board::from_fen(kiwipete).unwrap(), 5);
+    assert!(razored.razored_nodes > 0);
+    assert_eq!(search_engine.razored_nodes, razored.razored_nodes);
+
+    // Margins nothing can fall behind turn it off
+    let mut search_engine = SearchEngine::new();
+    search_engine.config.razor_margin = i32::MAX;
+    search_engine.config.razor_margin_d2 = i32::MAX;
+    let unrazored = search_engine.bench(&mut Board::from_fen(kiwipete).unwrap(), 5);
+    assert_eq!(unrazored.razored_nodes, 0);
+}
+
+#[test]
+fn test_search_config() {
+    let kiwipete = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+    assert_eq!(SearchEngine::new().config, SearchConfig::default());
+
+    let plain = SearchConfig {
+        use_null_move: false,
+        use_lmr: false,
+        use_futility: false,
+        use_razoring: false,
+        use_aspiration: false,
+        use_killer_moves: false,
+        use_history: false,
+        use_pvs: false,
+        ..SearchConfig::default()
+    };
+    let mut plain_engine = SearchEngine::with_config(plain);
+    assert_eq!(plain_engine.config, plain);
+    let plain_result = plain_engine.search(&mut Board::from_fen(kiwipete).unwrap(), 4);
+    assert_eq!((plain_result.fail_high, plain_result.fail_low), (0, 0));
+    assert_eq!(plain_engine.razored_nodes, 0);
+
+    // The pruning and ordering heuristics pay for themselves
+    let tuned_result = SearchEngine::new().search(&mut Board::from_fen(kiwipete).unwrap(), 4);
+    assert!(tuned_result.nodes_searched < plain_result.nodes_searched,
+            "{} nodes with every heuristic vs {} without", tuned_result.nodes_searched, plain_result.nodes_searched);
+}
+
+#[test]
+fn test_null_move_and_futility_pruning() {
+    type Toggle = fn(&mut SearchConfig, bool);
+    let toggles: [(&str, Toggle); 2] = [
+        ("null move", |config, on| config.use_null_move = on),
+        ("futility", |config, on| config.use_futility = on),
+    ];
+
+    for (name, toggle) in toggles {
+        let (mut with, mut without) = (0, 0);
+        for (fen, expected) in TACTICAL_POSITIONS {
+            let (on, off) = search_toggled(fen, 5, toggle);
+            assert_eq!(on.best_move, Move::from_uci(expected), "{} on for {}", name, fen);
+            assert_eq!(off.best_move, Move::from_uci(expected), "{} off for {}", name, fen);
+            with += on.nodes_searched;
+            without += off.nodes_searched;
+        }
+        assert!(with < without, "{} pruning searched {} nodes vs {} without", name, with, without);
+    }
+}
+
+#[test]
+fn test_search_timed_respects_budget() {
+    let kiwipete = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+    let mut board = Board::from_fen(kiwipete).unwrap();
+    let mut search_engine = SearchEngine::new();
+
+    let start = std::time::Instant::now();
+    let result = search_engine.search_timed(&mut board, 300, 50);
+    let elapsed = start.elapsed().as_millis();
+
+    // The running iteration is abandoned at the deadline, leaving the last completed one
+    assert!(elapsed < 600, "Timed search took {}ms", elapsed);
+    assert!(result.depth_reached >= 1);
+    assert!(result.best_move.is_some());
+    assert_eq!(board.to_fen(), kiwipete);
+
+    // A later fixed-depth search is unaffected by the old deadline
+    let result = search_engine.search(&mut board, 3);
+    assert_eq!(result.depth_reached, 3);
+}
+
+#[test]
+fn test_quiescence_sees_recapture() {
+    // Qxd5 wins a pawn but loses the queen to cxd5 just past the horizon
+    let mut board = Board::from_fen("4k3/8/2p5/3p4/8/8/8/3QK3 w - - 0 1").unwrap();
+    let mut search_engine = SearchEngine::new();
+    let result = search_engine.search(&mut board, 1);
+    assert_ne!(result.best_move, Move::from_uci("d1d5"));
+}
+
+#[test]
+fn test_search_multi_pv() {
+    let mut board = Board::from_fen("k7/8/8/3q4/8/4N3/8/K7 w - - 0 1").unwrap();
+    let mut search_engine = SearchEngine::new();
+
+    let results = search_engine.search_multi_pv(&mut board, 4, 3);
+    assert_eq!(results.len(), 3);
+    assert_eq!(results[0].best_move, Move::from_uci("e3d5"));
+    for pair in results.windows(2) {
+        assert!(pair[0].evaluation >= pair[1].evaluation);
+        assert_ne!(pair[0].best_move, pair[1].best_move);
+    }
+
+    // Each line starts with its move and plays out legally
+    for result in &results {
+        assert_eq!(result.pv_line.first().copied(), result.best_move);
+        let mut replay = board.clone();
+        for &mv in &result.pv_line {
+            assert!(replay.try_make_move(mv).is_ok(), "Illegal PV move {:?}", mv);
+        }
+    }
+
+    // A plain search afterwards isn't limited by the excluded moves
+    assert_eq!(search_engine.search(&mut board, 3).best_move, Move::from_uci("e3d5"));
+
+    // Only Kxb2 is legal
+    let mut board = Board::from_fen("k7/8/8/8/8/8/1r6/K7 w - - 0 1").unwrap();
+    let results = search_engine.search_multi_pv(&mut board, 3, 3);
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].best_move, Move::from_uci("a1b2"));
+}
+
+#[test]
+fn test_ponder_stop_and_hit() {
+    let mut board = Board::new();
+    board.try_make_move(Move::from_uci("e2e4").unwrap()).unwrap();
+    let mut search_engine = SearchEngine::new();
+
+    // Stopping returns the deepest finished iteration, for the side to move in the pondered position
+    search_engine.start_ponder(board.clone());
+    assert!(search_engine.is_pondering());
+    std::thread::sleep(std::time::Duration::from_millis(200));
+    let start = std::time::Instant::now();
+    let result = search_engine.stop_ponder().unwrap();
+    assert!(start.elapsed().as_millis() < 500, "Stopping took {}ms", start.elapsed().as_millis());
+    assert!(!search_engine.is_pondering());
+    assert!(result.depth_reached >= 1);
+    assert!(board.get_all_legal_moves().contains(&result.best_move.unwrap()));
+    assert!(search_engine.stop_ponder().is_none());
+
+    // A hit keeps the same search running for the given time
+    search_engine.start_ponder(board.clone());
+    let start = std::time::Instant::now();
+    let result = search_engine.ponder_hit(300).unwrap();
+    let elapsed = start.elapsed().as_millis();
+    assert!((300..800).contains(&elapsed), "Ponder hit took {}ms", elapsed);
+    assert!(board.get_all_legal_moves().contains(&result.best_move.unwrap()));
+    assert!(!search_engine.is_pondering());
+}
+
+#[test]
+fn test_mvv_lva_score() {
+    engine::bitboard::initialize_engine();
+    // Pawn and queen can both take the rook; the pawn capture scores higher
+    let board = Board::from_fen("4k3/8/8/3r4/2P5/8/8/3QK3 w - - 0 1").unwrap();
+    let pawn_takes = mvv_lva_score(&board, Move::from_uci("c4d5").unwrap());
+    let queen_takes = mvv_lva_score(&board, Move::from_uci("d1d5").unwrap());
+    assert_eq!(pawn_takes, 49);
+    assert_eq!(queen_takes, 41);
+    assert_eq!(mvv_lva_score(&board, Move::from_uci("d1d2").unwrap()), 0);
+}
+
+#[test]
+fn test_see() {
+    engine::bitboard::initialize_engine();
+    let see_at = |fen: &str, uci: &str, threshold: i32| {
+        let board = Board::from_fen(fen).unwrap();
+        board.see(Move::from_uci(uci).unwrap()) >= threshold
+    };
+
+    // Queen takes a pawn defended by a rook: loses 800
+    let fen = "4k3/8/4r3/8/8/4p3/8/4Q2K w - - 0 1";
+    assert!(!see_at(fen, "e1e3", 0));
+    assert!(see_at(fen, "e1e3", -800));
+    assert!(!see_at(fen, "e1e3", -799));
+
+    // Pawn takes a defended knight and is recaptured: still 220 up
+    let fen = "4k3/8/2p5/3n4/4P3/8/8/4K3 w - - 0 1";
+    assert!(see_at(fen, "e4d5", 220));
+    assert!(!see_at(fen, "e4d5", 221));
+
+    // The rook behind joins in once the front rook has captured
+    let fen = "4r1k1/8/8/4p3/8/8/4R3/4R1K1 w - - 0 1";
+    assert!(see_at(fen, "e2e5", 100));
+    assert!(!see_at(fen, "e2e5", 101));
+
+    // The king recaptures an undefended rook, but not one the bishop covers
+    assert!(!see_at("4k3/3p4/8/8/8/8/8/3RK3 w - - 0 1", "d1d7", 0));
+    assert!(see_at("4k3/3p4/8/1B6/8/8/8/3RK3 w - - 0 1", "d1d7", 100));
+}
+
+#[test]
+fn test_history_heuristic_reduces_nodes() {
+    let kiwipete = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+    // Razoring prunes different nodes depending on move order, which muddies the comparison
+    let (with, without) = search_toggled(kiwipete, 4, |config, on| {
+        config.use_history = on;
+        config.use_razoring = false;
+    });
+    assert!(with.nodes_searched < without.nodes_searched,
+            "History ordering searched {} nodes vs {} without", with.nodes_searched, without.nodes_searched);
+}
+
+#[test]
+fn test_killer_moves_reduce_nodes() {
+    let italian = "r1bqkb1r/pppp1ppp/2n2n2/4p3/2B1P3/5N2/PPPP1PPP/RNBQK2R w KQkq - 4 4";
+    // Razoring prunes different nodes depending on move order, which muddies the comparison
+    let (with, without) = search_toggled(italian, 6, |config, on| {
+        config.use_killer_moves = on;
+        config.use_razoring = false;
+    });
+    assert!(with.nodes_searched < without.nodes_searched,
+            "Killer ordering searched {} nodes vs {} without", with.nodes_searched, without.nodes_searched);
+}
+
+#[test]
+fn test_pvs_reduces_nodes() {
+    let italian = "r1bqkb1r/pppp1ppp/2n2n2/4p3/2B1P3/5N2/PPPP1PPP/RNBQK2R w KQkq - 4 4";
+    // LMR only works inside PVS's null windows, so it stays off on both sides
+    let (with, without) = search_toggled(italian, 5, |config, on| {
+        config.use_pvs = on;
+        config.use_lmr = false;
+    });
+    assert_eq!(with.best_move, without.best_move);
+    assert!(with.nodes_searched < without.nodes_searched,
+            "PVS searched {} nodes vs {} without", with.nodes_searched, without.nodes_searched);
+}
+
+#[test]
+fn test_aspiration_window_failures() {
+    // Qg6 only shows as mate at depth 5, far above a window centred on the depth 4 score
+    let mating = "2rr3k/pp3pp1/1nnqbN1p/3pN3/2pP4/2P3Q1/PPB4P/R4RK1 w - - 0 1";
+    // Without the c6 knight Black has to take on f6, and each extra ply finds more of the
+    // attack down the open f-file, so the score keeps dropping below the window
+    let defending = "2rr3k/pp3pp1/1n1qbN1p/3pN3/2pP4/2P3Q1/PPB4P/R4RK1 b - - 0 1";
+
+    // A window wider than any score, mates included, never fails
+    let window = |config: &mut SearchConfig, narrow: bool| {
+        config.aspiration_window = if narrow { ai::ASPIRATION_WINDOW } else { 2 * ai::CHECKMATE_SCORE };
+    };
+    let (narrow, wide) = search_toggled(mating, 5, window);
+    assert!(narrow.fail_high > 0, "{} fail highs", narrow.fail_high);
+    assert_eq!(narrow.best_move, Move::from_uci("g3g6"));
+    assert_eq!((wide.fail_high, wide.fail_low), (0, 0));
+    assert_eq!(wide.best_move, narrow.best_move);
+
+    let (narrow, wide) = search_toggled(defending, 5, window);
+    assert!(narrow.fail_low > 0, "{} fail lows", narrow.fail_low);
+    assert_eq!((wide.fail_high, wide.fail_low), (0, 0));
+    assert_eq!(wide.best_move, narrow.best_move);
+}
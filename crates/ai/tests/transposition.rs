@@ -0,0 +1,40 @@
+use ai::transposition::{NodeType, TranspositionTable};
+
+fn stored_score(table: &TranspositionTable, hash: u64) -> Option<i32> {
+    table.probe(hash, 0, i32::MIN, i32::MAX).map(|(score, _)| score)
+}
+
+#[test]
+fn test_two_tier_replacement_with_generations() {
+    // Zero megabytes still gets one bucket, so every key collides
+    let mut table = TranspositionTable::new(0);
+
+    table.store(1, 5, 100, None, NodeType::Exact);
+    table.store(2, 2, 200, None, NodeType::Exact);
+    assert_eq!(stored_score(&table, 1), Some(100));
+    assert_eq!(stored_score(&table, 2), Some(200));
+
+    // Shallower entry from the same search only evicts the always-replace slot
+    table.store(3, 1, 300, None, NodeType::Exact);
+    assert_eq!(stored_score(&table, 1), Some(100));
+    assert_eq!(stored_score(&table, 2), None);
+    assert_eq!(stored_score(&table, 3), Some(300));
+
+    // Once the deep entry is from an older generation it can be replaced
+    table.new_search();
+    table.store(4, 1, 400, None, NodeType::Exact);
+    assert_eq!(stored_score(&table, 1), None);
+    assert_eq!(stored_score(&table, 4), Some(400));
+    assert_eq!(table.size(), 2);
+}
+
+#[test]
+fn test_bounds_only_cut_outside_the_window() {
+    let mut table = TranspositionTable::new(1);
+    table.store(7, 4, 50, None, NodeType::LowerBound);
+
+    assert_eq!(table.probe(7, 4, 0, 40).map(|(score, _)| score), Some(50));
+    assert_eq!(table.probe(7, 4, 0, 60), None);
+    // Not deep enough for this search
+    assert_eq!(table.probe(7, 5, 0, 40), None);
+}
@@ -1,5 +1,6 @@
 use crate::types::*;
 use std::sync::Once;
+use serde::{Deserialize, Serialize};
 
 pub type Bitboard = u64;
 
@@ -27,6 +28,14 @@ pub const RANK_6: Bitboard = 0x0000FF0000000000;
 pub const RANK_7: Bitboard = 0x00FF000000000000;
 pub const RANK_8: Bitboard = 0xFF00000000000000;
 
+// Square color masks (a1 is dark)
+pub const LIGHT_SQUARES: Bitboard = 0x55AA55AA55AA55AA;
+pub const DARK_SQUARES: Bitboard = !LIGHT_SQUARES;
+
+// File and rank masks indexed by file/rank number
+pub const FILE_MASKS: [Bitboard; 8] = [FILE_A, FILE_B, FILE_C, FILE_D, FILE_E, FILE_F, FILE_G, FILE_H];
+pub const RANK_MASKS: [Bitboard; 8] = [RANK_1, RANK_2, RANK_3, RANK_4, RANK_5, RANK_6, RANK_7, RANK_8];
+
 // Core bitboard operations
 pub fn set_bit(bitboard: &mut Bitboard, square: u8) {
     *bitboard |= 1u64 << square;
@@ -163,7 +172,7 @@ pub fn test_knight_mask_direct() -> Bitboard {
 
 
 // BitboardManager - manages all piece bitboards
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct BitboardManager {
     // Individual piece bitboards
     pub white_pawns: Bitboard,
@@ -361,6 +370,10 @@ impl BitboardManager {
 pub fn initialize_engine() {
     initialize_knight_attacks();
     initialize_king_attacks();
+    initialize_zobrist_keys();
+    initialize_pawn_attacks();
+    initialize_king_zones();
+    initialize_lmr_table();
     // Add other initializations here later
 }
 
@@ -396,6 +409,7 @@ pub fn get_knight_attacks(square: u8) -> Bitboard {
 
 // Static storage for king attack masks  
 static mut KING_ATTACKS: [Bitboard; 64] = [0; 64];
+static KING_INIT: Once = Once::new();
 
 fn generate_king_attack_mask(square: u8) -> Bitboard {
     let file = square % 8;
@@ -420,9 +434,11 @@ fn generate_king_attack_mask(square: u8) -> Bitboard {
 
 pub fn initialize_king_attacks() {
     unsafe {
-        for square in 0..64 {
-            KING_ATTACKS[square] = generate_king_attack_mask(square as u8);
-        }
+        KING_INIT.call_once(|| {
+            for square in 0..64 {
+                KING_ATTACKS[square] = generate_king_attack_mask(square as u8);
+            }
+        });
     }
 }
 
@@ -432,6 +448,219 @@ pub fn get_king_attacks(square: u8) -> Bitboard {
     }
 }
 
+/// Squares a slider on `square` reaches along `directions`, stopping at (and including) the first blocker
+fn sliding_attacks(square: u8, directions: &[(i8, i8)], occupancy: Bitboard) -> Bitboard {
+    let mut attacks = 0u64;
+    for &(df, dr) in directions {
+        let mut file = (square % 8) as i8 + df;
+        let mut rank = (square / 8) as i8 + dr;
+        while (0..8).contains(&file) && (0..8).contains(&rank) {
+            let target = 1u64 << (rank * 8 + file);
+            attacks |= target;
+            if occupancy & target != 0 {
+                break;
+            }
+            file += df;
+            rank += dr;
+        }
+    }
+    attacks
+}
+
+/// Rook attacks from `square` given the board occupancy (ray walk, not magic lookups)
+pub fn get_rook_attacks(square: u8, occupancy: Bitboard) -> Bitboard {
+    sliding_attacks(square, &[(0, 1), (0, -1), (1, 0), (-1, 0)], occupancy)
+}
+
+/// Bishop attacks from `square` given the board occupancy
+pub fn get_bishop_attacks(square: u8, occupancy: Bitboard) -> Bitboard {
+    sliding_attacks(square, &[(1, 1), (1, -1), (-1, 1), (-1, -1)], occupancy)
+}
+
+
+// Pawn attack masks, [color_index][square] with 0 = White, 1 = Black
+static mut PAWN_ATTACK_MASKS: [[Bitboard; 64]; 2] = [[0; 64]; 2];
+static PAWN_ATTACKS_INIT: Once = Once::new();
+
+fn generate_pawn_attack_mask(square: u8, color: u8) -> Bitboard {
+    let file = (square % 8) as i8;
+    let rank = (square / 8) as i8;
+    let attack_rank = if color == WHITE { rank + 1 } else { rank - 1 };
+    let mut attacks = 0u64;
+
+    if !(0..8).contains(&attack_rank) {
+        return attacks;
+    }
+
+    for df in [-1, 1] {
+        let attack_file = file + df;
+        if (0..8).contains(&attack_file) {
+            attacks |= 1u64 << (attack_rank * 8 + attack_file);
+        }
+    }
+
+    attacks
+}
+
+pub fn initialize_pawn_attacks() {
+    unsafe {
+        PAWN_ATTACKS_INIT.call_once(|| {
+            for (color_index, color) in [WHITE, BLACK].into_iter().enumerate() {
+                for (square, mask) in PAWN_ATTACK_MASKS[color_index].iter_mut().enumerate() {
+                    *mask = generate_pawn_attack_mask(square as u8, color);
+                }
+            }
+        });
+    }
+}
+
+/// Squares a pawn of `color` on `square` attacks
+pub fn get_pawn_attacks(color: u8, square: u8) -> Bitboard {
+    let color_index = if color == WHITE { 0 } else { 1 };
+    unsafe {
+        PAWN_ATTACK_MASKS[color_index][square as usize]
+    }
+}
+
+// King zone masks: the 3x3 area around the king plus the three squares two ranks
+// in front of it. "In front" depends on color, so this is indexed [color_index][square]
+static mut KING_ZONE_MASKS: [[Bitboard; 64]; 2] = [[0; 64]; 2];
+static KING_ZONE_INIT: Once = Once::new();
+
+fn generate_king_zone_mask(square: u8, color: u8) -> Bitboard {
+    let file = (square % 8) as i8;
+    let rank = (square / 8) as i8;
+    let front_rank = if color == WHITE { rank + 2 } else { rank - 2 };
+    let mut zone = generate_king_attack_mask(square) | (1u64 << square);
+
+    if (0..8).contains(&front_rank) {
+        for df in [-1, 0, 1] {
+            let zone_file = file + df;
+            if (0..8).contains(&zone_file) {
+                zone |= 1u64 << (front_rank * 8 + zone_file);
+            }
+        }
+    }
+
+    zone
+}
+
+pub fn initialize_king_zones() {
+    unsafe {
+        KING_ZONE_INIT.call_once(|| {
+            for (color_index, color) in [WHITE, BLACK].into_iter().enumerate() {
+                for (square, mask) in KING_ZONE_MASKS[color_index].iter_mut().enumerate() {
+                    *mask = generate_king_zone_mask(square as u8, color);
+                }
+            }
+        });
+    }
+}
+
+/// King zone for a king of `color` standing on `square`
+pub fn get_king_zone(color: u8, square: u8) -> Bitboard {
+    let color_index = if color == WHITE { 0 } else { 1 };
+    unsafe {
+        KING_ZONE_MASKS[color_index][square as usize]
+    }
+}
+
+// Late move reduction amounts, indexed [depth][move_index] and saturating at 63
+static mut LMR_TABLE: [[i32; 64]; 64] = [[0; 64]; 64];
+static LMR_INIT: Once = Once::new();
+
+pub fn initialize_lmr_table() {
+    unsafe {
+        LMR_INIT.call_once(|| {
+            let mut table = [[0; 64]; 64];
+            for (depth, row) in table.iter_mut().enumerate().skip(1) {
+                for (move_index, reduction) in row.iter_mut().enumerate().skip(1) {
+                    *reduction = (((depth as f64).ln() * (move_index as f64).ln() / 2.0) as i32).max(1);
+                }
+            }
+            LMR_TABLE = table;
+        });
+    }
+}
+
+/// Plies to reduce a late quiet move by
+pub fn lmr_reduction(depth: i32, move_index: usize) -> i32 {
+    let depth_index = depth.clamp(0, 63) as usize;
+    unsafe {
+        LMR_TABLE[depth_index][move_index.min(63)]
+    }
+}
+
+// Zobrist hashing keys
+pub static mut ZOBRIST_PIECES: [[[u64; 64]; 6]; 2] = [[[0; 64]; 6]; 2]; // [color][piece_type - 1][square]
+pub static mut ZOBRIST_CASTLING: [u64; 16] = [0; 16];                    // Indexed by castling rights
+pub static mut ZOBRIST_EN_PASSANT: [u64; 9] = [0; 9];                    // Files 0-7, index 8 = none
+pub static mut ZOBRIST_SIDE_TO_MOVE: u64 = 0;
+static ZOBRIST_INIT: Once = Once::new();
+
+// Fixed seed so hashes are reproducible between runs
+const ZOBRIST_SEED: u64 = 0x9E37_79B9_7F4A_7C15;
+
+// xorshift64* pseudo-random number generator
+pub(crate) fn next_random(state: &mut u64) -> u64 {
+    *state ^= *state >> 12;
+    *state ^= *state << 25;
+    *state ^= *state >> 27;
+    state.wrapping_mul(0x2545_F491_4F6C_DD1D)
+}
+
+// Initialize all Zobrist keys
+pub fn initialize_zobrist_keys() {
+    unsafe {
+        ZOBRIST_INIT.call_once(|| {
+            let mut state = ZOBRIST_SEED;
+
+            // Tables are filled locally and stored whole; pieces go color by color, then type, then square
+            let mut pieces = [[[0u64; 64]; 6]; 2];
+            for key in pieces.iter_mut().flatten().flatten() {
+                *key = next_random(&mut state);
+            }
+            ZOBRIST_PIECES = pieces;
+
+            let mut castling = [0u64; 16];
+            castling.fill_with(|| next_random(&mut state));
+            ZOBRIST_CASTLING = castling;
+
+            let mut en_passant = [0u64; 9];
+            en_passant.fill_with(|| next_random(&mut state));
+            ZOBRIST_EN_PASSANT = en_passant;
+
+            ZOBRIST_SIDE_TO_MOVE = next_random(&mut state);
+        });
+    }
+}
+
+pub fn zobrist_piece_key(piece: Piece, square: u8) -> u64 {
+    let color_index = if piece_color(piece) == WHITE { 0 } else { 1 };
+    let piece_index = (piece_type(piece) - 1) as usize;
+    unsafe {
+        ZOBRIST_PIECES[color_index][piece_index][square as usize]
+    }
+}
+
+pub fn zobrist_castling_key(castling_rights: u8) -> u64 {
+    unsafe {
+        ZOBRIST_CASTLING[(castling_rights & 15) as usize]
+    }
+}
+
+pub fn zobrist_en_passant_key(en_passant_target: Option<Square>) -> u64 {
+    let index = en_passant_target.map_or(8, |square| square.file() as usize);
+    unsafe {
+        ZOBRIST_EN_PASSANT[index]
+    }
+}
+
+pub fn zobrist_side_key() -> u64 {
+    unsafe {
+        ZOBRIST_SIDE_TO_MOVE
+    }
+}
 
 #[cfg(test)]
 mod tests {
@@ -610,4 +839,37 @@ mod tests {
             println!("✅ All knight attack masks are correct!");
         }
     }
+
+    #[test]
+    fn test_pawn_attack_and_king_zone_masks() {
+        initialize_pawn_attacks();
+        initialize_king_attacks();
+        initialize_king_zones();
+
+        // White pawn on e2 (12) attacks d3 (19) and f3 (21)
+        assert_eq!(get_pawn_attacks(WHITE, 12), (1u64 << 19) | (1u64 << 21));
+        // Black pawn on a7 (48) only attacks b6 (41)
+        assert_eq!(get_pawn_attacks(BLACK, 48), 1u64 << 41);
+        // Pawns on the last rank attack nothing
+        assert_eq!(get_pawn_attacks(WHITE, 60), 0);
+
+        // White king on g1 (6): 3x3 around it (clipped) plus f3, g3, h3
+        let zone = get_king_zone(WHITE, 6);
+        assert_eq!(count_bits(zone), 9);
+        assert!(get_bit(zone, 21) && get_bit(zone, 22) && get_bit(zone, 23));
+        // Black king zone extends towards rank 1 instead
+        assert!(get_bit(get_king_zone(BLACK, 62), 45));
+    }
+
+    #[test]
+    fn test_lmr_table() {
+        initialize_lmr_table();
+
+        // Never less than one ply once a move is reduced
+        assert_eq!(lmr_reduction(3, 4), 1);
+        // ln(20) * ln(40) / 2 = 5.54
+        assert_eq!(lmr_reduction(20, 40), 5);
+        // Out-of-range inputs saturate instead of panicking
+        assert_eq!(lmr_reduction(200, 500), lmr_reduction(63, 63));
+    }
 }
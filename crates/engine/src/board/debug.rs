@@ -1,5 +1,6 @@
 use crate::types::*;
-use super::{Board, square_to_algebraic};
+use super::Board;
+use crate::bitboard::{get_bit, iterate_bits, index_to_square};
 
 impl Board {
     /// Debug game state information
@@ -13,13 +14,13 @@ impl Board {
                                self.castling_rights, self.castling_rights));
         
         if let Some(target) = self.en_passant_target {
-            debug_info.push(format!("En passant target: {}", square_to_algebraic(target)));
+            debug_info.push(format!("En passant target: {}", target));
         } else {
             debug_info.push("En passant target: None".to_string());
         }
         
         if let Some(pawn) = self.en_passant_pawn {
-            debug_info.push(format!("En passant pawn: {}", square_to_algebraic(pawn)));
+            debug_info.push(format!("En passant pawn: {}", pawn));
         } else {
             debug_info.push("En passant pawn: None".to_string());
         }
@@ -27,9 +28,87 @@ impl Board {
         debug_info.push(format!("Half-move clock: {}", self.half_move_clock));
         debug_info.push(format!("Full-move number: {}", self.full_move_number));
         
+        // Cross-check the check detection against the full attack table
+        if let Some(king_square) = self.find_king(self.current_turn) {
+            let enemy_pieces = self.bitboards.get_all_pieces(opposite_color(self.current_turn));
+            let attacked = self.attacked_by_bb(king_square) & enemy_pieces != 0;
+            if attacked != self.is_in_check() {
+                debug_info.push(format!("⚠️ Check inconsistency: is_in_check() = {}, attack table says {}",
+                                       self.is_in_check(), attacked));
+                debug_info.extend(self.print_attack_table().lines().map(|line| line.to_string()));
+            }
+        }
+        
         debug_info
     }
 
+    /// 8x8 grid of FEN piece letters (uppercase White, lowercase Black, . empty), rank 8 on top
+    pub fn display(&self) -> String {
+        self.display_with_highlights(&[])
+    }
+
+    /// Same grid with the given squares shown as *, e.g. the targets of a piece's moves
+    pub fn display_with_highlights(&self, squares: &[Square]) -> String {
+        let mut grid = String::new();
+
+        for rank in (0..8).rev() {
+            grid.push_str(&format!("{}", rank + 1));
+            for file in 0..8 {
+                let square = Square::new(file, rank);
+                let piece = self.get_piece(square);
+                let cell = if squares.contains(&square) {
+                    '*'
+                } else if is_empty(piece) {
+                    '.'
+                } else {
+                    self.piece_to_char(piece)
+                };
+                grid.push(' ');
+                grid.push(cell);
+            }
+            grid.push('\n');
+        }
+        grid.push_str("  a b c d e f g h\n");
+
+        grid
+    }
+
+    /// 8x8 grid of which colors attack each square (W, B, WB or .)
+    pub fn print_attack_table(&self) -> String {
+        let white_attacks = self.get_all_attacks(WHITE);
+        let black_attacks = self.get_all_attacks(BLACK);
+        let mut table = String::new();
+
+        for rank in (0..8).rev() {
+            table.push_str(&format!("{} ", rank + 1));
+            for file in 0..8 {
+                let square_index = Square::new(file, rank).0;
+                let cell = match (get_bit(white_attacks, square_index), get_bit(black_attacks, square_index)) {
+                    (true, true) => "WB",
+                    (true, false) => "W",
+                    (false, true) => "B",
+                    (false, false) => ".",
+                };
+                table.push_str(&format!("{:>3}", cell));
+            }
+            table.push('\n');
+        }
+        table.push_str("    a  b  c  d  e  f  g  h\n");
+
+        if let Some(king_square) = self.find_king(self.current_turn) {
+            let attackers = self.attacked_by_bb(king_square) & self.bitboards.get_all_pieces(opposite_color(self.current_turn));
+            let attacker_squares: Vec<String> = iterate_bits(attackers)
+                .map(|index| index_to_square(index).to_string())
+                .collect();
+            table.push_str(&format!("{} king on {} attacked by: {}\n",
+                                    if self.current_turn == WHITE { "White" } else { "Black" },
+                                    king_square,
+                                    if attacker_squares.is_empty() { "none".to_string() } else { attacker_squares.join(", ") }));
+        }
+
+        table
+    }
+
     /// Test specific problematic sequence
     pub fn debug_e2e4_sequence(&mut self) -> Vec<String> {
         let mut debug_info = Vec::new();
@@ -150,9 +229,7 @@ impl Board {
             for (i, black_move) in black_moves.iter().enumerate() {
                 if let Ok(_) = self.try_make_move(*black_move) {
                     let white_moves = self.get_all_legal_moves();
-                    let move_str = format!("{}{}", 
-                                         square_to_algebraic(black_move.from), 
-                                         square_to_algebraic(black_move.to));
+                    let move_str = black_move.to_string();
                     
                     // Run perft depth 1 from this position
                     let nodes = crate::perft::perft(self, 1);
@@ -167,9 +244,7 @@ impl Board {
                         // Show some of the white moves
                         debug_info.push("  White moves:".to_string());
                         for (j, white_move) in white_moves.iter().take(5).enumerate() {
-                            let white_move_str = format!("{}{}", 
-                                                       square_to_algebraic(white_move.from), 
-                                                       square_to_algebraic(white_move.to));
+                            let white_move_str = white_move.to_string();
                             debug_info.push(format!("    {}: {}", j + 1, white_move_str));
                         }
                         if white_moves.len() > 5 {
@@ -221,9 +296,7 @@ impl Board {
                 for mv in &white_moves {
                     let piece = self.get_piece(mv.from);
                     let piece_type_val = piece_type(piece);
-                    let move_str = format!("{}{}", 
-                                         square_to_algebraic(mv.from), 
-                                         square_to_algebraic(mv.to));
+                    let move_str = mv.to_string();
                     
                     match piece_type_val {
                         1 => pawn_moves += 1,    // PAWN
@@ -250,9 +323,7 @@ impl Board {
                     for mv in &white_moves {
                         let piece = self.get_piece(mv.from);
                         if piece_type(piece) == 5 { // QUEEN
-                            let move_str = format!("{}{}", 
-                                                 square_to_algebraic(mv.from), 
-                                                 square_to_algebraic(mv.to));
+                            let move_str = mv.to_string();
                             debug_info.push(format!("  Queen: {}", move_str));
                         }
                     }
@@ -291,9 +362,9 @@ impl Board {
                     if piece_type(piece) == BISHOP && piece_color(piece) == WHITE {
                         let moves = self.get_legal_moves(square);
                         debug_info.push(format!("  Bishop at {}: {} moves", 
-                                               square_to_algebraic(square), moves.len()));
+                                               square, moves.len()));
                         for mv in moves {
-                            debug_info.push(format!("    -> {}", square_to_algebraic(mv)));
+                            debug_info.push(format!("    -> {}", mv));
                         }
                     }
                 }
@@ -310,9 +381,9 @@ impl Board {
                     if piece_type(piece) == QUEEN && piece_color(piece) == WHITE {
                         let moves = self.get_legal_moves(square);
                         debug_info.push(format!("  Queen at {}: {} moves", 
-                                               square_to_algebraic(square), moves.len()));
+                                               square, moves.len()));
                         for mv in moves {
-                            debug_info.push(format!("    -> {}", square_to_algebraic(mv)));
+                            debug_info.push(format!("    -> {}", mv));
                         }
                     }
                 }
@@ -390,51 +461,45 @@ impl Board {
 
     /// Format perft divide output like Stockfish for easy comparison
     pub fn debug_perft_divide_formatted(&self, depth: u32) -> Vec<String> {
-        let mut formatted_output = Vec::new();
-        let moves = self.get_all_legal_moves();
-        let mut total_nodes = 0;
-        
-        for mv in moves {
-            let mut temp_board = self.clone();
-            if let Ok(_) = temp_board.try_make_move(mv) {
-                let nodes = if depth > 1 {
-                    crate::perft::perft(&mut temp_board, depth - 1)
-                } else {
-                    1
-                };
-                
-                // Format as algebraic notation like Stockfish: "e2e4: 13164"
-                let move_str = format!("{}{}", 
-                    square_to_algebraic(mv.from), 
-                    square_to_algebraic(mv.to));
-                
-                formatted_output.push(format!("{}: {}", move_str, nodes));
-                total_nodes += nodes;
-            }
-        }
-        
-        // Sort moves alphabetically (like Stockfish does)
-        formatted_output.sort();
-        
+        let divide = self.perft_divide(depth);
+        let total_nodes: u64 = divide.iter().map(|(_, nodes)| nodes).sum();
+
+        // Format as algebraic notation like Stockfish: "e2e4: 13164"
+        let mut formatted_output: Vec<String> = divide.iter()
+            .map(|(mv, nodes)| format!("{}: {}", mv, nodes))
+            .collect();
+
         // Add total at the end
         formatted_output.push(format!("\nNodes searched: {}", total_nodes));
         
         formatted_output
     }
 
+    /// Hash of the whole move tree at the given depth (XOR of the leaf position keys)
+    /// Two move generators agree on the signature only if they reach the same leaves
+    pub fn perft_signature(&self, depth: u32) -> u64 {
+        let mut temp_board = self.clone();
+        crate::perft::perft_signature(&mut temp_board, depth)
+    }
+
+    /// Perft with a callback after each root move, for progress reporting on deep runs
+    pub fn perft_with_progress<F: Fn(u64, u64)>(&self, depth: u32, progress: F) -> u64 {
+        let mut temp_board = self.clone();
+        crate::perft::perft_with_progress(&mut temp_board, depth, progress)
+    }
+
     /// Debug specific move at deeper levels
     pub fn debug_move_deeper(&self, move_notation: &str, max_depth: u32) -> Vec<String> {
         let mut debug_info = Vec::new();
         
         // Parse the move notation (e.g., "g1f3")
-        let from_file = (move_notation.chars().nth(0).unwrap() as u8) - b'a';
-        let from_rank = (move_notation.chars().nth(1).unwrap() as u8) - b'1';
-        let to_file = (move_notation.chars().nth(2).unwrap() as u8) - b'a';
-        let to_rank = (move_notation.chars().nth(3).unwrap() as u8) - b'1';
-        
-        let from_square = Square::new(from_file, from_rank);
-        let to_square = Square::new(to_file, to_rank);
-        let target_move = Move::new(from_square, to_square);
+        let target_move = match Move::from_uci_str(move_notation, self) {
+            Ok(mv) => mv,
+            Err(e) => {
+                debug_info.push(format!("❌ {}", e));
+                return debug_info;
+            }
+        };
         
         debug_info.push(format!("🔍 Deep analysis of move {}", move_notation));
         
@@ -459,9 +524,7 @@ impl Board {
                             1
                         };
                         
-                        let move_str = format!("{}{}", 
-                            square_to_algebraic(mv.from), 
-                            square_to_algebraic(mv.to));
+                        let move_str = mv.to_string();
                         
                         move_results.push((move_str, nodes));
                         total_nodes += nodes;
@@ -516,9 +579,7 @@ impl Board {
         
         // Show all moves
         for mv in &moves {
-            let move_str = format!("{}{}", 
-                square_to_algebraic(mv.from), 
-                square_to_algebraic(mv.to));
+            let move_str = mv.to_string();
             let piece = temp_board.get_piece(mv.from);
             let piece_name = match piece_type(piece) {
                 1 => "Pawn",
@@ -672,7 +733,7 @@ impl Board {
         debug_info.push(format!("Legal moves: {}", legal_moves.len()));
         
         for mv in legal_moves {
-            debug_info.push(format!("  -> {}", square_to_algebraic(mv)));
+            debug_info.push(format!("  -> {}", mv));
         }
         
         debug_info
@@ -698,9 +759,7 @@ impl Board {
                 };
                 
                 // Create a readable move string
-                let move_str = format!("{}{}", 
-                    square_to_algebraic(mv.from), 
-                    square_to_algebraic(mv.to));
+                let move_str = mv.to_string();
                 
                 results.push((move_str, nodes, 1));
             }
@@ -743,9 +802,7 @@ impl Board {
                 6 => "King",
                 _ => "Unknown"
             };
-            let move_str = format!("{}{}", 
-                square_to_algebraic(mv.from), 
-                square_to_algebraic(mv.to));
+            let move_str = mv.to_string();
             move_strings.push(format!("{}: {}", move_str, piece_name));
         }
         
@@ -801,16 +858,9 @@ impl Board {
         
         // Make the sequence of moves
         for move_notation in moves {
-            let from_file = (move_notation.chars().nth(0).unwrap() as u8) - b'a';
-            let from_rank = (move_notation.chars().nth(1).unwrap() as u8) - b'1';
-            let to_file = (move_notation.chars().nth(2).unwrap() as u8) - b'a';
-            let to_rank = (move_notation.chars().nth(3).unwrap() as u8) - b'1';
-            
-            let from_square = Square::new(from_file, from_rank);
-            let to_square = Square::new(to_file, to_rank);
-            let target_move = Move::new(from_square, to_square);
-            
-            if let Ok(_) = temp_board.try_make_move(target_move) {
+            let made = Move::from_uci_str(move_notation, &temp_board)
+                .map(|mv| temp_board.try_make_move(mv).is_ok());
+            if let Ok(true) = made {
                 debug_info.push(format!("Made move: {}", move_notation));
             } else {
                 debug_info.push(format!("Failed to make move: {}", move_notation));
@@ -832,9 +882,7 @@ impl Board {
                     1
                 };
                 
-                let move_str = format!("{}{}", 
-                    square_to_algebraic(mv.from), 
-                    square_to_algebraic(mv.to));
+                let move_str = mv.to_string();
                 
                 move_results.push((move_str, nodes));
                 total_nodes += nodes;
@@ -860,15 +908,7 @@ impl Board {
         
         // Make setup moves
         for move_notation in setup_moves {
-            let from_file = (move_notation.chars().nth(0).unwrap() as u8) - b'a';
-            let from_rank = (move_notation.chars().nth(1).unwrap() as u8) - b'1';
-            let to_file = (move_notation.chars().nth(2).unwrap() as u8) - b'a';
-            let to_rank = (move_notation.chars().nth(3).unwrap() as u8) - b'1';
-            
-            let from_square = Square::new(from_file, from_rank);
-            let to_square = Square::new(to_file, to_rank);
-            let target_move = Move::new(from_square, to_square);
-            
+            let target_move = Move::from_uci_str(move_notation, &temp_board).expect("Failed to parse move");
             temp_board.try_make_move(target_move).expect("Failed to make move");
         }
         
@@ -886,24 +926,7 @@ impl Board {
                     1
                 };
                 
-                // FIX: Include promotion notation
-                let move_str = if let Some(promotion) = mv.promotion {
-                    let promotion_char = match promotion {
-                        QUEEN => 'q',
-                        ROOK => 'r', 
-                        BISHOP => 'b',
-                        KNIGHT => 'n',
-                        _ => '?',
-                    };
-                    format!("{}{}{}", 
-                        square_to_algebraic(mv.from), 
-                        square_to_algebraic(mv.to),
-                        promotion_char)
-                } else {
-                    format!("{}{}", 
-                        square_to_algebraic(mv.from), 
-                        square_to_algebraic(mv.to))
-                };
+                let move_str = mv.to_string();
                 
                 move_results.push((move_str, nodes));
                 total_nodes += nodes;
@@ -956,9 +979,7 @@ impl Board {
         
         // Show all moves for analysis
         for mv in &moves {
-            let move_str = format!("{}{}", 
-                square_to_algebraic(mv.from), 
-                square_to_algebraic(mv.to));
+            let move_str = mv.to_string();
             let piece = temp_board.get_piece(mv.from);
             let piece_name = match piece_type(piece) {
                 1 => "Pawn",
@@ -1040,3 +1061,9 @@ impl Board {
     }
     
 }
+
+impl std::fmt::Display for Board {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.display())
+    }
+}
@@ -0,0 +1,37 @@
+use super::Board;
+
+impl Board {
+    /// Full board state as JSON, move history included so `undo_move` keeps working after a reload
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("board state always serializes")
+    }
+
+    /// Board saved by `to_json`; the logger isn't saved and has to be set again.
+    /// The bitboards and hash are rebuilt from the squares, so only the squares are trusted
+    pub fn from_json(json: &str) -> Result<Board, serde_json::Error> {
+        crate::bitboard::initialize_engine();
+
+        let mut board: Board = serde_json::from_str(json)?;
+        board.bitboards.rebuild_from_squares(&board.squares);
+        board.position_hash = board.compute_hash();
+        board.validate().map_err(serde::de::Error::custom)?;
+        Ok(board)
+    }
+}
+
+/// serde only handles arrays up to 32 elements, so the squares go through a slice
+pub(super) mod squares {
+    use crate::types::Piece;
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(squares: &[Piece; 64], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(squares)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<[Piece; 64], D::Error> {
+        let squares = Vec::<Piece>::deserialize(deserializer)?;
+        let count = squares.len();
+        squares.try_into().map_err(|_| D::Error::invalid_length(count, &"64 squares"))
+    }
+}
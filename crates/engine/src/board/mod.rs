@@ -5,47 +5,70 @@ pub mod moves;
 pub mod validation;
 pub mod state;
 pub mod debug;
+pub mod pgn;
+pub mod json;
+pub use pgn::{PgnMetadata, PgnError};
 use crate::bitboard::BitboardManager; 
 use crate::ChessLogger;
+use serde::{Deserialize, Serialize};
 
+/// Centipawn values by piece type for `material_balance` and `see`; the king isn't counted
+const MATERIAL_VALUES: [i32; 7] = [0, 100, 320, 330, 500, 900, 0];
 
-#[derive(Debug, Clone)]
+fn standard_castling_rook_files() -> [u8; 4] {
+    STANDARD_CASTLING_ROOK_FILES
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Board {
+    #[serde(with = "json::squares")]
     pub squares: [Piece; 64],
     pub current_turn: u8,
     pub move_history: Vec<GameMove>,
+    pub position_history: Vec<u64>, // Position keys before each move, for repetition detection
     pub game_status: GameStatus,
     pub half_move_clock: u16,
     pub full_move_number: u16,
     pub castling_rights: u8,
+    #[serde(default)]
+    pub chess960: bool, // Castling is written as the king taking its own rook
+    #[serde(default = "standard_castling_rook_files")]
+    pub castling_rook_files: [u8; 4], // Home file of the rook behind each castling right
     pub en_passant_target: Option<Square>,
     pub en_passant_pawn: Option<Square>,
     pub ignore_square_for_threats: RefCell<Option<Square>>,
     pub bitboards: BitboardManager,
+    #[serde(skip)]
     pub logger: Option<std::rc::Rc<std::cell::RefCell<ChessLogger>>>,
+    pub position_hash: u64, // Zobrist key, kept up to date move by move
 }
 
 impl Board {
     pub fn new() -> Self {
 
-        crate::bitboard::initialize_knight_attacks();
+        crate::bitboard::initialize_engine();
 
         let mut board = Self {
             squares: [EMPTY; 64],
             current_turn: WHITE,
             move_history: Vec::new(),
+            position_history: Vec::new(),
             game_status: GameStatus::InProgress,
             half_move_clock: 0,
             full_move_number: 1,
             castling_rights: ALL_CASTLING_RIGHTS,
+            chess960: false,
+            castling_rook_files: STANDARD_CASTLING_ROOK_FILES,
             en_passant_target: None,
             en_passant_pawn: None,
             ignore_square_for_threats: RefCell::new(None),
             bitboards: BitboardManager::new(),
             logger: None,
+            position_hash: 0,
         };
 
         board.setup_starting_position();
+        board.position_hash = board.compute_hash();
         board
     }
 
@@ -101,25 +124,31 @@ impl Board {
     }
 
     // FEN parsing functionality
-    pub fn from_fen(fen: &str) -> Result<Self, String> {
+    pub fn from_fen(fen: &str) -> Result<Self, MoveError> {
         let parts: Vec<&str> = fen.split_whitespace().collect();
         if parts.len() != 6 {
-            return Err("Invalid FEN: must have 6 parts".to_string());
+            return Err(invalid_fen("must have 6 parts"));
         }
 
+        crate::bitboard::initialize_engine();
+
         let mut board = Self {
             squares: [EMPTY; 64],
             current_turn: WHITE,
             move_history: Vec::new(),
+            position_history: Vec::new(),
             game_status: GameStatus::InProgress,
             half_move_clock: 0,
             full_move_number: 1,
             castling_rights: 0,
+            chess960: false,
+            castling_rook_files: STANDARD_CASTLING_ROOK_FILES,
             en_passant_target: None,
             en_passant_pawn: None,
             ignore_square_for_threats: RefCell::new(None),   
             bitboards: crate::bitboard::BitboardManager::new(),      
             logger: None,   
+            position_hash: 0,
         };
 
         // Parse piece placement (part 0)
@@ -129,7 +158,7 @@ impl Board {
         board.current_turn = match parts[1] {
             "w" => WHITE,
             "b" => BLACK,
-            _ => return Err("Invalid active color".to_string()),
+            _ => return Err(invalid_fen("Invalid active color")),
         };
         
         // Parse castling rights (part 2)
@@ -140,19 +169,45 @@ impl Board {
         
         // Parse halfmove clock (part 4)
         board.half_move_clock = parts[4].parse()
-            .map_err(|_| "Invalid halfmove clock")?;
+            .map_err(|_| invalid_fen("Invalid halfmove clock"))?;
         
         // Parse fullmove number (part 5)
         board.full_move_number = parts[5].parse()
-            .map_err(|_| "Invalid fullmove number")?;
+            .map_err(|_| invalid_fen("Invalid fullmove number"))?;
+
+        // Rejects what parsing alone lets through, like a missing king or a pawn on the back rank
+        board.validate().map_err(|error| invalid_fen(&error.to_string()))?;
+
+        board.position_hash = board.compute_hash();
+        board.update_game_status();
+        Ok(board)
+    }
+
+    /// Chess960 position from `fen`, castling with the rooks on the given files:
+    /// (white kingside, white queenside, black kingside, black queenside)
+    pub fn from_fen_960(fen: &str, rook_files: (u8, u8, u8, u8)) -> Result<Self, MoveError> {
+        let mut board = Self::from_fen(fen)?;
+        board.chess960 = true;
+        board.castling_rook_files = [rook_files.0, rook_files.1, rook_files.2, rook_files.3];
+
+        for (index, &file) in board.castling_rook_files.iter().enumerate() {
+            if !has_castling_right(board.castling_rights, 1 << index) {
+                continue;
+            }
+            let (color, back_rank) = if index < 2 { (WHITE, 0) } else { (BLACK, 7) };
+            if file > 7 || board.get_piece(Square::new(file, back_rank)) != make_piece(ROOK, color) {
+                return Err(invalid_fen(&format!("No rook to castle with on file {}", file)));
+            }
+        }
 
+        board.update_game_status();
         Ok(board)
     }
 
-    fn parse_piece_placement(&mut self, placement: &str) -> Result<(), String> {
+    fn parse_piece_placement(&mut self, placement: &str) -> Result<(), MoveError> {
         let ranks: Vec<&str> = placement.split('/').collect();
         if ranks.len() != 8 {
-            return Err("Invalid piece placement: must have 8 ranks".to_string());
+            return Err(invalid_fen("Invalid piece placement: must have 8 ranks"));
         }
 
         for (rank_idx, rank_str) in ranks.iter().enumerate() {
@@ -165,7 +220,7 @@ impl Board {
                     file += empty_squares;
                 } else {
                     if file >= 8 {
-                        return Err("Too many pieces in rank".to_string());
+                        return Err(invalid_fen("Too many pieces in rank"));
                     }
 
                     let piece = self.char_to_piece(ch)?;
@@ -175,14 +230,14 @@ impl Board {
             }
 
             if file != 8 {
-                return Err("Incomplete rank".to_string());
+                return Err(invalid_fen("Incomplete rank"));
             }
         }
 
         Ok(())
     }
 
-    fn char_to_piece(&self, ch: char) -> Result<Piece, String> {
+    fn char_to_piece(&self, ch: char) -> Result<Piece, MoveError> {
         let piece_type = match ch.to_ascii_lowercase() {
             'p' => PAWN,
             'n' => KNIGHT,
@@ -190,33 +245,52 @@ impl Board {
             'r' => ROOK,
             'q' => QUEEN,
             'k' => KING,
-            _ => return Err(format!("Unknown piece: {}", ch)),
+            _ => return Err(invalid_fen(&format!("Unknown piece: {}", ch))),
         };
 
         let color = if ch.is_uppercase() { WHITE } else { BLACK };
         Ok(make_piece(piece_type, color))
     }
 
-    fn parse_castling_rights(&mut self, castling_str: &str) -> Result<(), String> {
+    /// Accepts `KQkq` (X-FEN, meaning the outermost rook for Chess960 setups) and Shredder-FEN
+    /// rook files like `HAha`. Anything but the standard king and rook squares turns on `chess960`
+    fn parse_castling_rights(&mut self, castling_str: &str) -> Result<(), MoveError> {
+        self.castling_rights = 0;
         if castling_str == "-" {
-            self.castling_rights = 0;
             return Ok(());
         }
 
         for ch in castling_str.chars() {
-            match ch {
-                'K' => self.castling_rights |= WHITE_KINGSIDE,
-                'Q' => self.castling_rights |= WHITE_QUEENSIDE,
-                'k' => self.castling_rights |= BLACK_KINGSIDE,
-                'q' => self.castling_rights |= BLACK_QUEENSIDE,
-                _ => return Err(format!("Invalid castling right: {}", ch)),
+            let color = if ch.is_ascii_uppercase() { WHITE } else { BLACK };
+            let back_rank = if color == WHITE { 0 } else { 7 };
+            // A right without its king on the back rank or a rook to castle with is dropped
+            let king_file = self.find_king(color).filter(|square| square.rank() == back_rank).map(|square| square.file());
+            let is_own_rook = |file: u8| self.get_piece(Square::new(file, back_rank)) == make_piece(ROOK, color);
+
+            let (kingside, rook_file) = match (ch.to_ascii_lowercase(), king_file) {
+                ('k', Some(king_file)) => (true, (king_file + 1..8).rev().find(|&file| is_own_rook(file))),
+                ('q', Some(king_file)) => (false, (0..king_file).find(|&file| is_own_rook(file))),
+                ('k' | 'q' | 'a'..='h', None) => continue,
+                (file @ 'a'..='h', Some(king_file)) if file as u8 - b'a' != king_file => {
+                    let file = file as u8 - b'a';
+                    (file > king_file, Some(file).filter(|&file| is_own_rook(file)))
+                }
+                _ => return Err(invalid_fen(&format!("Invalid castling right: {}", ch))),
+            };
+            let Some(rook_file) = rook_file else { continue };
+
+            let right = castling_right(color, kingside);
+            self.castling_rights |= right;
+            self.castling_rook_files[right.trailing_zeros() as usize] = rook_file;
+            if king_file.is_some_and(|file| file != 4) || rook_file != STANDARD_CASTLING_ROOK_FILES[right.trailing_zeros() as usize] {
+                self.chess960 = true;
             }
         }
 
         Ok(())
     }
 
-    fn parse_en_passant(&mut self, en_passant_str: &str) -> Result<(), String> {
+    fn parse_en_passant(&mut self, en_passant_str: &str) -> Result<(), MoveError> {
         if en_passant_str == "-" {
             self.en_passant_target = None;
             self.en_passant_pawn = None;
@@ -224,7 +298,7 @@ impl Board {
         }
 
         if en_passant_str.len() != 2 {
-            return Err("Invalid en passant square".to_string());
+            return Err(invalid_fen("Invalid en passant square"));
         }
 
         let chars: Vec<char> = en_passant_str.chars().collect();
@@ -232,7 +306,7 @@ impl Board {
         let rank = (chars[1] as u8).wrapping_sub(b'1');
 
         if file >= 8 || rank >= 8 {
-            return Err("Invalid en passant square coordinates".to_string());
+            return Err(invalid_fen("Invalid en passant square coordinates"));
         }
 
         self.en_passant_target = Some(Square::new(file, rank));
@@ -244,18 +318,521 @@ impl Board {
         Ok(())
     }
 
+    // FEN serialization functionality
+    pub fn to_fen(&self) -> String {
+        let active_color = if self.current_turn == WHITE { "w" } else { "b" };
+        let en_passant = match self.en_passant_target {
+            Some(square) => square.to_string(),
+            None => "-".to_string(),
+        };
+
+        format!(
+            "{} {} {} {} {} {}",
+            self.piece_placement_to_fen(),
+            active_color,
+            self.castling_rights_to_fen(),
+            en_passant,
+            self.half_move_clock,
+            self.full_move_number
+        )
+    }
+
+    fn piece_placement_to_fen(&self) -> String {
+        let mut placement = String::new();
+
+        for rank in (0..8).rev() {
+            let mut empty_squares = 0;
+
+            for file in 0..8 {
+                let piece = self.squares[Square::new(file, rank).0 as usize];
+                if is_empty(piece) {
+                    empty_squares += 1;
+                } else {
+                    if empty_squares > 0 {
+                        placement.push_str(&empty_squares.to_string());
+                        empty_squares = 0;
+                    }
+                    placement.push(self.piece_to_char(piece));
+                }
+            }
+
+            if empty_squares > 0 {
+                placement.push_str(&empty_squares.to_string());
+            }
+            if rank > 0 {
+                placement.push('/');
+            }
+        }
+
+        placement
+    }
+
+    fn piece_to_char(&self, piece: Piece) -> char {
+        let ch = match piece_type(piece) {
+            PAWN => 'p',
+            KNIGHT => 'n',
+            BISHOP => 'b',
+            ROOK => 'r',
+            QUEEN => 'q',
+            KING => 'k',
+            _ => '?',
+        };
+
+        if piece_color(piece) == WHITE { ch.to_ascii_uppercase() } else { ch }
+    }
+
+    fn castling_rights_to_fen(&self) -> String {
+        let mut castling = String::new();
+        for (index, symbol) in ['K', 'Q', 'k', 'q'].into_iter().enumerate() {
+            if !has_castling_right(self.castling_rights, 1 << index) {
+                continue;
+            }
+            if self.chess960 {
+                // Shredder-FEN names the rook's file
+                let file = (b'a' + self.castling_rook_files[index]) as char;
+                castling.push(if symbol.is_ascii_uppercase() { file.to_ascii_uppercase() } else { file });
+            } else {
+                castling.push(symbol);
+            }
+        }
+
+        if castling.is_empty() { "-".to_string() } else { castling }
+    }
+
+
+    /// Number of pieces of the given color and type (bitboard popcount)
+    pub fn num_pieces(&self, color: u8, piece_type: u8) -> u8 {
+        self.bitboards.get_pieces(color, piece_type).count_ones() as u8
+    }
+
+    /// `num_pieces` widened to `u32`
+    pub fn count_pieces(&self, color: u8, piece_type: u8) -> u32 {
+        self.num_pieces(color, piece_type) as u32
+    }
+
+    /// White's material minus Black's, in centipawns
+    pub fn material_balance(&self) -> i32 {
+        (PAWN..=QUEEN).map(|kind| {
+            (self.count_pieces(WHITE, kind) as i32 - self.count_pieces(BLACK, kind) as i32) * MATERIAL_VALUES[kind as usize]
+        }).sum()
+    }
+
+    /// Static exchange evaluation: centipawns the side making `capture` nets from the exchange on
+    /// its target square, both sides recapturing with their least valuable attacker and free to
+    /// stop when continuing would lose
+    pub fn see(&self, capture: Move) -> i32 {
+        let value_of = |piece: u8| MATERIAL_VALUES[piece_type(piece) as usize];
+        let mut occupancy = self.bitboards.all_pieces;
+        let mut gain = [0i32; 32];
 
-    /// Check if the current player is in check
-    pub fn is_in_check(&self) -> bool {
-        let king_square = match self.find_king(self.current_turn) {
-            Some(square) => square,
-            None => return false,
+        gain[0] = if self.is_en_passant_move(capture) {
+            if let Some(pawn_square) = self.en_passant_pawn {
+                occupancy &= !(1u64 << pawn_square.0);
+            }
+            value_of(PAWN)
+        } else {
+            value_of(self.get_piece(capture.to))
         };
-        
-        let opponent_color = opposite_color(self.current_turn);
-        let checking_pieces = self.find_checking_pieces(king_square, opponent_color);
-        !checking_pieces.is_empty()
-    }    
+
+        // The piece standing on the target square, which the next capture would win
+        let mut on_square = capture.promotion.unwrap_or_else(|| piece_type(self.get_piece(capture.from)));
+        if let Some(promotion) = capture.promotion {
+            gain[0] += value_of(promotion) - value_of(PAWN);
+        }
+
+        let mut from_bit = 1u64 << capture.from.0;
+        let mut side = piece_color(self.get_piece(capture.from));
+        let mut depth = 0;
+        loop {
+            depth += 1;
+            gain[depth] = value_of(on_square) - gain[depth - 1];
+            if depth == gain.len() - 1 {
+                break;
+            }
+
+            occupancy &= !from_bit;
+            side = opposite_color(side);
+            let attackers = self.get_attackers_to(capture.to, occupancy);
+            let ours = attackers & self.bitboards.get_all_pieces(side);
+
+            let least_valuable = (PAWN..=KING)
+                .map(|kind| (kind, ours & self.bitboards.get_pieces(side, kind)))
+                .find(|&(_, bits)| bits != 0);
+            match least_valuable {
+                // The king may only recapture when nothing defends the square
+                Some((KING, _)) if attackers & self.bitboards.get_all_pieces(opposite_color(side)) != 0 => break,
+                Some((kind, bits)) => {
+                    from_bit = bits & bits.wrapping_neg();
+                    on_square = kind;
+                }
+                None => break,
+            }
+        }
+
+        // Walk back up the exchange, letting each side stand pat when capturing loses
+        while depth > 1 {
+            depth -= 1;
+            gain[depth - 1] = -(-gain[depth - 1]).max(gain[depth]);
+        }
+
+        gain[0]
+    }
+
+    /// Nothing tactical left for the side to move: not in check, no pawn on its seventh rank, and
+    /// every capture loses material by SEE. Quiescence can stand pat in such positions.
+    /// Captures come straight from the attack masks, so a pinned piece's capture still counts
+    pub fn is_quiet_position(&self) -> bool {
+        let color = self.current_turn;
+        let seventh_rank = if color == WHITE { crate::bitboard::RANK_7 } else { crate::bitboard::RANK_2 };
+        if self.bitboards.get_pieces(color, PAWN) & seventh_rank != 0 || self.is_in_check() {
+            return false;
+        }
+
+        crate::bitboard::iterate_bits(self.bitboards.get_all_pieces(color)).all(|from| {
+            let targets = self.get_piece_captures(Square(from));
+            crate::bitboard::iterate_bits(targets).all(|to| self.see(Move::new(Square(from), Square(to))) < 0)
+        })
+    }
+
+    /// Remaining non-pawn material as a fraction of the starting total: 1.0 = middlegame, 0.0 = endgame
+    pub fn game_phase(&self) -> f32 {
+        use crate::pst::{MAX_PHASE, PHASE_WEIGHTS};
+
+        let mut phase = 0;
+        for piece_type in [KNIGHT, BISHOP, ROOK, QUEEN] {
+            let count = self.num_pieces(WHITE, piece_type) as i32 + self.num_pieces(BLACK, piece_type) as i32;
+            phase += count * PHASE_WEIGHTS[piece_type as usize];
+        }
+
+        phase.min(MAX_PHASE) as f32 / MAX_PHASE as f32
+    }
+
+    /// Piece-square score from White's perspective, tapered between the middlegame and endgame tables
+    pub fn pst_score(&self) -> i32 {
+        use crate::pst::{pst_score_with, ENDGAME_PST, MIDDLEGAME_PST};
+
+        let phase = self.game_phase();
+        let mg = pst_score_with(self, &MIDDLEGAME_PST) as f32;
+        let eg = pst_score_with(self, &ENDGAME_PST) as f32;
+        (mg * phase + eg * (1.0 - phase)).round() as i32
+    }
+
+    /// Material plus piece-square tables for the side to move; the AI's evaluation
+    /// adds mobility, king safety and pawn structure on top
+    pub fn static_eval(&self) -> i32 {
+        let score = self.material_balance() + self.pst_score();
+        if self.current_turn == WHITE { score } else { -score }
+    }
+
+    /// Total number of pieces (king included) for the given color
+    pub fn total_piece_count(&self, color: u8) -> u8 {
+        self.bitboards.get_all_pieces(color).count_ones() as u8
+    }
+
+    /// Material of the given color excluding pawns and king, in centipawns
+    pub fn non_pawn_material(&self, color: u8) -> u32 {
+        (KNIGHT..=QUEEN).map(|kind| self.count_pieces(color, kind) * MATERIAL_VALUES[kind as usize] as u32).sum()
+    }
+
+    /// True if the file has no pawns of either color
+    pub fn is_open_file(&self, file: u8) -> bool {
+        let pawns = self.bitboards.white_pawns | self.bitboards.black_pawns;
+        (crate::bitboard::FILE_MASKS[file as usize] & pawns) == 0
+    }
+
+    /// True if the file has no pawns of `color` but has opposing pawns
+    pub fn is_half_open_file(&self, file: u8, color: u8) -> bool {
+        let file_mask = crate::bitboard::FILE_MASKS[file as usize];
+        let friendly_pawns = self.bitboards.get_pieces(color, PAWN);
+        let enemy_pawns = self.bitboards.get_pieces(opposite_color(color), PAWN);
+        (file_mask & friendly_pawns) == 0 && (file_mask & enemy_pawns) != 0
+    }
+
+    /// Zobrist hash of the current position (pieces, side to move, castling, en passant)
+    pub fn position_key(&self) -> u64 {
+        self.position_hash
+    }
+
+    /// Zobrist hash computed from scratch; `position_hash` tracks the same value incrementally
+    pub fn compute_hash(&self) -> u64 {
+        crate::bitboard::initialize_zobrist_keys();
+
+        let mut key = 0u64;
+        for square_index in crate::bitboard::iterate_bits(self.bitboards.all_pieces) {
+            let piece = self.squares[square_index as usize];
+            key ^= crate::bitboard::zobrist_piece_key(piece, square_index);
+        }
+
+        if self.current_turn == BLACK {
+            key ^= crate::bitboard::zobrist_side_key();
+        }
+        key ^= crate::bitboard::zobrist_castling_key(self.castling_rights);
+        key ^= crate::bitboard::zobrist_en_passant_key(self.en_passant_target);
+
+        key
+    }
+
+    /// Fold the move just made (the last entry of `move_history`) into `position_hash`
+    pub fn update_hash_for_move(&mut self, mv: Move) {
+        use crate::bitboard::{zobrist_castling_key, zobrist_en_passant_key, zobrist_piece_key, zobrist_side_key};
+
+        let Some(game_move) = self.move_history.last() else {
+            return;
+        };
+        let mut hash = self.position_hash;
+        if game_move.is_castling {
+            let (king_to, rook_from, rook_to) = self.castling_squares(mv);
+            let king = game_move.moved_piece;
+            let rook = make_piece(ROOK, piece_color(king));
+            hash ^= zobrist_piece_key(king, mv.from.0) ^ zobrist_piece_key(king, king_to.0);
+            hash ^= zobrist_piece_key(rook, rook_from.0) ^ zobrist_piece_key(rook, rook_to.0);
+        } else {
+            let landed = self.squares[mv.to.0 as usize];
+            let moved = if mv.is_promotion() { make_piece(PAWN, piece_color(landed)) } else { landed };
+            hash ^= zobrist_piece_key(moved, mv.from.0) ^ zobrist_piece_key(landed, mv.to.0);
+        }
+
+        if !is_empty(game_move.captured_piece) {
+            let captured_square = if game_move.is_en_passant {
+                game_move.previous_en_passant_pawn.unwrap_or(mv.to)
+            } else {
+                mv.to
+            };
+            hash ^= zobrist_piece_key(game_move.captured_piece, captured_square.0);
+        }
+
+        hash ^= zobrist_side_key();
+        hash ^= zobrist_castling_key(game_move.previous_castling_rights) ^ zobrist_castling_key(self.castling_rights);
+        hash ^= zobrist_en_passant_key(game_move.previous_en_passant_target) ^ zobrist_en_passant_key(self.en_passant_target);
+
+        self.position_hash = hash;
+    }
+
+    /// Convert a move to Standard Algebraic Notation (e.g. "Nf3", "exd5", "e8=Q+", "O-O")
+    pub fn move_to_san(&self, mv: Move) -> String {
+        let piece = self.get_piece(mv.from);
+        let piece_type_val = piece_type(piece);
+        let mut san = String::new();
+
+        if let Some(kingside) = self.is_castling_move(mv) {
+            san.push_str(if kingside { "O-O" } else { "O-O-O" });
+        } else {
+            let is_capture = !is_empty(self.get_piece(mv.to)) || self.is_en_passant_move(mv);
+
+            if piece_type_val == PAWN {
+                if is_capture {
+                    san.push((b'a' + mv.from.file()) as char);
+                }
+            } else {
+                san.push(piece_to_san_char(piece_type_val));
+                san.push_str(&self.san_disambiguation(mv, piece_type_val));
+            }
+
+            if is_capture {
+                san.push('x');
+            }
+            san.push_str(&mv.to.to_string());
+
+            if let Some(promotion) = mv.promotion {
+                san.push('=');
+                san.push(piece_to_san_char(promotion));
+            }
+        }
+
+        // Check and checkmate suffixes
+        let mut temp_board = self.clone();
+        if temp_board.try_make_move(mv).is_ok() && temp_board.is_in_check() {
+            if temp_board.get_all_legal_moves().is_empty() {
+                san.push('#');
+            } else {
+                san.push('+');
+            }
+        }
+
+        san
+    }
+
+    /// Departure file/rank needed to make a SAN move unambiguous
+    fn san_disambiguation(&self, mv: Move, piece_type_val: u8) -> String {
+        let rivals: Vec<Square> = self.get_all_legal_moves()
+            .into_iter()
+            .filter(|other| {
+                other.to == mv.to &&
+                other.from != mv.from &&
+                piece_type(self.get_piece(other.from)) == piece_type_val
+            })
+            .map(|other| other.from)
+            .collect();
+
+        if rivals.is_empty() {
+            return String::new();
+        }
+
+        let file_char = (b'a' + mv.from.file()) as char;
+        let rank_char = (b'1' + mv.from.rank()) as char;
+
+        if rivals.iter().all(|sq| sq.file() != mv.from.file()) {
+            file_char.to_string()
+        } else if rivals.iter().all(|sq| sq.rank() != mv.from.rank()) {
+            rank_char.to_string()
+        } else {
+            format!("{}{}", file_char, rank_char)
+        }
+    }
+
+    /// Parse a move in Standard Algebraic Notation (e.g. "Nf3", "Nbd2", "exd5", "d8=Q", "O-O") and play it
+    pub fn make_move_san(&mut self, san: &str) -> Result<GameMove, MoveError> {
+        let mv = self.parse_san(san)?;
+        self.try_make_move(mv)
+    }
+
+    /// Find the unique legal move described by a SAN string
+    fn parse_san(&self, san: &str) -> Result<Move, MoveError> {
+        let invalid = || MoveError::InvalidString(san.to_string());
+        let notation = san.trim().trim_end_matches(['+', '#', '!', '?']);
+        let legal_moves = self.get_all_legal_moves();
+
+        // Castling
+        let castle_side = match notation {
+            "O-O" | "0-0" => Some(true),
+            "O-O-O" | "0-0-0" => Some(false),
+            _ => None,
+        };
+        if let Some(kingside) = castle_side {
+            return legal_moves.into_iter()
+                .find(|&mv| self.is_castling_move(mv) == Some(kingside))
+                .ok_or_else(|| MoveError::IllegalMove(san.to_string()));
+        }
+
+        let mut chars: Vec<char> = notation.chars().collect();
+
+        // Piece prefix (none = pawn)
+        let moving_type = match chars.first() {
+            Some('N') => KNIGHT,
+            Some('B') => BISHOP,
+            Some('R') => ROOK,
+            Some('Q') => QUEEN,
+            Some('K') => KING,
+            Some(_) => PAWN,
+            None => return Err(invalid()),
+        };
+        if moving_type != PAWN {
+            chars.remove(0);
+        }
+
+        // Promotion suffix, with or without '='
+        let mut promotion = None;
+        if let Some(&last) = chars.last() {
+            let promotion_type = match last {
+                'N' => Some(KNIGHT),
+                'B' => Some(BISHOP),
+                'R' => Some(ROOK),
+                'Q' => Some(QUEEN),
+                _ => None,
+            };
+            if promotion_type.is_some() {
+                promotion = promotion_type;
+                chars.pop();
+                if chars.last() == Some(&'=') {
+                    chars.pop();
+                }
+            }
+        }
+
+        // Destination square
+        if chars.len() < 2 {
+            return Err(invalid());
+        }
+        let rank_char = chars.pop().unwrap();
+        let file_char = chars.pop().unwrap();
+        if !('a'..='h').contains(&file_char) || !('1'..='8').contains(&rank_char) {
+            return Err(MoveError::OutOfBounds(san.to_string()));
+        }
+        let to = Square::new(file_char as u8 - b'a', rank_char as u8 - b'1');
+
+        // Capture marker and departure file/rank disambiguation
+        let mut is_capture = false;
+        let mut from_file = None;
+        let mut from_rank = None;
+        for c in chars {
+            match c {
+                'x' => is_capture = true,
+                'a'..='h' => from_file = Some(c as u8 - b'a'),
+                '1'..='8' => from_rank = Some(c as u8 - b'1'),
+                _ => return Err(invalid()),
+            }
+        }
+
+        let candidates: Vec<Move> = legal_moves.into_iter()
+            .filter(|&mv| {
+                mv.to == to &&
+                mv.promotion == promotion &&
+                piece_type(self.get_piece(mv.from)) == moving_type &&
+                from_file.is_none_or(|file| mv.from.file() == file) &&
+                from_rank.is_none_or(|rank| mv.from.rank() == rank) &&
+                (!is_capture || !is_empty(self.get_piece(mv.to)) || self.is_en_passant_move(mv))
+            })
+            .collect();
+
+        // Zero matches is illegal, several means the SAN was ambiguous
+        match candidates.as_slice() {
+            [mv] => Ok(*mv),
+            _ => Err(MoveError::IllegalMove(san.to_string())),
+        }
+    }
+
+    /// Describe a move in detail: captures, special moves, checks and notation
+    pub fn annotate_move(&self, mv: Move) -> MoveAnnotation {
+        let is_en_passant = self.is_en_passant_move(mv);
+        let is_castling = self.is_castling_move(mv);
+        let target_piece = self.get_piece(mv.to);
+        let captured_piece = if is_en_passant {
+            Some(PAWN)
+        } else if !is_empty(target_piece) && is_castling.is_none() {
+            Some(piece_type(target_piece))
+        } else {
+            None
+        };
+
+        let mut is_check = false;
+        let mut is_checkmate = false;
+        let mut is_double_check = false;
+        let mut gives_discovered_check = false;
+
+        let mut temp_board = self.clone();
+        if temp_board.try_make_move(mv).is_ok() {
+            if let Some(king_square) = temp_board.find_king(temp_board.current_turn) {
+                let checking_pieces = temp_board.find_checking_pieces(king_square, self.current_turn);
+
+                // Squares the moving side's pieces landed on (rook included for castling)
+                let moved_to = if is_castling.is_some() {
+                    let (king_to, _, rook_to) = self.castling_squares(mv);
+                    vec![king_to, rook_to]
+                } else {
+                    vec![mv.to]
+                };
+
+                is_check = !checking_pieces.is_empty();
+                is_double_check = checking_pieces.len() == 2;
+                gives_discovered_check = checking_pieces.iter().any(|sq| !moved_to.contains(sq));
+                is_checkmate = is_check && temp_board.get_all_legal_moves().is_empty();
+            }
+        }
+
+        MoveAnnotation {
+            is_capture: captured_piece.is_some(),
+            captured_piece,
+            is_en_passant,
+            is_castling,
+            is_promotion: mv.promotion,
+            is_check,
+            is_checkmate,
+            is_double_check,
+            gives_discovered_check,
+            san: self.move_to_san(mv),
+            uci: mv.to_string(),
+        }
+    }
 
     #[cfg(debug_assertions)]
     pub fn verify_bitboards(&self) -> bool {
@@ -286,6 +863,34 @@ impl Board {
         println!("✅ Bitboard synchronization check PASSED");
         true
     }
+
+    /// True if the squares array and every piece bitboard describe the same position
+    pub fn check_consistency_with_bitboards(&self) -> bool {
+        let piece_types = [PAWN, KNIGHT, BISHOP, ROOK, QUEEN, KING];
+        let mut occupied = crate::bitboard::BITBOARD_EMPTY;
+
+        for square_index in 0..64u8 {
+            let piece = self.squares[square_index as usize];
+            if !is_empty(piece) {
+                occupied |= 1u64 << square_index;
+            }
+
+            // The square must be set in exactly the bitboard matching the array, and no other
+            for color in [WHITE, BLACK] {
+                for piece_type_val in piece_types {
+                    let expected = !is_empty(piece) && piece == make_piece(piece_type_val, color);
+                    let actual = crate::bitboard::get_bit(self.bitboards.get_pieces(color, piece_type_val), square_index);
+                    if expected != actual {
+                        return false;
+                    }
+                }
+            }
+        }
+
+        let all_pieces = self.bitboards.get_all_pieces(WHITE) | self.bitboards.get_all_pieces(BLACK);
+        all_pieces == occupied && self.bitboards.all_pieces == occupied
+    }
+
     pub fn set_logger(&mut self, logger: std::rc::Rc<std::cell::RefCell<crate::ChessLogger>>) {
         self.logger = Some(logger);
     }
@@ -297,29 +902,19 @@ impl Default for Board {
     }
 }
 
-// Helper function (outside the impl block)
-pub fn square_to_algebraic(square: Square) -> String {
-    let file = (b'a' + square.file()) as char;
-    let rank = (b'1' + square.rank()) as char;
-    format!("{}{}", file, rank)
+fn invalid_fen(reason: &str) -> MoveError {
+    MoveError::InvalidFen { reason: reason.to_string() }
 }
 
-// Helper function to display moves with promotion
-pub fn move_to_algebraic(mv: Move) -> String {
-    let from_str = square_to_algebraic(mv.from);
-    let to_str = square_to_algebraic(mv.to);
-    
-    if let Some(promotion) = mv.promotion {
-        let promotion_char = match promotion {
-            QUEEN => 'q',
-            ROOK => 'r', 
-            BISHOP => 'b',
-            KNIGHT => 'n',
-            _ => '?',
-        };
-        format!("{}{}{}", from_str, to_str, promotion_char)
-    } else {
-        format!("{}{}", from_str, to_str)
+// Helper function to get the SAN letter for a piece type
+fn piece_to_san_char(piece_type_val: u8) -> char {
+    match piece_type_val {
+        KNIGHT => 'N',
+        BISHOP => 'B',
+        ROOK => 'R',
+        QUEEN => 'Q',
+        KING => 'K',
+        _ => '?',
     }
 }
 
@@ -375,7 +970,1382 @@ mod tests {
         println!("✅ Knight check detection test PASSED!");
     }
 
-    pub fn set_logger(&mut self, logger: std::rc::Rc<std::cell::RefCell<ChessLogger>>) {
-        self.logger = Some(logger);
+    #[test]
+    fn test_perft_signature() {
+        crate::bitboard::initialize_engine();
+
+        let board = Board::new();
+        let fen_board = Board::from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1").unwrap();
+
+        // Depth 0 is just the position itself
+        assert_eq!(board.perft_signature(0), board.position_key());
+
+        // Same position reached two ways must give the same tree signature
+        assert_eq!(board.perft_signature(3), fen_board.perft_signature(3));
+        assert_ne!(board.perft_signature(2), board.perft_signature(3));
+    }
+
+    #[test]
+    fn test_open_and_half_open_files() {
+        crate::bitboard::initialize_engine();
+
+        let mut board = Board::new();
+        for file in 0..8 {
+            assert!(!board.is_open_file(file));
+            assert!(!board.is_half_open_file(file, WHITE));
+            assert!(!board.is_half_open_file(file, BLACK));
+        }
+
+        // 1. e4 d5 2. exd5
+        for (from, to) in [("e2", "e4"), ("d7", "d5"), ("e4", "d5")] {
+            board.try_make_move(Move::new(Square::from_algebraic(from).unwrap(), Square::from_algebraic(to).unwrap())).unwrap();
+        }
+
+        assert!(board.is_half_open_file(4, WHITE), "e-file should be half-open for White");
+        assert!(board.is_half_open_file(3, BLACK), "d-file should be half-open for Black");
+        assert!(!board.is_half_open_file(4, BLACK));
+        assert!(!board.is_half_open_file(3, WHITE));
+        assert!(!board.is_open_file(3));
+        assert!(!board.is_open_file(4));
+    }
+
+    #[test]
+    fn test_castling_rights_lost_when_rook_captured() {
+        crate::bitboard::initialize_engine();
+
+        let mut board = Board::from_fen("4k3/8/8/8/8/8/7r/4K2R b K - 0 1").unwrap();
+        assert!(has_castling_right(board.castling_rights, WHITE_KINGSIDE));
+
+        // Black rook takes the White rook on h1
+        board.try_make_move(Move::new(Square::from_algebraic("h2").unwrap(), Square::from_algebraic("h1").unwrap())).unwrap();
+
+        assert!(!has_castling_right(board.castling_rights, WHITE_KINGSIDE), "WHITE_KINGSIDE should be revoked");
+        assert!(!board.can_castle(WHITE, true));
+    }
+
+    #[test]
+    fn test_consistency_with_bitboards() {
+        crate::bitboard::initialize_engine();
+
+        let board = Board::new();
+        assert!(board.check_consistency_with_bitboards());
+
+        // Promotion with capture: pawn b7 takes rook a8 and becomes a queen
+        let mut board = Board::from_fen("r3k3/1P6/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+        assert!(board.check_consistency_with_bitboards());
+        board.try_make_move(Move::new_promotion(Square::from_algebraic("b7").unwrap(), Square::from_algebraic("a8").unwrap(), QUEEN)).unwrap();
+        assert!(board.check_consistency_with_bitboards());
+        assert_eq!(board.get_piece(Square::from_algebraic("a8").unwrap()), make_piece(QUEEN, WHITE));
+
+        board.undo_move().unwrap();
+        assert!(board.check_consistency_with_bitboards());
+    }
+
+    #[test]
+    fn test_stalemate_trap() {
+        crate::bitboard::initialize_engine();
+
+        // Black king boxed in on h8; Qf7 would stalemate it
+        let board = Board::from_fen("7k/8/6K1/8/8/8/8/5Q2 b - - 0 1").unwrap();
+        assert!(board.is_stalemate_trap(BLACK));
+
+        assert!(!Board::new().is_stalemate_trap(BLACK));
+    }
+
+    #[test]
+    fn test_terminal_status() {
+        crate::bitboard::initialize_engine();
+
+        assert!(!Board::new().is_terminal());
+        assert_eq!(Board::new().terminal_status(), None);
+
+        // Fool's mate: 1. f3 e5 2. g4 Qh4#
+        let mut board = Board::new();
+        for uci in ["f2f3", "e7e5", "g2g4", "d8h4"] {
+            board.try_make_move(Move::from_uci(uci).unwrap()).unwrap();
+        }
+        assert!(board.is_terminal());
+        assert_eq!(board.terminal_status(), Some(GameStatus::Checkmate(WHITE)));
+
+        let stalemate = Board::from_fen("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1").unwrap();
+        assert_eq!(stalemate.terminal_status(), Some(GameStatus::Stalemate));
+
+        let fifty_moves = Board::from_fen("4k3/8/8/8/8/8/R7/4K3 w - - 100 80").unwrap();
+        assert!(fifty_moves.is_terminal());
+        assert_eq!(fifty_moves.terminal_status(), Some(GameStatus::Draw(DrawReason::FiftyMove)));
+
+        let bare_kings = Board::from_fen("4k3/8/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+        assert_eq!(bare_kings.terminal_status(), Some(GameStatus::Draw(DrawReason::InsufficientMaterial)));
+
+        let mut board = Board::new();
+        for uci in ["g1f3", "g8f6", "f3g1", "f6g8", "g1f3", "g8f6", "f3g1"] {
+            board.try_make_move(Move::from_uci(uci).unwrap()).unwrap();
+            assert!(!board.is_terminal(), "not yet repeated after {}", uci);
+        }
+        board.try_make_move(Move::from_uci("f6g8").unwrap()).unwrap();
+        assert_eq!(board.terminal_status(), Some(GameStatus::Draw(DrawReason::Repetition)));
+    }
+
+    fn assert_fen_round_trip(board: &Board) {
+        let fen = board.to_fen();
+        let reloaded = Board::from_fen(&fen).unwrap();
+
+        assert_eq!(reloaded.squares, board.squares, "Pieces differ after round trip of {}", fen);
+        assert_eq!(reloaded.current_turn, board.current_turn);
+        assert_eq!(reloaded.castling_rights, board.castling_rights);
+        assert_eq!(reloaded.en_passant_target, board.en_passant_target);
+        assert_eq!(reloaded.half_move_clock, board.half_move_clock);
+        assert_eq!(reloaded.full_move_number, board.full_move_number);
+        assert_eq!(reloaded.to_fen(), fen);
+    }
+
+    #[test]
+    fn test_to_fen_round_trip() {
+        crate::bitboard::initialize_engine();
+
+        assert_eq!(Board::new().to_fen(), "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1");
+
+        for test_case in crate::perft::PERFT_POSITIONS {
+            let board = Board::from_fen(test_case.fen).unwrap();
+            assert_eq!(board.to_fen(), test_case.fen);
+
+            // Every position two plies deep must survive the round trip too
+            for first in board.get_all_legal_moves() {
+                let mut after_first = board.clone();
+                after_first.try_make_move(first).unwrap();
+                assert_fen_round_trip(&after_first);
+
+                for second in after_first.get_all_legal_moves() {
+                    let mut after_second = after_first.clone();
+                    after_second.try_make_move(second).unwrap();
+                    assert_fen_round_trip(&after_second);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_update_game_status() {
+        crate::bitboard::initialize_engine();
+
+        // Fool's mate: 1. f3 e5 2. g4 Qh4#
+        let mut board = Board::new();
+        for (from, to) in [("f2", "f3"), ("e7", "e5"), ("g2", "g4"), ("d8", "h4")] {
+            board.try_make_move(Move::new(Square::from_algebraic(from).unwrap(), Square::from_algebraic(to).unwrap())).unwrap();
+        }
+        assert_eq!(board.game_status, GameStatus::Checkmate(WHITE));
+
+        // Undo restores the previous status
+        board.undo_move().unwrap();
+        assert_eq!(board.game_status, GameStatus::InProgress);
+
+        // Check that isn't mate
+        let mut board = Board::from_fen("4k3/8/8/8/8/8/8/R3K3 w - - 0 1").unwrap();
+        board.try_make_move(Move::new(Square::from_algebraic("a1").unwrap(), Square::from_algebraic("a8").unwrap())).unwrap();
+        assert_eq!(board.game_status, GameStatus::Check(BLACK));
+
+        // Stalemate: Qf7 leaves the h8 king without moves
+        let mut board = Board::from_fen("7k/8/6K1/8/8/8/8/5Q2 w - - 0 1").unwrap();
+        board.try_make_move(Move::new(Square::from_algebraic("f1").unwrap(), Square::from_algebraic("f7").unwrap())).unwrap();
+        assert_eq!(board.game_status, GameStatus::Stalemate);
+    }
+
+    #[test]
+    fn test_fifty_move_rule_draw() {
+        crate::bitboard::initialize_engine();
+
+        let mut board = Board::from_fen("4k3/8/8/8/8/8/8/R3K3 w - - 99 80").unwrap();
+        assert_eq!(board.game_status, GameStatus::InProgress);
+
+        board.try_make_move(Move::new(Square::from_algebraic("a1").unwrap(), Square::from_algebraic("a2").unwrap())).unwrap();
+        assert_eq!(board.game_status, GameStatus::Draw(DrawReason::FiftyMove));
+        assert_eq!(board.game_result(), Some(GameResult::Draw(DrawReason::FiftyMove)));
+
+        // No further moves once the game is drawn
+        assert!(board.try_make_move(Move::new(Square::from_algebraic("e8").unwrap(), Square::from_algebraic("d8").unwrap())).is_err());
+    }
+
+    #[test]
+    fn test_fifty_move_warning() {
+        crate::bitboard::initialize_engine();
+        let board = |half_moves: u16| Board::from_fen(&format!("4k3/8/8/8/8/8/8/R3K3 w - - {} 80", half_moves)).unwrap();
+
+        assert!(!board(79).fifty_move_warning());
+        assert_eq!(board(79).moves_until_fifty_draw(), 10);
+        assert!(board(80).fifty_move_warning());
+        assert_eq!(board(80).moves_until_fifty_draw(), 10);
+        assert!(board(100).fifty_move_warning());
+        assert_eq!(board(100).moves_until_fifty_draw(), 0);
+    }
+
+    #[test]
+    fn test_insufficient_material() {
+        crate::bitboard::initialize_engine();
+
+        let insufficient = [
+            "4k3/8/8/8/8/8/8/4K3 w - - 0 1",     // K vs K
+            "4k3/8/8/8/8/8/8/2B1K3 w - - 0 1",   // K+B vs K
+            "4k3/8/8/8/8/8/8/1N2K3 w - - 0 1",   // K+N vs K
+            "2b1k3/8/8/8/8/8/8/5BK1 w - - 0 1",  // K+B vs K+B, both on light squares
+            "4k3/8/8/8/8/8/8/1N2KN2 w - - 0 1",  // K+N+N vs K
+        ];
+        for fen in insufficient {
+            let board = Board::from_fen(fen).unwrap();
+            assert!(board.is_insufficient_material(), "Should be insufficient: {}", fen);
+            assert_eq!(board.game_status, GameStatus::Draw(DrawReason::InsufficientMaterial));
+        }
+
+        let sufficient = [
+            "4k3/8/8/8/8/8/8/3QK3 w - - 0 1",    // K+Q vs K
+            "4k3/8/8/8/8/8/8/R3K3 w - - 0 1",    // K+R vs K
+            "4k3/8/8/8/8/8/4P3/4K3 w - - 0 1",   // K+P vs K
+            "1b2k3/8/8/8/8/8/8/5BK1 w - - 0 1",  // K+B vs K+B, opposite colors
+            "4k3/8/8/8/8/8/8/2B1KB2 w - - 0 1",  // Bishop pair
+        ];
+        for fen in sufficient {
+            let board = Board::from_fen(fen).unwrap();
+            assert!(!board.is_insufficient_material(), "Should be sufficient: {}", fen);
+        }
+    }
+
+    #[test]
+    fn test_check_queries() {
+        let mut board = Board::new();
+        assert!(!board.is_in_check());
+        for uci in ["f2f3", "e7e5", "g2g4"] {
+            board.try_make_move(Move::from_uci(uci).unwrap()).unwrap();
+        }
+        assert!(!board.is_in_checkmate());
+        board.try_make_move(Move::from_uci("d8h4").unwrap()).unwrap();
+        assert!(board.is_in_check());
+        assert!(board.is_in_checkmate());
+        assert_eq!(board.is_in_checkmate(), board.is_checkmate());
+        assert!(!board.is_in_stalemate());
+
+        let stalemate = Board::from_fen("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1").unwrap();
+        assert!(!stalemate.is_in_check());
+        assert!(stalemate.is_in_stalemate());
+        assert_eq!(stalemate.is_in_stalemate(), stalemate.is_stalemate());
+    }
+
+    #[test]
+    fn test_move_from_uci_str() {
+        let board = Board::new();
+        assert_eq!(Move::from_uci_str("e2e4", &board), Ok(Move::new(Square::new(4, 1), Square::new(4, 3))));
+        assert!(matches!(Move::from_uci_str("e2", &board), Err(MoveError::InvalidString(_))));
+        assert!(matches!(Move::from_uci_str("e7e8k", &board), Err(MoveError::InvalidString(_))));
+        assert!(matches!(Move::from_uci_str("i2i4", &board), Err(MoveError::OutOfBounds(_))));
+        assert!(matches!(Move::from_uci_str("e2e5", &board), Err(MoveError::IllegalMove(_))));
+
+        let promotion = Board::from_fen("8/4P3/8/8/8/8/k7/7K w - - 0 1").unwrap();
+        assert_eq!(Move::from_uci_str("e7e8n", &promotion), Ok(Move::new_promotion(Square::new(4, 6), Square::new(4, 7), KNIGHT)));
+    }
+
+    #[test]
+    fn test_make_move_san() {
+        let mut board = Board::new();
+        for san in ["e4", "d5", "exd5", "Nf6", "Nf3", "Nxd5", "Bc4", "e6", "O-O", "Be7"] {
+            assert!(board.make_move_san(san).is_ok(), "Failed to play {}", san);
+        }
+        assert!(matches!(board.make_move_san("Qh9"), Err(MoveError::OutOfBounds(_))));
+        assert!(matches!(board.make_move_san("Ke3"), Err(MoveError::IllegalMove(_))));
+        assert!(matches!(board.make_move_san("Nqc3"), Err(MoveError::InvalidString(_))));
+
+        // Two knights can reach d2
+        let mut knights = Board::from_fen("4k3/p7/8/8/8/8/8/1N2KN2 w - - 0 1").unwrap();
+        assert!(matches!(knights.make_move_san("Nd2"), Err(MoveError::IllegalMove(_))));
+        let bd2 = Move::new(Square::new(1, 0), Square::new(3, 1));
+        assert_eq!(knights.move_to_san(bd2), "Nbd2");
+        assert_eq!(knights.make_move_san("Nbd2").unwrap().mv, bd2);
+
+        let mut promotion = Board::from_fen("8/4P3/8/8/8/8/k7/7K w - - 0 1").unwrap();
+        let game_move = promotion.make_move_san("e8=Q").unwrap();
+        assert_eq!(game_move.mv.promotion, Some(QUEEN));
+
+        let mut mate = Board::from_fen("6k1/5ppp/8/8/8/8/5PPP/R5K1 w - - 0 1").unwrap();
+        let back_rank = Move::from_uci("a1a8").unwrap();
+        assert_eq!(mate.move_to_san(back_rank), "Ra8#");
+        assert_eq!(mate.make_move_san("Ra8#").unwrap().mv, back_rank);
+    }
+
+    #[test]
+    fn test_perft_variants_match_perft() {
+        crate::bitboard::initialize_engine();
+
+        for test_case in crate::perft::PERFT_POSITIONS {
+            let mut board = Board::from_fen(test_case.fen).unwrap();
+            for &(depth, expected) in test_case.expected_results.iter().filter(|(depth, _)| *depth <= 3) {
+                assert_eq!(crate::perft::perft_bulk(&mut board, depth), expected, "{} depth {}", test_case.name, depth);
+                if depth <= 2 {
+                    assert_eq!(crate::perft::perft(&mut board, depth), expected, "{} depth {}", test_case.name, depth);
+                }
+                // A tiny table forces plenty of overwrites
+                let mut table = crate::perft::PerftTable::new(0);
+                assert_eq!(crate::perft::perft_with_table(&mut board, depth, &mut table), expected, "{} depth {}", test_case.name, depth);
+            }
+        }
+    }
+
+    #[test]
+    fn test_en_passant_rank_pin_perft() {
+        crate::bitboard::initialize_engine();
+
+        // bxc6 would take both pawns off the fifth rank and leave the king facing the rook
+        let mut board = Board::from_fen("4k3/8/8/KPp4r/8/8/8/8 w - c6 0 1").unwrap();
+        let en_passant = Move::from_uci("b5c6").unwrap();
+        assert!(!board.legal_moves_from(Square::new(1, 4)).contains(&en_passant));
+        assert!(!board.get_all_legal_moves().contains(&en_passant));
+
+        // Bulk counting trusts the generated moves, plain perft only counts moves that can be made
+        for depth in 1..=4 {
+            assert_eq!(crate::perft::perft_bulk(&mut board, depth), crate::perft::perft(&mut board, depth), "depth {}", depth);
+        }
+    }
+
+    #[test]
+    fn test_perft_with_progress() {
+        crate::bitboard::initialize_engine();
+
+        let mut board = Board::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1").unwrap();
+        let root_moves = board.get_all_legal_moves().len() as u64;
+        let reports = std::cell::RefCell::new(Vec::new());
+        let nodes = board.perft_with_progress(2, |completed, total| reports.borrow_mut().push((completed, total)));
+
+        // One report per root move, counting up to the total
+        let expected: Vec<(u64, u64)> = (1..=root_moves).map(|completed| (completed, root_moves)).collect();
+        assert_eq!(reports.into_inner(), expected);
+        assert_eq!(nodes, board.perft(2));
+    }
+
+    #[test]
+    fn test_perft_detailed_move_types() {
+        crate::bitboard::initialize_engine();
+
+        for test_case in crate::perft::PERFT_POSITIONS {
+            let mut board = Board::from_fen(test_case.fen).unwrap();
+            for details in test_case.expected_details.iter().filter(|details| details.depth <= 3) {
+                let result = crate::perft::perft_detailed(&mut board, details.depth);
+                assert!(details.matches(&result), "{} depth {}: expected {:?}, got {:?}",
+                        test_case.name, details.depth, details, result);
+            }
+        }
+
+        // Without bulk counting a wrong check count fails the test even when the nodes match
+        let details = crate::perft::PERFT_POSITIONS[0].expected_details[2];
+        let mut board = Board::new();
+        assert!(crate::perft::run_perft_test(&mut board, 3, 8_902, Some(&details), false));
+        let wrong_checks = crate::perft::PerftDetails { checks: 11, ..details };
+        assert!(!crate::perft::run_perft_test(&mut board, 3, 8_902, Some(&wrong_checks), false));
+        assert!(crate::perft::run_perft_test(&mut board, 3, 8_902, Some(&wrong_checks), true));
+    }
+
+    #[test]
+    fn test_expected_divide() {
+        crate::bitboard::initialize_engine();
+
+        // Every stored divide adds up to the published count at its depth
+        for test_case in crate::perft::PERFT_POSITIONS {
+            let total: u64 = test_case.expected_divide.iter().map(|&(_, nodes)| nodes).sum();
+            let expected = test_case.expected_results.iter()
+                .find(|&&(depth, _)| depth == crate::perft::DIVIDE_DEPTH).map(|&(_, nodes)| nodes);
+            assert_eq!(Some(total), expected, "{}", test_case.name);
+        }
+
+        // Position 3 is small enough to check move by move
+        let test_case = &crate::perft::PERFT_POSITIONS[2];
+        let mut board = Board::from_fen(test_case.fen).unwrap();
+        let divide: Vec<(String, u64)> = crate::perft::perft_divide(&mut board, crate::perft::DIVIDE_DEPTH)
+            .into_iter().map(|(mv, nodes)| (mv.to_string(), nodes)).collect();
+        let expected: Vec<(String, u64)> = test_case.expected_divide.iter()
+            .map(|&(uci, nodes)| (uci.to_string(), nodes)).collect();
+        assert_eq!(divide, expected);
+    }
+
+    #[test]
+    fn test_typed_errors() {
+        assert!(matches!(Board::from_fen("8/8/8 w - - 0 1"), Err(MoveError::InvalidFen { .. })));
+        assert!(matches!(Board::from_fen("4k3/8/8/8/8/8/8/4K3 x - - 0 1"), Err(MoveError::InvalidFen { .. })));
+
+        let mut board = Board::new();
+        assert_eq!(board.undo_move().unwrap_err(), MoveError::NoMoveToUndo);
+        assert_eq!(board.try_make_move(Move::from_uci("e2e5").unwrap()).unwrap_err(), MoveError::IllegalMove("e2e5".to_string()));
+
+        let mut mated = Board::from_fen("R5k1/5ppp/8/8/8/8/5PPP/6K1 b - - 0 1").unwrap();
+        assert_eq!(mated.try_make_move(Move::from_uci("g8h8").unwrap()).unwrap_err(), MoveError::GameAlreadyOver);
+    }
+
+    #[test]
+    fn test_goto_move() {
+        let mut board = Board::new();
+        let start_fen = board.to_fen();
+        for uci in ["e2e4", "e7e5", "g1f3", "b8c6"] {
+            board.try_make_move(Move::from_uci(uci).unwrap()).unwrap();
+        }
+        let after_two = {
+            let mut copy = Board::new();
+            copy.try_make_move(Move::from_uci("e2e4").unwrap()).unwrap();
+            copy.try_make_move(Move::from_uci("e7e5").unwrap()).unwrap();
+            copy.to_fen()
+        };
+
+        let undone = board.goto_move(2).unwrap();
+        assert_eq!(undone.iter().map(|game_move| game_move.mv).collect::<Vec<_>>(),
+                   vec![Move::from_uci("g1f3").unwrap(), Move::from_uci("b8c6").unwrap()]);
+        assert_eq!(board.to_fen(), after_two);
+
+        // Going forward isn't possible, staying put is a no-op
+        assert_eq!(board.goto_move(3).unwrap_err(), MoveError::NoMoveToUndo);
+        assert!(board.goto_move(2).unwrap().is_empty());
+
+        board.goto_move(0).unwrap();
+        assert_eq!(board.to_fen(), start_fen);
+        assert!(board.move_history.is_empty());
+
+        // Undoing an en passant capture brings back the pawn and the en passant square
+        let mut board = Board::from_fen("4k3/8/8/4Pp2/8/8/8/4K3 w - f6 0 2").unwrap();
+        let fen = board.to_fen();
+        board.try_make_move(Move::from_uci("e5f6").unwrap()).unwrap();
+        board.goto_move(0).unwrap();
+        assert_eq!(board.to_fen(), fen);
+    }
+
+    #[test]
+    fn test_to_pgn_round_trip() {
+        let mut board = Board::new();
+        for san in ["e4", "e5", "Bc4", "Nc6", "Qh5", "Nf6", "Qxf7#"] {
+            board.make_move_san(san).unwrap();
+        }
+
+        let metadata = PgnMetadata {
+            white: "White \"W\" Player".to_string(),
+            result: board.pgn_result().to_string(),
+            ..PgnMetadata::default()
+        };
+        let pgn = board.to_pgn(&metadata);
+        assert!(pgn.starts_with("[Event \"?\"]\n[Site \"?\"]\n[Date \"????.??.??\"]\n[Round \"?\"]\n"));
+        assert!(pgn.contains("[White \"White \\\"W\\\" Player\"]\n"));
+        assert!(pgn.contains("[Result \"1-0\"]\n"));
+        assert!(pgn.ends_with("\n1. e4 e5 2. Bc4 Nc6 3. Qh5 Nf6 4. Qxf7# 1-0\n"));
+
+        // Replaying the movetext reaches the same position
+        let mut replayed = Board::new();
+        let movetext = pgn.split("\n\n").nth(1).unwrap();
+        for token in movetext.split_whitespace() {
+            if !token.ends_with('.') && token != "1-0" {
+                replayed.make_move_san(token).unwrap();
+            }
+        }
+        assert_eq!(replayed.to_fen(), board.to_fen());
+
+        // Positions set up from FEN are recorded, long games wrap at 80 columns
+        let mut board = Board::from_fen("4k3/8/8/8/8/8/8/R3K3 b Q - 0 1").unwrap();
+        let moves = [
+            "Kd7", "Ra7+", "Ke8", "Ra1", "Kf7", "Ke2", "Kg6", "Ke3", "Kh5", "Kf4", "Kh4", "Kf3",
+            "Kh3", "Ra2", "Kh4", "Ra3", "Kh5", "Ra4", "Kh6", "Ra5", "Kh7", "Ra6",
+        ];
+        for san in moves {
+            board.make_move_san(san).unwrap();
+        }
+        let pgn = board.to_pgn(&PgnMetadata::default());
+        assert!(pgn.contains("[SetUp \"1\"]\n[FEN \"4k3/8/8/8/8/8/8/R3K3 b Q - 0 1\"]\n"));
+        assert!(pgn.contains("\n\n1... Kd7 2. Ra7+ Ke8 3. Ra1"));
+        assert!(pgn.lines().all(|line| line.len() <= 80));
+    }
+
+    #[test]
+    fn test_incremental_hash() {
+        // En passant, castling, capture-promotion and plain promotion all update the hash in place
+        let mut board = Board::from_fen("r3k2r/1P6/8/3pP3/8/8/8/R3K2R w KQkq d6 0 1").unwrap();
+        let start_hash = board.position_hash;
+        assert_eq!(start_hash, board.compute_hash());
+
+        for uci in ["e5d6", "e8g8", "b7a8q", "f8a8", "e1g1", "a8a1", "f1a1", "g8f7", "d6d7", "f7e7", "d7d8n"] {
+            board.try_make_move(Move::from_uci(uci).unwrap()).unwrap();
+            assert_eq!(board.position_hash, board.compute_hash(), "after {}", uci);
+        }
+
+        // Undo restores the earlier hashes
+        while board.undo_move().is_ok() {
+            assert_eq!(board.position_hash, board.compute_hash());
+        }
+        assert_eq!(board.position_hash, start_hash);
+
+        // Transpositions share a key
+        let mut board = Board::new();
+        for uci in ["g1f3", "g8f6", "f3g1", "f6g8"] {
+            board.try_make_move(Move::from_uci(uci).unwrap()).unwrap();
+        }
+        assert_eq!(board.position_hash, Board::new().position_hash);
+        assert_ne!(board.with_flipped_turn().position_hash, board.position_hash);
+    }
+
+    #[test]
+    fn test_attacked_squares() {
+        // Agrees with asking each square for its attackers
+        for fen in [
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+        ] {
+            let board = Board::from_fen(fen).unwrap();
+            for color in [WHITE, BLACK] {
+                let pieces = board.bitboards.get_all_pieces(color);
+                let expected = (0..64u8)
+                    .filter(|&index| board.attacked_by_bb(Square(index)) & pieces != 0)
+                    .fold(0u64, |mask, index| mask | (1u64 << index));
+                assert_eq!(board.attacked_squares(color), expected, "{} {}", fen, color);
+            }
+        }
+
+        // A bishop eyeing f1 stops kingside castling but not queenside
+        let board = Board::from_fen("4k3/8/8/8/8/8/6b1/R3K2R w KQ - 0 1").unwrap();
+        assert!(!board.can_castle(WHITE, true));
+        assert!(board.can_castle(WHITE, false));
+    }
+
+    #[test]
+    fn test_pinned_pieces() {
+        // Bishop pinned by the queen, knight by the rook on the file and bishop by the rook on the rank
+        let board = Board::from_fen("4r1k1/8/8/q7/8/2B5/4N3/1n2KB1r w - - 0 1").unwrap();
+        let expected = ["c3", "e2", "f1"].iter()
+            .fold(0u64, |mask, square| mask | (1u64 << Square::from_algebraic(square).unwrap().0));
+        assert_eq!(board.pinned_pieces(WHITE), expected);
+        assert_eq!(board.pinned_pieces(BLACK), 0);
+
+        // Agrees with the per-piece check
+        for fen in [
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+            "4r1k1/8/8/q7/8/2B5/4N3/1n2KB1r w - - 0 1",
+        ] {
+            let board = Board::from_fen(fen).unwrap();
+            let pinned = board.pinned_pieces(board.current_turn);
+            for index in crate::bitboard::iterate_bits(board.bitboards.get_all_pieces(board.current_turn)) {
+                assert_eq!(pinned & (1u64 << index) != 0, board.is_piece_pinned(Square(index)).is_some(), "{} {}", fen, index);
+            }
+        }
+    }
+
+    #[test]
+    fn test_is_legal_move_fast() {
+        // Every from/to/promotion combination agrees with the full generator
+        for fen in [
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+            "8/8/8/K2pP2r/8/8/8/7k w - d6 0 1",
+            "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+            "4r1k1/8/8/q7/8/2B5/4N3/1n2KB1r w - - 0 1",
+        ] {
+            let board = Board::from_fen(fen).unwrap();
+            let legal = board.get_all_legal_moves();
+            for from in 0..64u8 {
+                for to in 0..64u8 {
+                    for promotion in [None, Some(QUEEN), Some(KNIGHT)] {
+                        let mv = Move { from: Square(from), to: Square(to), promotion };
+                        assert_eq!(board.is_legal_move_fast(mv), legal.contains(&mv), "{} {:?}", fen, mv);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_noisy_moves() {
+        for fen in [
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+            "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 b kq - 0 1",
+            "8/8/8/K2pP2r/8/8/8/7k w - d6 0 1",
+            "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+        ] {
+            let board = Board::from_fen(fen).unwrap();
+            let mut expected: Vec<Move> = board.get_all_legal_moves().into_iter()
+                .filter(|&mv| !is_empty(board.get_piece(mv.to)) || mv.promotion.is_some() || board.is_en_passant_move(mv))
+                .collect();
+            let mut noisy = board.noisy_moves();
+            let key = |mv: &Move| (mv.from.0, mv.to.0, mv.promotion);
+            expected.sort_by_key(key);
+            noisy.sort_by_key(key);
+            assert_eq!(noisy, expected, "{}", fen);
+        }
+
+        // The starting position has nothing to capture
+        assert!(Board::new().noisy_moves().is_empty());
+    }
+
+    #[test]
+    fn test_theoretical_draw() {
+        crate::bitboard::initialize_engine();
+
+        let drawn = [
+            "4k3/8/8/8/8/8/8/1N2KN2 w - - 0 1",   // K+N+N vs K
+            "7k/8/8/8/8/8/7P/4KB2 w - - 0 1",     // Light bishop, h-pawn, king in the h8 corner
+            "8/6k1/8/8/8/8/7P/4KB2 b - - 0 1",    // Same with the king on g7
+        ];
+        for fen in drawn {
+            let board = Board::from_fen(fen).unwrap();
+            assert!(board.is_theoretical_draw(), "Should be drawn: {}", fen);
+            assert!(board.is_drawn());
+        }
+
+        let black_wrong_bishop = Board::from_fen("4kb2/7p/8/8/8/8/6K1/8 w - - 0 1").unwrap();
+        assert!(black_wrong_bishop.is_theoretical_draw());
+        assert_eq!(black_wrong_bishop.game_status, GameStatus::Draw(DrawReason::Theoretical));
+        assert_eq!(black_wrong_bishop.game_result(), Some(GameResult::Draw(DrawReason::Theoretical)));
+
+        let not_drawn = [
+            "4kb2/7p/p7/8/8/8/8/7K w - - 0 1",    // Black pawns on both rook files
+            "7k/8/8/8/8/8/7P/2B1K3 w - - 0 1",    // Dark bishop covers h8
+            "4k3/8/8/8/8/8/7P/4KB2 w - - 0 1",    // Defending king away from the corner
+            "7k/8/8/8/8/8/6P1/4KB2 w - - 0 1",    // g-pawn
+            "7k/8/8/8/8/8/7P/4KN2 w - - 0 1",     // Knight instead of bishop
+            "6bk/8/8/8/8/8/7P/4KB2 w - - 0 1",    // Defender has a bishop too
+        ];
+        for fen in not_drawn {
+            let board = Board::from_fen(fen).unwrap();
+            assert!(!board.is_theoretical_draw(), "Should not be drawn: {}", fen);
+        }
+
+        // is_drawn also covers the fifty-move rule and repetition
+        assert!(Board::from_fen("4k3/8/8/8/8/8/8/R3K3 w - - 100 80").unwrap().is_drawn());
+        let mut board = Board::new();
+        for uci in ["g1f3", "g8f6", "f3g1", "f6g8", "g1f3", "g8f6", "f3g1"] {
+            board.try_make_move(Move::from_uci(uci).unwrap()).unwrap();
+            assert!(!board.is_drawn());
+        }
+        board.try_make_move(Move::from_uci("f6g8").unwrap()).unwrap();
+        assert!(board.is_drawn());
+        assert_eq!(board.game_status, GameStatus::Draw(DrawReason::Repetition));
+    }
+
+    #[test]
+    fn test_count_attackers() {
+        crate::bitboard::initialize_engine();
+
+        // White hits d5 with both pawns, both knights and the d2 queen; the d1 rook behind the
+        // queen doesn't count. Black defends with the g8 bishop and the king
+        let board = Board::from_fen("6b1/8/1Nk5/3p4/2P1PN2/8/3Q4/3R2K1 w - - 0 1").unwrap();
+        let d5 = Square::from_algebraic("d5").unwrap();
+        assert_eq!(board.count_attackers(d5, WHITE), 5);
+        assert_eq!(board.count_attackers(d5, BLACK), 2);
+        let expected: u64 = ["c4", "e4", "b6", "f4", "d2"].iter()
+            .map(|name| 1u64 << Square::from_algebraic(name).unwrap().0)
+            .fold(0, |bits, bit| bits | bit);
+        assert_eq!(board.attackers_bitboard(d5, WHITE), expected);
+
+        // Agrees with is_under_threat on every square
+        for fen in ["rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1"] {
+            let board = Board::from_fen(fen).unwrap();
+            for index in 0..64 {
+                for color in [WHITE, BLACK] {
+                    let square = Square(index);
+                    assert_eq!(board.count_attackers(square, color) > 0, board.is_under_threat(square, color),
+                               "{} {:?} {}", fen, square, color);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_attacked_by_bb() {
+        crate::bitboard::initialize_engine();
+
+        // Both colors' attackers of d5 together, with the rook behind the queen still hidden
+        let board = Board::from_fen("6b1/8/1Nk5/3p4/2P1PN2/8/3Q4/3R2K1 w - - 0 1").unwrap();
+        let d5 = Square::from_algebraic("d5").unwrap();
+        assert_eq!(board.attacked_by_bb(d5), board.attackers_bitboard(d5, WHITE) | board.attackers_bitboard(d5, BLACK));
+        assert_eq!(board.attacked_by_bb(d5).count_ones(), 7);
+
+        // An empty corner nobody reaches
+        let board = Board::from_fen("4k3/8/8/8/8/8/8/4R1K1 b - - 0 1").unwrap();
+        assert_eq!(board.attacked_by_bb(Square::from_algebraic("a8").unwrap()), 0);
+
+        // The attack table marks who covers each square and names the checking rook
+        let table = board.print_attack_table();
+        let lines: Vec<&str> = table.lines().collect();
+        assert_eq!(lines[0], "8   .  .  .  B  W  B  .  .");
+        assert_eq!(lines[1], "7   .  .  .  B WB  B  .  .");
+        assert_eq!(lines[7], "1   W  W  W  W  .  W  W  W");
+        assert_eq!(lines[9], "Black king on e8 attacked by: e1");
+        assert!(Board::new().print_attack_table().ends_with("White king on e1 attacked by: none\n"));
+    }
+
+    #[test]
+    fn test_material_balance() {
+        let board = Board::new();
+        assert_eq!(board.material_balance(), 0);
+        assert_eq!(board.count_pieces(WHITE, PAWN), 8);
+        assert_eq!(board.count_pieces(BLACK, KNIGHT), 2);
+        assert_eq!(board.count_pieces(BLACK, KING), 1);
+
+        // White has a rook and an extra pawn against the bishop
+        let board = Board::from_fen("4k3/pp3b2/8/8/8/8/PPP5/R3K3 w - - 0 1").unwrap();
+        assert_eq!(board.material_balance(), 500 + 100 - 330);
+        assert_eq!(board.with_flipped_turn().material_balance(), board.material_balance());
+        assert_eq!(board.count_pieces(WHITE, QUEEN), 0);
+    }
+
+    #[test]
+    fn test_mirror_vertical() {
+        crate::bitboard::initialize_engine();
+
+        let start = Board::new();
+        assert_eq!(start.mirror_vertical().to_fen(), "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR b KQkq - 0 1");
+
+        // Castling rights, the en passant square and the side to move all swap over
+        let board = Board::from_fen("r3k2r/8/8/8/3pP3/8/8/R3K3 b Qk e3 0 1").unwrap();
+        let mirrored = board.mirror_vertical();
+        assert_eq!(mirrored.to_fen(), "r3k3/8/8/3Pp3/8/8/8/R3K2R w Kq e6 0 1");
+        assert_eq!(mirrored.position_hash, Board::from_fen(&mirrored.to_fen()).unwrap().position_hash);
+        assert_eq!(mirrored.mirror_vertical().to_fen(), board.to_fen());
+
+        // Chess960 rook files follow their castling rights to the other side
+        let chess960 = Board::from_fen("1r2k1r1/8/8/8/8/8/8/R3KR2 w FAgb - 0 1").unwrap();
+        let mirrored_960 = chess960.mirror_vertical();
+        assert_eq!(mirrored_960.to_fen(), "r3kr2/8/8/8/8/8/8/1R2K1R1 b GBfa - 0 1");
+        assert_eq!(mirrored_960.mirror_vertical().to_fen(), chess960.to_fen());
+
+        // Every legal move has a mirrored counterpart
+        let mirror_move = |mv: Move| Move { from: Square(mv.from.0 ^ 56), to: Square(mv.to.0 ^ 56), promotion: mv.promotion };
+        let key = |mv: &Move| (mv.from.0, mv.to.0, mv.promotion);
+        for (original, mirrored) in [(board, mirrored), (chess960, mirrored_960)] {
+            let mut expected: Vec<Move> = original.get_all_legal_moves().into_iter().map(mirror_move).collect();
+            let mut actual = mirrored.get_all_legal_moves();
+            expected.sort_by_key(key);
+            actual.sort_by_key(key);
+            assert_eq!(actual, expected, "{}", original.to_fen());
+        }
+    }
+
+    #[test]
+    fn test_display() {
+        let board = Board::new();
+        let expected = "\
+8 r n b q k b n r
+7 p p p p p p p p
+6 . . . . . . . .
+5 . . . . . . . .
+4 . . . . . . . .
+3 . . . . . . . .
+2 P P P P P P P P
+1 R N B Q K B N R
+  a b c d e f g h
+";
+        assert_eq!(board.display(), expected);
+        assert_eq!(board.to_string(), expected);
+
+        // The knight's targets, one of them a capture
+        let board = Board::from_fen("4k3/8/8/3p4/8/4N3/8/4K3 w - - 0 1").unwrap();
+        let targets = board.get_legal_moves(Square::from_algebraic("e3").unwrap());
+        let lines: Vec<String> = board.display_with_highlights(&targets).lines().map(String::from).collect();
+        assert_eq!(lines[3], "5 . . . * . * . .");
+        assert_eq!(lines[5], "3 . . . . N . . .");
+        assert_eq!(lines[6], "2 . . * . . . * .");
+    }
+
+    #[test]
+    fn test_square_algebraic() {
+        assert_eq!(Square::from_algebraic("e4"), Ok(Square::new(4, 3)));
+        assert_eq!(Square::from_algebraic("a1"), Ok(Square(0)));
+        assert_eq!(Square::from_algebraic("h8"), Ok(Square(63)));
+        assert_eq!(Square::from_algebraic("e"), Err(MoveError::InvalidString("e".to_string())));
+        assert_eq!(Square::from_algebraic("e44"), Err(MoveError::InvalidString("e44".to_string())));
+        assert_eq!(Square::from_algebraic("i1"), Err(MoveError::OutOfBounds("i1".to_string())));
+        assert_eq!(Square::from_algebraic("a9"), Err(MoveError::OutOfBounds("a9".to_string())));
+
+        for index in 0..64 {
+            let square = Square(index);
+            assert_eq!(Square::from_algebraic(&square.to_string()), Ok(square));
+        }
+        assert_eq!(format!("{} {:?}", Square(12), Square(12)), "e2 e2");
+    }
+
+    #[test]
+    fn test_move_display() {
+        assert_eq!(Move::from_uci("e2e4").unwrap().to_string(), "e2e4");
+        assert_eq!(Move::from_uci("a7a8q").unwrap().to_string(), "a7a8q");
+        assert_eq!(Move::new_promotion(Square(52), Square(60), KNIGHT).to_string(), "e7e8n");
+        for uci in ["b2b1r", "g7g8b", "e1g1"] {
+            assert_eq!(Move::from_uci(uci).unwrap().to_string(), uci);
+        }
+
+        // Captures include en passant
+        let board = Board::from_fen("4k3/8/8/3pP3/8/8/1p6/R3K3 w - d6 0 1").unwrap();
+        assert!(Move::from_uci("e5d6").unwrap().is_capture(&board));
+        assert!(!Move::from_uci("e5e6").unwrap().is_capture(&board));
+        assert!(!Move::from_uci("a1a2").unwrap().is_capture(&board));
+        assert!(!Move::from_uci("a1a8").unwrap().is_promotion());
+        let moves: std::collections::HashSet<Move> = board.get_all_legal_moves().into_iter().collect();
+        assert!(moves.contains(&Move::from_uci("e5d6").unwrap()));
+    }
+
+    #[test]
+    fn test_fen_round_trip_state() {
+        crate::bitboard::initialize_engine();
+
+        // Re-parsing to_fen must give back the same board state, not just the same string
+        let assert_round_trip = |board: &Board| {
+            let fen = board.to_fen();
+            let reparsed = Board::from_fen(&fen).unwrap_or_else(|e| panic!("{}: {}", fen, e));
+            assert_eq!(reparsed.squares, board.squares, "{}", fen);
+            assert_eq!(reparsed.castling_rights, board.castling_rights, "{}", fen);
+            assert_eq!(reparsed.en_passant_target, board.en_passant_target, "{}", fen);
+            assert_eq!(reparsed.current_turn, board.current_turn, "{}", fen);
+            assert_eq!((reparsed.half_move_clock, reparsed.full_move_number), (board.half_move_clock, board.full_move_number), "{}", fen);
+            assert_eq!(reparsed.position_hash, board.position_hash, "{}", fen);
+        };
+
+        // Random games from each perft position, reproducible through fixed seeds
+        for test_case in crate::perft::PERFT_POSITIONS {
+            for seed in 1..=10 {
+                let start = Board::from_fen(test_case.fen).unwrap();
+                for board in crate::perft::random_game_positions(&start, seed, 40) {
+                    assert_round_trip(&board);
+                }
+            }
+        }
+
+        for fen in [
+            "4k3/8/8/8/8/8/8/4K2R w - - 0 1",                                     // No castling rights
+            "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",     // En passant
+            "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1",       // Black to move after a double push
+            "r3k2r/8/8/8/8/8/8/R3K2R b Kq - 17 42",                              // Partial rights and clocks
+        ] {
+            let board = Board::from_fen(fen).unwrap();
+            assert_eq!(board.to_fen(), fen);
+            assert_round_trip(&board);
+        }
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        crate::bitboard::initialize_engine();
+
+        // Castling, en passant and a promotion in the history, so every GameMove field is exercised
+        let mut board = Board::from_fen("r3k2r/P7/8/8/3p4/8/4P3/R3K2R w KQkq - 0 1").unwrap();
+        for mv in ["e2e4", "d4e3", "e1g1", "e8c8", "a7a8q"] {
+            let mv = Move::from_uci_str(mv, &board).unwrap();
+            board.try_make_move(mv).unwrap();
+        }
+
+        let mut restored = Board::from_json(&board.to_json()).unwrap();
+        assert_eq!(restored.to_fen(), board.to_fen());
+        assert_eq!(restored.position_hash, board.position_hash);
+        assert_eq!(restored.move_history.len(), 5);
+        assert_eq!(restored.position_history, board.position_history);
+
+        // Undoing the restored game walks back through the same positions
+        while !board.move_history.is_empty() {
+            board.undo_move().unwrap();
+            restored.undo_move().unwrap();
+            assert_eq!(restored.to_fen(), board.to_fen());
+            assert_eq!(restored.position_hash, board.position_hash);
+        }
+
+        assert!(Board::from_json("{}").is_err());
+        let mut json: serde_json::Value = serde_json::from_str(&board.to_json()).unwrap();
+        json["squares"] = serde_json::json!([0, 0, 0]);
+        assert!(Board::from_json(&json.to_string()).is_err());
+
+        // Stale bitboards and hash are replaced, but an impossible position is still refused
+        let mut json: serde_json::Value = serde_json::from_str(&board.to_json()).unwrap();
+        json["position_hash"] = serde_json::json!(0);
+        json["bitboards"] = serde_json::to_value(BitboardManager::new()).unwrap();
+        let restored = Board::from_json(&json.to_string()).unwrap();
+        assert_eq!(restored.position_hash, board.position_hash);
+        assert!(restored.validate().is_ok());
+        json["squares"][4] = serde_json::json!(0);
+        assert!(Board::from_json(&json.to_string()).is_err());
+    }
+
+    #[test]
+    fn test_from_pgn() {
+        crate::bitboard::initialize_engine();
+
+        let (board, metadata) = Board::from_pgn_with_metadata(
+            include_str!("../../testdata/pgn/deep_blue_kasparov_1997_game6.pgn")).unwrap();
+        assert_eq!(metadata.white, "Deep Blue");
+        assert_eq!(metadata.black, "Kasparov, Garry");
+        assert_eq!(metadata.date, "1997.05.11");
+        assert_eq!(metadata.result, "1-0");
+        assert_eq!(board.move_history.len(), 37);
+        assert_eq!(board.to_fen(), "r1k4r/p2nb1p1/2b4p/1p1n1p2/2PP4/3Q1NB1/1P3PPP/R5K1 b - c3 0 19");
+
+        let board = Board::from_pgn(include_str!("../../testdata/pgn/byrne_fischer_1956.pgn")).unwrap();
+        assert_eq!(board.move_history.len(), 82);
+        assert_eq!(board.game_result(), Some(GameResult::BlackWins));
+
+        // Comments, NAGs, annotations and nested variations don't disturb the main line
+        let (board, metadata) = Board::from_pgn_with_metadata(
+            include_str!("../../testdata/pgn/morphy_opera_game_1858.pgn")).unwrap();
+        assert_eq!(metadata.white, "Morphy, Paul");
+        assert_eq!(board.move_history.len(), 33);
+        assert_eq!(board.game_result(), Some(GameResult::WhiteWins));
+
+        // Games set up from a FEN tag, as written by to_pgn
+        let mut board = Board::from_fen("4k3/8/8/8/8/8/4P3/4K3 w - - 0 1").unwrap();
+        for san in ["e4", "Kd7", "e5"] {
+            board.make_move_san(san).unwrap();
+        }
+        let reloaded = Board::from_pgn(&board.to_pgn(&PgnMetadata::new("A", "B"))).unwrap();
+        assert_eq!(reloaded.to_fen(), board.to_fen());
+
+        assert!(matches!(
+            Board::from_pgn("1. e4 e5\n2. Ke3 Nc6"),
+            Err(PgnError::IllegalMove { line: 2, column: 4, ref san, .. }) if san == "Ke3"
+        ));
+        assert_eq!(Board::from_pgn("1. e4 { unfinished").unwrap_err(), PgnError::UnterminatedComment { line: 1, column: 7 });
+        assert_eq!(Board::from_pgn("1. e4 (1. d4 e5").unwrap_err(), PgnError::UnbalancedVariation { line: 1, column: 7 });
+        assert_eq!(Board::from_pgn("1. e4 e5 )").unwrap_err(), PgnError::UnbalancedVariation { line: 1, column: 10 });
+        assert_eq!(Board::from_pgn("[White Deep Blue]\n1. e4").unwrap_err(), PgnError::InvalidTag { line: 1, column: 1 });
+        assert_eq!(Board::from_pgn("1. e4 [White \"A\"]").unwrap_err(), PgnError::InvalidTag { line: 1, column: 7 });
+        assert!(matches!(Board::from_pgn("[FEN \"8/8/8 w - - 0 1\"]\n"), Err(PgnError::InvalidFen { line: 1, column: 1, .. })));
+    }
+
+    #[test]
+    fn test_null_move() {
+        crate::bitboard::initialize_engine();
+
+        let mut board = Board::new();
+        board.make_move_san("e4").unwrap();
+        let fen = board.to_fen();
+        let hash = board.position_hash;
+
+        board.apply_null_move();
+        assert_eq!(board.current_turn, WHITE);
+        assert_eq!((board.en_passant_target, board.en_passant_pawn), (None, None));
+        assert_eq!(board.half_move_clock, 1);
+        assert!(board.move_history.last().unwrap().is_null_move);
+        assert_eq!(board.position_hash, board.compute_hash());
+
+        // Play on after the pass and come back through it
+        board.make_move_san("d4").unwrap();
+        board.undo_move().unwrap();
+        assert!(board.undo_null_move().unwrap().is_null_move);
+        assert_eq!(board.to_fen(), fen);
+        assert_eq!(board.position_hash, hash);
+
+        // undo_move takes back a pass too, and undo_null_move refuses real moves
+        board.apply_null_move();
+        board.undo_move().unwrap();
+        assert_eq!(board.to_fen(), fen);
+        assert_eq!(board.undo_null_move().unwrap_err(), MoveError::NoMoveToUndo);
+        assert_eq!(board.move_history.len(), 1);
+    }
+
+    #[test]
+    fn test_pruning_eligibility() {
+        crate::bitboard::initialize_engine();
+
+        let start = Board::new();
+        assert_eq!(start.non_pawn_material(WHITE), 2 * 320 + 2 * 330 + 2 * 500 + 900);
+        assert!(start.null_move_is_safe());
+
+        // No null move in check, or for a side left with only pawns
+        let in_check = Board::from_fen("4k3/8/8/8/8/8/4q3/R3K3 w - - 0 1").unwrap();
+        assert!(!in_check.null_move_is_safe());
+        let knight_vs_pawns = Board::from_fen("4k3/pppp4/8/8/8/8/PPPP4/3NK3 w - - 0 1").unwrap();
+        assert!(knight_vs_pawns.null_move_is_safe());
+        assert_eq!(knight_vs_pawns.non_pawn_material(BLACK), 0);
+        assert!(!knight_vs_pawns.with_flipped_turn().null_move_is_safe());
+
+        // LMR: quiet moves from the fourth onwards, at depth 3 and deeper
+        let push = Move::from_uci("e4e5").unwrap();
+        let board = Board::from_fen("4k3/8/8/3p4/4P3/8/8/4K3 w - - 0 1").unwrap();
+        assert!(board.can_apply_lmr(push, 3, 3));
+        assert!(!board.can_apply_lmr(push, 2, 3));
+        assert!(!board.can_apply_lmr(push, 3, 2));
+        assert!(!board.can_apply_lmr(Move::from_uci("e4d5").unwrap(), 3, 3));
+        assert!(!in_check.can_apply_lmr(Move::from_uci("a1a8").unwrap(), 3, 3));
+    }
+
+    #[test]
+    fn test_static_eval() {
+        crate::bitboard::initialize_engine();
+
+        assert_eq!(Board::new().static_eval(), 0);
+        assert_eq!(Board::new().game_phase(), 1.0);
+
+        // A missing knight is worth about a knight whichever side is to move
+        let white_to_move = Board::from_fen("r1bqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1").unwrap();
+        let black_to_move = Board::from_fen("r1bqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR b KQkq - 0 1").unwrap();
+        assert_eq!(white_to_move.static_eval(), -black_to_move.static_eval());
+        assert!((250..400).contains(&white_to_move.static_eval()));
+
+        // Color-swapped positions score the same for the side to move
+        let board = Board::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1").unwrap();
+        assert_eq!(board.static_eval(), board.mirror_vertical().static_eval());
+
+        // A centralized king is worth more once the pieces are off
+        let central = Board::from_fen("8/8/8/8/4K3/8/8/k7 w - - 0 1").unwrap();
+        assert_eq!(central.game_phase(), 0.0);
+        assert!(central.static_eval() > 0);
+    }
+
+    #[test]
+    fn test_undo_special_moves() {
+        crate::bitboard::initialize_engine();
+
+        // Every special move for both colors, undone straight away
+        let cases = [
+            ("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1", ["e1g1", "e1c1"]),
+            ("r3k2r/8/8/8/8/8/8/R3K2R b KQkq - 0 1", ["e8g8", "e8c8"]),
+            ("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1", ["e5d6", "e5e6"]),
+            ("4k3/8/8/8/3Pp3/8/8/4K3 b - d3 0 1", ["e4d3", "e4e3"]),
+            ("1r2k3/P7/8/8/8/8/8/4K3 w - - 0 1", ["a7b8n", "a7a8q"]),
+            ("4k3/8/8/8/8/8/p7/1R2K3 b - - 0 1", ["a2b1q", "a2a1r"]),
+        ];
+        for (fen, moves) in cases {
+            for uci in moves {
+                let mut board = Board::from_fen(fen).unwrap();
+                let before = board.clone();
+                let mv = Move::from_uci_str(uci, &board).unwrap();
+                board.try_make_move(mv).unwrap();
+                board.undo_move().unwrap();
+
+                assert_eq!(board.squares, before.squares, "{} {}", fen, uci);
+                assert_eq!(board.bitboards.all_pieces, before.bitboards.all_pieces, "{} {}", fen, uci);
+                assert_eq!(board.bitboards.white_pieces, before.bitboards.white_pieces, "{} {}", fen, uci);
+                assert_eq!(board.to_fen(), fen);
+                assert_eq!(board.position_hash, before.position_hash);
+            }
+        }
+    }
+
+    #[test]
+    fn test_en_passant_undo_snapshot() {
+        crate::bitboard::initialize_engine();
+
+        // The snapshot is taken before the en passant square is cleared, so the capture
+        // records the taken pawn and undo brings back the square it was taken on
+        let mut board = Board::new();
+        for uci in ["e2e4", "a7a6", "e4e5", "d7d5", "e5d6"] {
+            let mv = Move::from_uci_str(uci, &board).unwrap();
+            board.try_make_move(mv).unwrap();
+        }
+        let capture = board.get_last_move().unwrap();
+        assert!(capture.is_en_passant);
+        assert_eq!(capture.captured_piece, make_piece(PAWN, BLACK));
+        assert_eq!(capture.previous_en_passant_target, Some(Square::new(3, 5)));
+        assert_eq!(capture.previous_en_passant_pawn, Some(Square::new(3, 4)));
+
+        board.undo_move().unwrap();
+        assert_eq!(board.to_fen(), "rnbqkbnr/1pp1pppp/p7/3pP3/8/8/PPPP1PPP/RNBQKBNR w KQkq d6 0 3");
+
+        // Jumping back past the double push leaves no en passant square behind
+        board.goto_move(3).unwrap();
+        assert_eq!(board.en_passant_target, None);
+        assert_eq!(board.position_hash, board.compute_hash());
+    }
+
+    #[test]
+    fn test_perft_divide() {
+        crate::bitboard::initialize_engine();
+
+        let mut board = Board::new();
+        assert_eq!(board.perft(3), 8902);
+
+        let divide = board.perft_divide(2);
+        assert_eq!(divide.len(), 20);
+        assert_eq!(divide.iter().map(|(_, nodes)| nodes).sum::<u64>(), 400);
+        let names: Vec<String> = divide.iter().map(|(mv, _)| mv.to_string()).collect();
+        assert_eq!(names.first().map(String::as_str), Some("a2a3"));
+        assert!(names.windows(2).all(|pair| pair[0] < pair[1]));
+
+        // Each promotion piece is its own entry
+        let board = Board::from_fen("4k3/P7/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+        let names: Vec<String> = board.perft_divide(1).iter().map(|(mv, _)| mv.to_string()).collect();
+        assert_eq!(&names[..4], ["a7a8b", "a7a8n", "a7a8q", "a7a8r"]);
+        assert_eq!(board.debug_perft_divide_formatted(1)[0], "a7a8b: 1");
+    }
+
+    #[test]
+    fn test_check_evasions() {
+        crate::bitboard::initialize_engine();
+
+        // Every checked node of these trees
+        fn collect_checks(board: &mut Board, depth: u32, checked: &mut Vec<Board>) {
+            if board.is_in_check() {
+                checked.push(board.clone());
+            }
+            if depth == 0 {
+                return;
+            }
+            for mv in board.get_all_legal_moves() {
+                board.try_make_move(mv).unwrap();
+                collect_checks(board, depth - 1, checked);
+                board.undo_move().unwrap();
+            }
+        }
+
+        let mut checked = Vec::new();
+        for test_case in crate::perft::PERFT_POSITIONS.iter() {
+            let mut board = Board::from_fen(test_case.fen).unwrap();
+            collect_checks(&mut board, 2, &mut checked);
+        }
+        assert!(!checked.is_empty());
+
+        // Same moves as the generic generator
+        for board in &checked {
+            let mut moves = board.get_all_legal_moves();
+            let mut evasions = board.generate_check_evasions();
+            moves.sort_by_key(|mv| mv.to_string());
+            evasions.sort_by_key(|mv| mv.to_string());
+            assert_eq!(evasions, moves, "evasions differ in {}", board.to_fen());
+        }
+
+        // Timings are only reported, since debug builds and busy machines make them too noisy to assert
+        let time = |generate: &dyn Fn(&Board) -> usize| {
+            let start = std::time::Instant::now();
+            let moves: usize = (0..20).map(|_| checked.iter().map(generate).sum::<usize>()).sum();
+            (moves, start.elapsed())
+        };
+        let (generic_moves, generic_time) = time(&|board| board.get_all_legal_moves().len());
+        let (evasion_moves, evasion_time) = time(&|board| board.generate_check_evasions().len());
+        assert_eq!(generic_moves, evasion_moves);
+        println!("{} checked positions x 20: generic {:?}, evasions {:?}", checked.len(), generic_time, evasion_time);
+
+        // Double check leaves only king moves
+        let board = Board::from_fen("4k3/8/8/8/8/5n2/8/r3K2R w - - 0 1").unwrap();
+        let evasions = board.generate_check_evasions();
+        assert!(!evasions.is_empty());
+        assert!(evasions.iter().all(|mv| mv.from == Square::new(4, 0)));
+
+        // En passant takes the pawn that just gave check
+        let board = Board::from_fen("8/8/8/2k5/3Pp3/8/8/4K3 b - d3 0 1").unwrap();
+        assert!(board.generate_check_evasions().iter().any(|mv| mv.to_string() == "e4d3"));
+    }
+
+    #[test]
+    fn test_see() {
+        crate::bitboard::initialize_engine();
+        let see = |fen: &str, uci: &str| {
+            let board = Board::from_fen(fen).unwrap();
+            board.see(Move::from_uci_str(uci, &board).unwrap())
+        };
+
+        // Undefended pieces are won outright
+        assert_eq!(see("4k3/8/8/3n4/8/8/8/3RK3 w - - 0 1", "d1d5"), 320);
+        assert_eq!(see("4k3/8/8/8/3Pp3/8/8/4K3 b - d3 0 1", "e4d3"), 100);
+
+        // Like for like, then recaptured: even
+        assert_eq!(see("4k3/8/2p5/3p4/4P3/8/8/4K3 w - - 0 1", "e4d5"), 0);
+        assert_eq!(see("3rk3/8/8/3r4/8/8/8/3RK3 w - - 0 1", "d1d5"), 0);
+
+        // A queen taking a defended pawn loses the difference
+        assert_eq!(see("4k3/8/4r3/8/8/4p3/8/4Q2K w - - 0 1", "e1e3"), -800);
+    }
+
+    #[test]
+    fn test_chess960() {
+        crate::bitboard::initialize_engine();
+
+        // Reference counts from the Chess960 perft suite
+        let positions = [
+            ("bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w HFhf - 2 9", [21, 528, 12_189]),
+            ("2nnrbkr/p1qppppp/8/1ppb4/6PP/3PP3/PPP2P2/BQNNRBKR w HEhe - 1 9", [21, 807, 18_002]),
+            ("b1q1rrkb/pppppppp/3nn3/8/P7/1PPP4/4PPPP/BQNNRKRB w GE - 1 9", [20, 479, 10_471]),
+        ];
+        for (fen, expected) in positions {
+            let mut board = Board::from_fen(fen).unwrap();
+            assert!(board.chess960);
+            assert_eq!(board.to_fen(), fen);
+            for (depth, &nodes) in (1..).zip(expected.iter()) {
+                assert_eq!(board.perft(depth), nodes, "{} depth {}", fen, depth);
+            }
+        }
+
+        // King on g1 with both rooks beside it: the kingside rook has nowhere to go
+        let board = Board::from_fen("4k3/8/8/8/8/8/8/5RKR w HF - 0 1").unwrap();
+        let castles: Vec<String> = board.get_all_legal_moves().into_iter()
+            .filter(|&mv| board.is_castling_move(mv).is_some())
+            .map(|mv| mv.to_string())
+            .collect();
+        assert_eq!(castles, ["g1f1"]);
+
+        // Castling is written as the king taking its rook, and undoes cleanly
+        let mut board = Board::from_fen_960("4k3/8/8/8/8/8/8/R5KR w - - 0 1", (7, 0, 7, 0)).unwrap();
+        assert!(board.get_all_legal_moves().iter().all(|&mv| board.is_castling_move(mv).is_none()));
+        board.castling_rights = WHITE_KINGSIDE | WHITE_QUEENSIDE;
+        board.position_hash = board.compute_hash();
+        assert_eq!(board.to_fen(), "4k3/8/8/8/8/8/8/R5KR w HA - 0 1");
+
+        let start_fen = board.to_fen();
+        let start_hash = board.position_hash;
+        for (uci, san, king, rook) in [("g1h1", "O-O", "g1", "f1"), ("g1a1", "O-O-O", "c1", "d1")] {
+            let mv = Move::from_uci(uci).unwrap();
+            assert!(board.get_all_legal_moves().contains(&mv), "{}", uci);
+            assert_eq!(board.move_to_san(mv), san);
+
+            let game_move = board.try_make_move(mv).unwrap();
+            assert!(game_move.is_castling && is_empty(game_move.captured_piece));
+            assert_eq!(board.get_piece(Square::from_algebraic(king).unwrap()), make_piece(KING, WHITE));
+            assert_eq!(board.get_piece(Square::from_algebraic(rook).unwrap()), make_piece(ROOK, WHITE));
+            assert_eq!(board.castling_rights, 0);
+
+            board.undo_move().unwrap();
+            assert_eq!(board.to_fen(), start_fen);
+            assert_eq!(board.position_hash, start_hash);
+        }
+
+        // No rook on a claimed castling file
+        assert!(Board::from_fen_960("4k3/8/8/8/8/8/8/5RKR w HF - 0 1", (7, 4, 7, 0)).is_err());
+        // X-FEN KQkq on a Chess960 setup picks the outermost rooks
+        let board = Board::from_fen("1r2k1r1/8/8/8/8/8/8/1R2K1R1 w KQkq - 0 1").unwrap();
+        assert!(board.chess960);
+        assert_eq!(board.castling_rook_files, [6, 1, 6, 1]);
+        assert!(!Board::new().chess960);
+    }
+
+    #[test]
+    fn test_validate() {
+        assert_eq!(Board::new().validate(), Ok(()));
+        for fen in crate::perft::PERFT_POSITIONS.iter().map(|test_case| test_case.fen) {
+            assert_eq!(Board::from_fen(fen).unwrap().validate(), Ok(()), "{}", fen);
+        }
+
+        let mut board = Board::new();
+        board.squares[0] = EMPTY;
+        assert_eq!(board.validate(), Err(BoardError::BitboardMismatch));
+
+        let mut board = Board::from_fen("4k3/8/8/8/8/8/8/4K2R w K - 0 1").unwrap();
+        board.set_piece(Square::new(7, 0), EMPTY);
+        assert_eq!(board.validate(), Err(BoardError::CastlingRight { right: WHITE_KINGSIDE }));
+        board.set_piece(Square::new(4, 7), EMPTY);
+        assert_eq!(board.validate(), Err(BoardError::KingCount { color: BLACK, count: 0 }));
+
+        let mut board = Board::from_fen("4k3/8/8/8/8/8/8/4K3 b - - 0 1").unwrap();
+        board.en_passant_target = Some(Square::new(4, 2));
+        assert_eq!(board.validate(), Err(BoardError::EnPassant));
+        let mut board = Board::from_fen("4k3/8/8/8/8/8/8/4K3 b - - 0 1").unwrap();
+        board.set_piece(Square::new(0, 7), make_piece(PAWN, WHITE));
+        assert_eq!(board.validate(), Err(BoardError::PawnOnBackRank { square: Square::new(0, 7) }));
+
+        // from_fen refuses boards validate would reject
+        for fen in ["4k3/8/8/8/8/8/8/4K3 b - e3 0 1", "P3k3/8/8/8/8/8/8/4K3 b - - 0 1", "8/8/8/8/8/8/8/4K3 w - - 0 1"] {
+            assert!(matches!(Board::from_fen(fen), Err(MoveError::InvalidFen { .. })), "{}", fen);
+        }
+
+        // Rights with no rook behind them are dropped, so moving the other rook can't break the board
+        let mut board = Board::from_fen("4k3/8/8/8/8/8/8/R3K3 w K - 0 1").unwrap();
+        assert_eq!(board.castling_rights, 0);
+        board.try_make_move(Move::from_uci("a1a2").unwrap()).unwrap();
+        assert_eq!(board.validate(), Ok(()));
+        assert_eq!(Board::from_fen("4k3/8/8/8/8/8/8/4K2R w KQ - 0 1").unwrap().castling_rights, WHITE_KINGSIDE);
+    }
+
+    #[test]
+    fn test_move_to_san_disambiguated() {
+        // Knights on b8 and f6 can both reach d7: the file tells them apart
+        let mut board = Board::from_fen("rn2k3/8/5n2/8/8/8/8/4K3 b - - 0 1").unwrap();
+        let nbd7 = Move::from_uci("b8d7").unwrap();
+        assert_eq!(board.move_to_san_disambiguated(nbd7), "Nbd7");
+        assert_eq!(board.move_to_san_disambiguated(Move::from_uci("f6d7").unwrap()), "Nfd7");
+        board.try_make_move(nbd7).unwrap();
+        assert!(board.to_pgn(&PgnMetadata::default()).ends_with("\n1... Nbd7 *\n"));
+
+        // Same file: the rank, and both when a rival shares each
+        let board = Board::from_fen("4k3/8/8/1N6/8/8/8/1N2K3 w - - 0 1").unwrap();
+        assert_eq!(board.move_to_san_disambiguated(Move::from_uci("b1c3").unwrap()), "N1c3");
+        let board = Board::from_fen("4k3/8/8/8/8/Q7/8/Q1Q1K3 w - - 0 1").unwrap();
+        assert_eq!(board.move_to_san_disambiguated(Move::from_uci("a1b2").unwrap()), "Qa1b2");
+
+        // Castling and the check suffixes
+        let board = Board::from_fen("4k3/8/8/8/8/8/8/R3K2R w KQ - 0 1").unwrap();
+        assert_eq!(board.move_to_san_disambiguated(Move::from_uci("e1g1").unwrap()), "O-O");
+        assert_eq!(board.move_to_san_disambiguated(Move::from_uci("e1c1").unwrap()), "O-O-O");
+        assert_eq!(board.move_to_san_disambiguated(Move::from_uci("a1a8").unwrap()), "Ra8+");
+        let board = Board::from_fen("6k1/5ppp/8/8/8/8/5PPP/R5K1 w - - 0 1").unwrap();
+        assert_eq!(board.move_to_san_disambiguated(Move::from_uci("a1a8").unwrap()), "Ra8#");
+    }
+
+    #[test]
+    fn test_get_game_result() {
+        crate::bitboard::initialize_engine();
+
+        assert_eq!(Board::new().get_game_result(), GameResult::InProgress);
+        assert_eq!(Board::new().game_result(), None);
+
+        // Fool's mate
+        let mut board = Board::new();
+        for san in ["f3", "e5", "g4", "Qh4#"] {
+            board.make_move_san(san).unwrap();
+        }
+        assert_eq!(board.get_game_result(), GameResult::BlackWins);
+
+        let stalemate = Board::from_fen("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1").unwrap();
+        assert_eq!(stalemate.get_game_result(), GameResult::Draw(DrawReason::Stalemate));
+
+        let fifty_moves = Board::from_fen("4k3/8/8/8/8/8/R7/4K3 w - - 100 80").unwrap();
+        assert_eq!(fifty_moves.get_game_result(), GameResult::Draw(DrawReason::FiftyMove));
+
+        let bare_kings = Board::from_fen("4k3/8/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+        assert_eq!(bare_kings.get_game_result(), GameResult::Draw(DrawReason::InsufficientMaterial));
+
+        let mut board = Board::new();
+        for san in ["Nf3", "Nf6", "Ng1", "Ng8", "Nf3", "Nf6", "Ng1", "Ng8"] {
+            board.make_move_san(san).unwrap();
+        }
+        assert_eq!(board.get_game_result(), GameResult::Draw(DrawReason::Repetition));
+        assert_eq!(board.pgn_result(), "1/2-1/2");
+    }
+
+    #[test]
+    fn test_get_all_captures() {
+        crate::bitboard::initialize_engine();
+
+        for fen in [
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 b kq - 0 1",
+            "8/8/8/K2pP2r/8/8/8/7k w - d6 0 1",
+            "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+            "4k3/8/8/8/8/5n2/6PP/6RK w - - 0 1",
+        ] {
+            let board = Board::from_fen(fen).unwrap();
+            let mut expected: Vec<Move> = board.get_all_legal_moves().into_iter()
+                .filter(|&mv| !is_empty(board.get_piece(mv.to)) || board.is_en_passant_move(mv))
+                .collect();
+            let mut captures = board.get_all_captures();
+            let key = |mv: &Move| (mv.from.0, mv.to.0, mv.promotion);
+            expected.sort_by_key(key);
+            captures.sort_by_key(key);
+            assert_eq!(captures, expected, "{}", fen);
+        }
+
+        // Capturing onto the last rank promotes, and quiet promotions aren't captures
+        let board = Board::from_fen("1n2k3/P7/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+        let captures: Vec<String> = board.get_all_captures().iter().map(|mv| mv.to_string()).collect();
+        assert_eq!(captures, ["a7b8q", "a7b8r", "a7b8b", "a7b8n"]);
+        assert!(Board::new().get_all_captures().is_empty());
+
+        // Leaf capture counts agree with the reference breakdowns
+        for position in crate::perft::PERFT_POSITIONS.iter() {
+            for details in position.expected_details.iter().filter(|details| details.depth <= 3) {
+                let mut board = Board::from_fen(position.fen).unwrap();
+                assert_eq!(crate::perft::perft_captures(&mut board, details.depth), details.captures,
+                           "{} at depth {}", position.name, details.depth);
+            }
+        }
+    }
+
+    #[test]
+    fn test_is_quiet_position() {
+        crate::bitboard::initialize_engine();
+        let quiet = |fen: &str| Board::from_fen(fen).unwrap().is_quiet_position();
+
+        assert!(quiet("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1"));
+        // Free pawn, and an even trade of knights
+        assert!(!quiet("4k3/8/8/3p4/8/2N5/8/4K3 w - - 0 1"));
+        assert!(!quiet("4k3/2p5/3n4/8/4N3/8/8/4K3 w - - 0 1"));
+        // Queen takes a defended pawn: the only capture loses
+        assert!(quiet("4k3/8/2p5/3p4/8/8/3Q4/4K3 w - - 0 1"));
+        // In check, or a pawn about to promote
+        assert!(!quiet("4k3/8/8/8/8/8/8/r3K3 w - - 0 1"));
+        assert!(!quiet("4k3/1P6/8/8/8/8/8/4K3 w - - 0 1"));
+        assert!(!quiet("4k3/8/8/8/8/8/1p6/4K3 b - - 0 1"));
+        // En passant that wins a pawn
+        assert!(!quiet("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1"));
     }
 }
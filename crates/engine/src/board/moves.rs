@@ -1,6 +1,6 @@
 use crate::types::*;
 use super::Board;
-use crate::bitboard::{iterate_bits, index_to_square, get_knight_attacks, get_king_attacks};
+use crate::bitboard::{Bitboard, BITBOARD_EMPTY, iterate_bits, index_to_square, get_knight_attacks, get_king_attacks, get_pawn_attacks, get_rook_attacks, get_bishop_attacks};
 
 
 
@@ -12,46 +12,198 @@ impl Board {
 
         // OPTIMIZATION: Get all pieces of current color using bitboards - O(1) operation
         let our_pieces = self.bitboards.get_all_pieces(self.current_turn);
+        // Pins are found once and shared by every piece's move generation
+        let pinned = self.pinned_pieces(self.current_turn);
         
         // OPTIMIZATION: Iterate only over squares with our pieces - O(actual_pieces) instead of O(64)
         for square_index in iterate_bits(our_pieces) {
             let square = index_to_square(square_index);
-            let piece = self.get_piece(square);
-            
-            // We know this square has our piece, so no empty check needed
-            let piece_moves = self.get_legal_moves(square);
-            let piece_type_val = piece_type(piece);
-            
-            for target_square in piece_moves {
-                // Check if this is a pawn promotion
-                if piece_type_val == PAWN {
-                    let promotion_rank = if piece_color(piece) == WHITE { 7 } else { 0 };
-                    if target_square.rank() == promotion_rank {
-                        // Generate 4 promotion moves - ✅ REMOVE DOUBLE VALIDATION
-                        for &promotion_piece in &[QUEEN, ROOK, BISHOP, KNIGHT] {
-                            let promotion_move = Move::new_promotion(square, target_square, promotion_piece);
-                            all_moves.push(promotion_move); // ✅ No extra validation needed
-                        }
-                    } else {
-                        // Regular pawn move
-                        let regular_move = Move::new(square, target_square);
-                        all_moves.push(regular_move); // ✅ No extra validation needed
+            all_moves.extend(self.legal_moves_from_pinned(square, pinned));
+        }
+        
+        all_moves
+    }
+
+    /// Legal moves for a side in check: safe king steps, plus captures of and interpositions
+    /// against a lone checker. Same moves as `get_all_legal_moves`, with the check worked out once
+    pub fn generate_check_evasions(&self) -> Vec<Move> {
+        debug_assert!(self.is_in_check(), "generate_check_evasions called without a check");
+        let color = self.current_turn;
+        let opponent_color = opposite_color(color);
+        let king_square = match self.find_king(color) {
+            Some(square) => square,
+            None => return Vec::new(),
+        };
+        let pinned = self.pinned_pieces(color);
+
+        let king_targets = self.get_pseudo_legal_moves_pinned(king_square, pinned);
+        *self.ignore_square_for_threats.borrow_mut() = Some(king_square);
+        let king_targets = self.filter_king_moves_in_check(king_targets, opponent_color);
+        *self.ignore_square_for_threats.borrow_mut() = None;
+        let mut moves: Vec<Move> = king_targets.into_iter().map(|to| Move::new(king_square, to)).collect();
+
+        // Double check: only the king can move
+        let checking_pieces = self.find_checking_pieces(king_square, opponent_color);
+        if checking_pieces.len() != 1 {
+            return moves;
+        }
+        let checking_piece_square = checking_pieces[0];
+        let blocking_squares = self.get_blocking_squares(king_square, checking_piece_square)
+            .into_iter()
+            .fold(BITBOARD_EMPTY, |mask, square| mask | (1u64 << square.0));
+
+        // A pinned piece stays on its pin ray, so it can neither block nor capture the checker
+        let defenders = self.bitboards.get_all_pieces(color) & !pinned & !(1u64 << king_square.0);
+        let promotion_rank = if color == WHITE { 7 } else { 0 };
+
+        for from in iterate_bits(defenders) {
+            let square = Square(from);
+            let is_pawn = piece_type(self.squares[from as usize]) == PAWN;
+
+            for to in self.get_pseudo_legal_moves_pinned(square, pinned) {
+                let mv = Move::new(square, to);
+                let en_passant = is_pawn && self.is_en_passant_move(mv);
+                if en_passant {
+                    // Only answers the check by taking the pawn that just gave it
+                    if self.en_passant_pawn != Some(checking_piece_square) || !self.is_en_passant_legal(mv) {
+                        continue;
+                    }
+                } else if blocking_squares & (1u64 << to.0) == 0 {
+                    continue;
+                }
+
+                if is_pawn && to.rank() == promotion_rank {
+                    for &promotion_piece in &[QUEEN, ROOK, BISHOP, KNIGHT] {
+                        moves.push(Move::new_promotion(square, to, promotion_piece));
                     }
                 } else {
-                    // Non-pawn move
-                    let regular_move = Move::new(square, target_square);
-                    all_moves.push(regular_move); // ✅ No extra validation needed
+                    moves.push(mv);
                 }
             }
         }
-        
-        all_moves
+
+        moves
+    }
+
+    /// True if the current player has at least one legal move (stops at the first one found)
+    pub fn can_player_move(&self) -> bool {
+        let our_pieces = self.bitboards.get_all_pieces(self.current_turn);
+        let pinned = self.pinned_pieces(self.current_turn);
+        iterate_bits(our_pieces).any(|square_index| !self.legal_moves_from_pinned(index_to_square(square_index), pinned).is_empty())
+    }
+
+    /// Captures (en passant included) and promotions for the current player. Targets come
+    /// straight from the attack masks, so quiet moves are never generated
+    pub fn noisy_moves(&self) -> Vec<Move> {
+        self.generate_captures(true)
+    }
+
+    /// Every legal capture for the current player, en passant included. Captures onto the last
+    /// rank come as all four promotions, queen first
+    pub fn get_all_captures(&self) -> Vec<Move> {
+        self.generate_captures(false)
+    }
+
+    /// Squares the piece on `square` attacks that hold an enemy piece, or the en passant square
+    /// for a pawn. Pins and checks aren't considered
+    pub fn get_piece_captures(&self, square: Square) -> Bitboard {
+        let piece = self.get_piece(square);
+        if is_empty(piece) {
+            return BITBOARD_EMPTY;
+        }
+        let color = piece_color(piece);
+        let from = square.0;
+        let enemy_pieces = self.bitboards.get_all_pieces(opposite_color(color));
+        let occupancy = self.bitboards.all_pieces;
+
+        match piece_type(piece) {
+            PAWN => {
+                let en_passant = self.en_passant_target.filter(|_| color == self.current_turn).map_or(0, |square| 1u64 << square.0);
+                get_pawn_attacks(color, from) & (enemy_pieces | en_passant)
+            }
+            KNIGHT => get_knight_attacks(from) & enemy_pieces,
+            BISHOP => get_bishop_attacks(from, occupancy) & enemy_pieces,
+            ROOK => get_rook_attacks(from, occupancy) & enemy_pieces,
+            QUEEN => (get_rook_attacks(from, occupancy) | get_bishop_attacks(from, occupancy)) & enemy_pieces,
+            KING => get_king_attacks(from) & enemy_pieces,
+            _ => BITBOARD_EMPTY,
+        }
+    }
+
+    /// Legal captures, plus quiet promotion pushes when `with_promotions` is set
+    fn generate_captures(&self, with_promotions: bool) -> Vec<Move> {
+        let color = self.current_turn;
+        let occupancy = self.bitboards.all_pieces;
+        let promotion_rank = if color == WHITE { 7 } else { 0 };
+        let mut moves = Vec::new();
+
+        for from in iterate_bits(self.bitboards.get_all_pieces(color)) {
+            let is_pawn = piece_type(self.squares[from as usize]) == PAWN;
+            let mut targets = self.get_piece_captures(Square(from));
+            if is_pawn && with_promotions {
+                let push = if color == WHITE { from + 8 } else { from.wrapping_sub(8) };
+                if push < 64 && push / 8 == promotion_rank && occupancy & (1u64 << push) == 0 {
+                    targets |= 1u64 << push;
+                }
+            }
+
+            for to in iterate_bits(targets) {
+                if is_pawn && to / 8 == promotion_rank {
+                    for &promotion_piece in &[QUEEN, ROOK, BISHOP, KNIGHT] {
+                        moves.push(Move::new_promotion(Square(from), Square(to), promotion_piece));
+                    }
+                } else {
+                    moves.push(Move::new(Square(from), Square(to)));
+                }
+            }
+        }
+
+        moves.retain(|&mv| self.is_legal_move_fast(mv));
+        moves
+    }
+
+    /// Generate legal moves for the piece at the given square as full `Move` objects
+    /// Pawn moves to the last rank are expanded into the four promotion choices
+    pub fn legal_moves_from(&self, square: Square) -> Vec<Move> {
+        self.legal_moves_from_pinned(square, self.pinned_pieces(self.current_turn))
+    }
+
+    /// `legal_moves_from` with the side to move's pinned pieces already known
+    fn legal_moves_from_pinned(&self, square: Square, pinned: Bitboard) -> Vec<Move> {
+        let piece = self.get_piece(square);
+        let target_squares = self.get_legal_moves_pinned(square, pinned);
+        let mut moves = Vec::with_capacity(target_squares.len());
+
+        let promotion_rank = if piece_color(piece) == WHITE { 7 } else { 0 };
+        let is_pawn = piece_type(piece) == PAWN;
+
+        for target_square in target_squares {
+            if is_pawn && target_square.rank() == promotion_rank {
+                // Generate 4 promotion moves
+                for &promotion_piece in &[QUEEN, ROOK, BISHOP, KNIGHT] {
+                    moves.push(Move::new_promotion(square, target_square, promotion_piece));
+                }
+            } else {
+                let mv = Move::new(square, target_square);
+                // En passant can expose the king along the rank, which get_legal_moves misses
+                if is_pawn && self.is_en_passant_move(mv) && !self.is_en_passant_legal(mv) {
+                    continue;
+                }
+                moves.push(mv);
+            }
+        }
+
+        moves
     }
 
     /// Get legal moves for a piece at the given square
     pub fn get_legal_moves(&self, square: Square) -> Vec<Square> {
+        self.get_legal_moves_pinned(square, self.pinned_pieces(self.current_turn))
+    }
+
+    fn get_legal_moves_pinned(&self, square: Square, pinned: Bitboard) -> Vec<Square> {
         // Get pseudo-legal moves first
-        let pseudo_moves = self.get_pseudo_legal_moves(square);
+        let pseudo_moves = self.get_pseudo_legal_moves_pinned(square, pinned);
         
         // Check if our king is in check
         let our_color = self.current_turn;
@@ -126,6 +278,10 @@ impl Board {
     
     /// Get pseudo-legal moves (before checking for check/pins)
     pub fn get_pseudo_legal_moves(&self, square: Square) -> Vec<Square> {
+        self.get_pseudo_legal_moves_pinned(square, self.pinned_pieces(self.current_turn))
+    }
+
+    fn get_pseudo_legal_moves_pinned(&self, square: Square, pinned: Bitboard) -> Vec<Square> {
         let piece = self.get_piece(square);
         if is_empty(piece) {
             return Vec::new();
@@ -136,10 +292,15 @@ impl Board {
             return Vec::new();
         }
 
-        // Check if piece is pinned
-        if let Some(pin_direction) = self.is_piece_pinned(square) {
-            let pinned_moves = self.get_pinned_piece_moves(square, pin_direction);
-            return pinned_moves;
+        // Check if piece is pinned; the pin runs from the piece toward its king
+        if pinned & (1u64 << square.0) != 0 {
+            if let Some(king_square) = self.find_king(self.current_turn) {
+                let pin_direction = (
+                    (king_square.file() as i8 - square.file() as i8).signum(),
+                    (king_square.rank() as i8 - square.rank() as i8).signum(),
+                );
+                return self.get_pinned_piece_moves(square, pin_direction);
+            }
         }
 
 
@@ -311,17 +472,11 @@ impl Board {
             remaining_moves &= remaining_moves - 1; // Remove the processed bit
         }
         
-        // Add castling moves (unchanged - castling logic remains the same)
-        if self.can_castle(source_color, true) {
-            // Kingside castling
-            let king_rank = square.rank();
-            moves.push(Square::new(6, king_rank)); // g1 or g8
-        }
-
-        if self.can_castle(source_color, false) {
-            // Queenside castling
-            let king_rank = square.rank();
-            moves.push(Square::new(2, king_rank)); // c1 or c8
+        // Add castling moves: g1/c1 (g8/c8), or the rook's square in Chess960
+        for kingside in [true, false] {
+            if self.can_castle(source_color, kingside) {
+                moves.push(self.castling_target(source_color, kingside));
+            }
         }
 
         moves
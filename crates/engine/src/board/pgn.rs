@@ -0,0 +1,301 @@
+use crate::types::*;
+use super::Board;
+
+/// Movetext lines are wrapped to this width
+const PGN_LINE_WIDTH: usize = 80;
+
+/// Header tags for PGN export (the seven tag roster)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PgnMetadata {
+    pub event: String,
+    pub site: String,
+    pub date: String, // "YYYY.MM.DD", with '?' for unknown parts
+    pub round: String,
+    pub white: String,
+    pub black: String,
+    pub result: String, // "1-0", "0-1", "1/2-1/2" or "*"
+}
+
+impl Default for PgnMetadata {
+    fn default() -> Self {
+        Self {
+            event: "?".to_string(),
+            site: "?".to_string(),
+            date: "????.??.??".to_string(),
+            round: "?".to_string(),
+            white: "?".to_string(),
+            black: "?".to_string(),
+            result: "*".to_string(),
+        }
+    }
+}
+
+impl PgnMetadata {
+    /// Casual game played today between the given players
+    pub fn new(white: &str, black: &str) -> Self {
+        Self {
+            event: "Casual game".to_string(),
+            date: chrono::Local::now().format("%Y.%m.%d").to_string(),
+            white: white.to_string(),
+            black: black.to_string(),
+            ..Self::default()
+        }
+    }
+}
+
+/// Why a PGN game failed to load, with the 1-based line and column it went wrong at
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PgnError {
+    InvalidTag { line: usize, column: usize },
+    InvalidFen { line: usize, column: usize, error: MoveError },
+    UnterminatedComment { line: usize, column: usize },
+    UnbalancedVariation { line: usize, column: usize },
+    IllegalMove { line: usize, column: usize, san: String, error: MoveError },
+}
+
+impl std::fmt::Display for PgnError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PgnError::InvalidTag { line, column } => write!(f, "Malformed tag at line {}, column {}", line, column),
+            PgnError::InvalidFen { line, column, error } => write!(f, "Bad FEN tag at line {}, column {}: {}", line, column, error),
+            PgnError::UnterminatedComment { line, column } => write!(f, "Comment opened at line {}, column {} is never closed", line, column),
+            PgnError::UnbalancedVariation { line, column } => write!(f, "Unbalanced variation at line {}, column {}", line, column),
+            PgnError::IllegalMove { line, column, san, error } => write!(f, "Move {} at line {}, column {}: {}", san, line, column, error),
+        }
+    }
+}
+
+impl std::error::Error for PgnError {}
+
+/// Characters that end a movetext token
+fn is_token_end(c: char) -> bool {
+    c.is_whitespace() || matches!(c, '{' | '}' | '(' | ')' | '[' | ']' | ';')
+}
+
+/// `Name "value"` between the brackets of a tag pair, with `\\` and `\"` unescaped
+fn parse_tag(body: &str) -> Option<(String, String)> {
+    let (name, rest) = body.trim().split_once(char::is_whitespace)?;
+    let quoted = rest.trim().strip_prefix('"')?.strip_suffix('"')?;
+
+    let mut value = String::new();
+    let mut chars = quoted.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => value.push(chars.next()?),
+            '"' => return None,
+            _ => value.push(c),
+        }
+    }
+    Some((name.to_string(), value))
+}
+
+impl Board {
+    /// Play out the first game of a PGN, returning the final position
+    pub fn from_pgn(pgn: &str) -> Result<Board, PgnError> {
+        Self::from_pgn_with_metadata(pgn).map(|(board, _)| board)
+    }
+
+    /// Like `from_pgn`, also returning the header tags. Comments, NAGs and variations are skipped
+    pub fn from_pgn_with_metadata(pgn: &str) -> Result<(Board, PgnMetadata), PgnError> {
+        let chars: Vec<char> = pgn.chars().collect();
+        // 1-based (line, column) of every character
+        let mut positions = Vec::with_capacity(chars.len());
+        let (mut line, mut column) = (1, 1);
+        for &c in &chars {
+            positions.push((line, column));
+            if c == '\n' {
+                line += 1;
+                column = 1;
+            } else {
+                column += 1;
+            }
+        }
+
+        let mut metadata = PgnMetadata::default();
+        let mut start_fen: Option<(String, (usize, usize))> = None;
+        let mut board: Option<Board> = None;
+        let mut variation_starts = Vec::new();
+        let mut i = 0;
+
+        while i < chars.len() {
+            let c = chars[i];
+            let (line, column) = positions[i];
+
+            if c.is_whitespace() {
+                i += 1;
+            } else if c == '{' {
+                match chars[i..].iter().position(|&c| c == '}') {
+                    Some(length) => i += length + 1,
+                    None => return Err(PgnError::UnterminatedComment { line, column }),
+                }
+            } else if c == ';' {
+                // Comment to the end of the line
+                i = chars[i..].iter().position(|&c| c == '\n').map_or(chars.len(), |length| i + length);
+            } else if c == '(' {
+                variation_starts.push((line, column));
+                i += 1;
+            } else if c == ')' {
+                if variation_starts.pop().is_none() {
+                    return Err(PgnError::UnbalancedVariation { line, column });
+                }
+                i += 1;
+            } else if c == '[' {
+                // Tag pairs only come before the first move
+                if board.is_some() || !variation_starts.is_empty() {
+                    return Err(PgnError::InvalidTag { line, column });
+                }
+                let length = chars[i..].iter().position(|&c| c == ']')
+                    .ok_or(PgnError::InvalidTag { line, column })?;
+                let body: String = chars[i + 1..i + length].iter().collect();
+                let (name, value) = parse_tag(&body).ok_or(PgnError::InvalidTag { line, column })?;
+                match name.as_str() {
+                    "Event" => metadata.event = value,
+                    "Site" => metadata.site = value,
+                    "Date" => metadata.date = value,
+                    "Round" => metadata.round = value,
+                    "White" => metadata.white = value,
+                    "Black" => metadata.black = value,
+                    "Result" => metadata.result = value,
+                    "FEN" => start_fen = Some((value, (line, column))),
+                    _ => {}
+                }
+                i += length + 1;
+            } else {
+                let length = chars[i..].iter().position(|&c| is_token_end(c)).unwrap_or(chars.len() - i).max(1);
+                let token: String = chars[i..i + length].iter().collect();
+                i += length;
+
+                // Moves inside a variation aren't played
+                if !variation_starts.is_empty() {
+                    continue;
+                }
+
+                if matches!(token.as_str(), "1-0" | "0-1" | "1/2-1/2" | "*") {
+                    metadata.result = token;
+                    break;
+                }
+
+                // NAGs ("$1") and standalone annotations ("!?") carry no move
+                let token = if token.starts_with('$') { "" } else { token.as_str() };
+                // Move numbers, possibly run together with the move ("12.e4", "12...Nf6")
+                let san = match token.rfind('.') {
+                    Some(dot) if token[..dot].chars().all(|c| c.is_ascii_digit() || c == '.') => &token[dot + 1..],
+                    _ => token,
+                };
+                let san = san.trim_end_matches(['!', '?']);
+                if san.is_empty() {
+                    continue;
+                }
+
+                if board.is_none() {
+                    board = Some(Self::pgn_start_position(&start_fen)?);
+                }
+                let current = board.as_mut().unwrap();
+                current.make_move_san(san).map_err(|error| PgnError::IllegalMove {
+                    line,
+                    column,
+                    san: san.to_string(),
+                    error,
+                })?;
+            }
+        }
+
+        if let Some(&(line, column)) = variation_starts.last() {
+            return Err(PgnError::UnbalancedVariation { line, column });
+        }
+        let board = match board {
+            Some(board) => board,
+            None => Self::pgn_start_position(&start_fen)?,
+        };
+        Ok((board, metadata))
+    }
+
+    /// Initial position, or the one given by a FEN tag
+    fn pgn_start_position(start_fen: &Option<(String, (usize, usize))>) -> Result<Board, PgnError> {
+        match start_fen {
+            None => Ok(Board::new()),
+            Some((fen, (line, column))) => Board::from_fen(fen).map_err(|error| PgnError::InvalidFen {
+                line: *line,
+                column: *column,
+                error,
+            }),
+        }
+    }
+
+    /// SAN for movetext: the departure file, rank or both are added when another piece of the
+    /// same type could also reach the target ("Nbd7"), plus "+" or "#"
+    pub fn move_to_san_disambiguated(&self, mv: Move) -> String {
+        self.move_to_san(mv)
+    }
+
+    /// PGN result token for the current position ("*" while the game is still going)
+    pub fn pgn_result(&self) -> &'static str {
+        match self.get_game_result() {
+            GameResult::InProgress => "*",
+            GameResult::WhiteWins => "1-0",
+            GameResult::BlackWins => "0-1",
+            GameResult::Draw(_) => "1/2-1/2",
+        }
+    }
+
+    /// Export the game so far as PGN, replaying the move history on a scratch board for SAN
+    pub fn to_pgn(&self, metadata: &PgnMetadata) -> String {
+        let mut replay = self.clone();
+        replay.logger = None;
+        let moves = replay.goto_move(0).unwrap_or_default();
+
+        let mut pgn = String::new();
+        let tags = [
+            ("Event", &metadata.event),
+            ("Site", &metadata.site),
+            ("Date", &metadata.date),
+            ("Round", &metadata.round),
+            ("White", &metadata.white),
+            ("Black", &metadata.black),
+            ("Result", &metadata.result),
+        ];
+        for (name, value) in tags {
+            pgn.push_str(&format!("[{} \"{}\"]\n", name, value.replace('\\', "\\\\").replace('"', "\\\"")));
+        }
+
+        // Games that don't start from the initial position carry it along
+        let start_fen = replay.to_fen();
+        if start_fen != Board::new().to_fen() {
+            pgn.push_str("[SetUp \"1\"]\n");
+            pgn.push_str(&format!("[FEN \"{}\"]\n", start_fen));
+        }
+        pgn.push('\n');
+
+        let mut tokens = Vec::new();
+        for (index, game_move) in moves.iter().enumerate() {
+            if replay.current_turn == WHITE {
+                tokens.push(format!("{}.", replay.full_move_number));
+            } else if index == 0 {
+                tokens.push(format!("{}...", replay.full_move_number));
+            }
+
+            tokens.push(replay.move_to_san_disambiguated(game_move.mv));
+            if replay.try_make_move(game_move.mv).is_err() {
+                break;
+            }
+        }
+        tokens.push(metadata.result.clone());
+
+        let mut line = String::new();
+        for token in tokens {
+            if !line.is_empty() && line.len() + 1 + token.len() > PGN_LINE_WIDTH {
+                pgn.push_str(&line);
+                pgn.push('\n');
+                line.clear();
+            }
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            line.push_str(&token);
+        }
+        pgn.push_str(&line);
+        pgn.push('\n');
+
+        pgn
+    }
+}
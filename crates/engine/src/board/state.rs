@@ -1,34 +1,36 @@
 use crate::types::*;
 use super::Board;
+use crate::bitboard::{Bitboard, iterate_bits};
 
 impl Board {
     /// Execute a move and update game state
-    pub fn try_make_move(&mut self, mv: Move) -> Result<GameMove, String> {
-        if !self.is_valid_move(mv) {
-            return Err("Invalid move".to_string());
-        }
-    
+    pub fn try_make_move(&mut self, mv: Move) -> Result<GameMove, MoveError> {
+        #[cfg(test)]
+        debug_assert!(self.check_consistency_with_bitboards(), "Bitboards out of sync before {:?}", mv);
+
         match self.game_status {
             GameStatus::InProgress | GameStatus::Check(_) => {
                 // Game can continue
             },
             _ => {
-                return Err("Game is over".to_string());
+                return Err(MoveError::GameAlreadyOver);
             }
         }
+
+        if !self.is_legal_move_fast(mv) {
+            return Err(MoveError::IllegalMove(mv.to_string()));
+        }
     
-        let captured_piece = self.get_piece(mv.to);
+        let position_key = self.position_key();
         let moving_piece = self.get_piece(mv.from);
     
         // CHECK FOR SPECIAL MOVES FIRST (before clearing en passant)
         let is_castling = self.is_castling_move(mv).is_some();
         let is_en_passant = self.is_en_passant_move(mv);
+        // A Chess960 castling move lands on our own rook, which isn't captured
+        let captured_piece = if is_castling { EMPTY } else { self.get_piece(mv.to) };
     
-        // THEN clear en passant target for next move
-
-        self.en_passant_target = None;
-        self.en_passant_pawn = None;
-    
+        // Snapshot the state for undo while the en passant square is still set
         let mut game_move = if is_en_passant {
             let captured_pawn = self.get_piece(self.en_passant_pawn.unwrap_or(mv.to));
             GameMove::with_capture_and_state(mv, captured_pawn, self)
@@ -37,19 +39,22 @@ impl Board {
         } else {
             GameMove::with_capture_and_state(mv, captured_piece, self)
         };
+
+        // THEN clear en passant target for next move
+        self.en_passant_target = None;
+        self.en_passant_pawn = None;
     
         game_move.is_castling = is_castling;
         game_move.is_en_passant = is_en_passant;
         game_move.promotion = mv.promotion;
     
         if !is_castling && !is_en_passant {
-            self.update_castling_rights_fixed(mv, moving_piece, captured_piece);
+            self.update_castling_rights_fixed(mv, moving_piece);
         }
     
         // Execute the move
         if is_castling {
-            let kingside = self.is_castling_move(mv).unwrap();
-            self.execute_castling(piece_color(moving_piece), kingside);
+            self.execute_castling(mv);
         } else if is_en_passant {
             self.execute_en_passant(mv);
         } else {
@@ -68,7 +73,9 @@ impl Board {
         }
     
         self.move_history.push(game_move.clone());
+        self.position_history.push(position_key);
         self.current_turn = opposite_color(self.current_turn);
+        self.update_hash_for_move(mv);
     
         if piece_type(moving_piece) == PAWN || !is_empty(captured_piece) || is_en_passant {
             self.half_move_clock = 0;
@@ -80,11 +87,21 @@ impl Board {
             self.full_move_number += 1;
         }
         self.update_game_status();
+
+        #[cfg(test)]
+        debug_assert!(self.check_consistency_with_bitboards(), "Bitboards out of sync after {:?}", mv);
+        #[cfg(test)]
+        debug_assert_eq!(self.position_hash, self.compute_hash(), "Hash out of sync after {:?}", mv);
+        #[cfg(debug_assertions)]
+        if let Err(error) = self.validate() {
+            panic!("Inconsistent board after {}: {} ({})", mv, error, self.to_fen());
+        }
+
         Ok(game_move)
     }
     
     
-    pub fn update_castling_rights_fixed(&mut self, mv: Move, moving_piece: Piece, captured_piece: Piece) {
+    pub fn update_castling_rights_fixed(&mut self, mv: Move, moving_piece: Piece) {
         let piece_color_val = piece_color(moving_piece);
     
         if piece_type(moving_piece) == KING {
@@ -99,35 +116,64 @@ impl Board {
     
         // Handle rook moves (from square)
         if piece_type(moving_piece) == ROOK {
-            match (mv.from.file(), mv.from.rank()) {
-                (0, 0) => remove_castling_right(&mut self.castling_rights, WHITE_QUEENSIDE), // a1
-                (7, 0) => remove_castling_right(&mut self.castling_rights, WHITE_KINGSIDE),  // h1
-                (0, 7) => remove_castling_right(&mut self.castling_rights, BLACK_QUEENSIDE), // a8
-                (7, 7) => remove_castling_right(&mut self.castling_rights, BLACK_KINGSIDE),  // h8
-                _ => {}
+            if let Some(right) = self.castling_right_at(mv.from) {
+                remove_castling_right(&mut self.castling_rights, right);
             }
         }
     
-        // Handle captured rooks (to square) - ✅ NOW we have the captured piece!
-        if piece_type(captured_piece) == ROOK {
-            match (mv.to.file(), mv.to.rank()) {
-                (0, 0) => remove_castling_right(&mut self.castling_rights, WHITE_QUEENSIDE), // a1
-                (7, 0) => remove_castling_right(&mut self.castling_rights, WHITE_KINGSIDE),  // h1
-                (0, 7) => remove_castling_right(&mut self.castling_rights, BLACK_QUEENSIDE), // a8
-                (7, 7) => remove_castling_right(&mut self.castling_rights, BLACK_KINGSIDE),  // h8
-                _ => {}
-            }
+        // Handle moves onto a rook origin square (to square). Whatever stands there
+        // is being captured, so the rook that castles from that square is gone
+        if let Some(right) = self.castling_right_at(mv.to) {
+            remove_castling_right(&mut self.castling_rights, right);
         }
     }
+
+    /// The castling right whose rook starts on `square`, if any
+    fn castling_right_at(&self, square: Square) -> Option<u8> {
+        (0..4).map(|index| 1u8 << index).find(|&right| {
+            let back_rank = if right & (WHITE_KINGSIDE | WHITE_QUEENSIDE) != 0 { 0 } else { 7 };
+            square == Square::new(self.castling_rook_files[right.trailing_zeros() as usize], back_rank)
+        })
+    }
+
+    /// Square the king moves to when castling: g1/c1 (and g8/c8) normally, the castling rook's
+    /// square in Chess960, where g1 may also be an ordinary king step
+    pub fn castling_target(&self, color: u8, kingside: bool) -> Square {
+        let back_rank = if color == WHITE { 0 } else { 7 };
+        if self.chess960 {
+            let right = castling_right(color, kingside);
+            Square::new(self.castling_rook_files[right.trailing_zeros() as usize], back_rank)
+        } else {
+            Square::new(if kingside { 6 } else { 2 }, back_rank)
+        }
+    }
+
+    /// (king destination, rook origin, rook destination) of a castling move. Both variants end
+    /// with the king on the g- or c-file and the rook beside it
+    pub fn castling_squares(&self, mv: Move) -> (Square, Square, Square) {
+        let back_rank = mv.from.rank();
+        let kingside = mv.to.file() > mv.from.file();
+        let rook_from = if self.chess960 { mv.to } else { Square::new(if kingside { 7 } else { 0 }, back_rank) };
+        let (king_file, rook_file) = if kingside { (6, 5) } else { (2, 3) };
+        (Square::new(king_file, back_rank), rook_from, Square::new(rook_file, back_rank))
+    }
     
 
     /// Undo the last move made
-    pub fn undo_move(&mut self) -> Result<GameMove, String> {
+    pub fn undo_move(&mut self) -> Result<GameMove, MoveError> {
+        if self.move_history.last().is_some_and(|game_move| game_move.is_null_move) {
+            return self.undo_null_move();
+        }
+
         // Get the last move from history
         let last_move = match self.move_history.pop() {
             Some(mv) => mv,
-            None => return Err("No moves to undo".to_string()),
+            None => return Err(MoveError::NoMoveToUndo),
         };
+        // The key from before the move is exactly the hash to go back to
+        if let Some(previous_hash) = self.position_history.pop() {
+            self.position_hash = previous_hash;
+        }
 
         // Restore the pieces on the board
         self.restore_pieces(&last_move);
@@ -138,32 +184,81 @@ impl Board {
         self.en_passant_pawn = last_move.previous_en_passant_pawn;
         self.half_move_clock = last_move.previous_half_move_clock;
         self.full_move_number = last_move.previous_full_move_number;
+        self.game_status = last_move.previous_game_status;
 
         // Switch turn back
         self.current_turn = opposite_color(self.current_turn);
 
+        #[cfg(debug_assertions)]
+        if let Err(error) = self.validate() {
+            panic!("Inconsistent board after undoing {}: {} ({})", last_move.mv, error, self.to_fen());
+        }
+
         Ok(last_move)
     }
 
+    /// Pass the turn without moving, for null-move pruning. Clears en passant, which only the
+    /// side giving up its move could have used
+    pub fn apply_null_move(&mut self) {
+        use crate::bitboard::{zobrist_en_passant_key, zobrist_side_key};
+
+        self.move_history.push(GameMove::null_move(self));
+        self.position_history.push(self.position_hash);
+
+        self.position_hash ^= zobrist_side_key() ^ zobrist_en_passant_key(self.en_passant_target) ^ zobrist_en_passant_key(None);
+        self.current_turn = opposite_color(self.current_turn);
+        self.en_passant_target = None;
+        self.en_passant_pawn = None;
+        self.half_move_clock += 1;
+    }
+
+    /// Take back a pass made by `apply_null_move`
+    pub fn undo_null_move(&mut self) -> Result<GameMove, MoveError> {
+        if !self.move_history.last().is_some_and(|game_move| game_move.is_null_move) {
+            return Err(MoveError::NoMoveToUndo);
+        }
+        let null_move = self.move_history.pop().unwrap();
+        if let Some(previous_hash) = self.position_history.pop() {
+            self.position_hash = previous_hash;
+        }
+
+        self.current_turn = opposite_color(self.current_turn);
+        self.en_passant_target = null_move.previous_en_passant_target;
+        self.en_passant_pawn = null_move.previous_en_passant_pawn;
+        self.half_move_clock = null_move.previous_half_move_clock;
+
+        Ok(null_move)
+    }
+
+    /// Undo moves from the end of the history until only `index` moves remain.
+    /// Returns the undone moves in the order they were originally played
+    pub fn goto_move(&mut self, index: usize) -> Result<Vec<GameMove>, MoveError> {
+        if index > self.move_history.len() {
+            return Err(MoveError::NoMoveToUndo);
+        }
+
+        let mut undone = Vec::with_capacity(self.move_history.len() - index);
+        while self.move_history.len() > index {
+            undone.push(self.undo_move()?);
+        }
+        undone.reverse();
+
+        Ok(undone)
+    }
+
     /// Restore pieces after undoing a move
+    /// Everything is taken from the stored `GameMove`, never from the board being restored
     fn restore_pieces(&mut self, game_move: &GameMove) {
         let mv = game_move.mv;
 
         if game_move.is_castling {
-            self.undo_castling(mv);
+            self.undo_castling(game_move);
         } else if game_move.is_en_passant {
             self.undo_en_passant(game_move);
-        } else if game_move.promotion.is_some() {
-            // PROMOTION UNDO: Restore original pawn, not promoted piece
-            let original_pawn_color = if mv.from.rank() == 6 { WHITE } else { BLACK };
-            let original_pawn = make_piece(PAWN, original_pawn_color);
-            
-            self.set_piece(mv.from, original_pawn);
-            self.set_piece(mv.to, game_move.captured_piece);
         } else {
-            // Regular move - move piece back and restore captured piece
-            let moving_piece = self.get_piece(mv.to);
-            self.set_piece(mv.from, moving_piece);
+            // Regular moves and promotions alike: the moved piece (the pawn, for a
+            // promotion) goes back and whatever was captured returns
+            self.set_piece(mv.from, game_move.moved_piece);
             self.set_piece(mv.to, game_move.captured_piece);
         }
     }
@@ -171,44 +266,22 @@ impl Board {
 
     /// Check if castling is possible for a given color and side
     pub fn can_castle(&self, color: u8, kingside: bool) -> bool {
-        // Determine castling right to check
-        let castling_right = match (color, kingside) {
-            (WHITE, true) => WHITE_KINGSIDE,
-            (WHITE, false) => WHITE_QUEENSIDE,
-            (BLACK, true) => BLACK_KINGSIDE,
-            (BLACK, false) => BLACK_QUEENSIDE,
-            _ => return false,
-        };
-
         // Check if we have castling rights
-        if !has_castling_right(self.castling_rights, castling_right) {
+        if !has_castling_right(self.castling_rights, castling_right(color, kingside)) {
             return false;
         }
 
         // Determine squares involved
         let king_rank = if color == WHITE { 0 } else { 7 };
-        let king_start = Square::new(4, king_rank); // e1 or e8
-
-        let (king_end, rook_start, squares_to_check) = if kingside {
-            // Kingside castling
-            let king_end = Square::new(6, king_rank); // g1 or g8
-            let rook_start = Square::new(7, king_rank); // h1 or h8
-            let squares = vec![
-                Square::new(5, king_rank), // f1 or f8
-                Square::new(6, king_rank), // g1 or g8
-            ];
-            (king_end, rook_start, squares)
+        let king_start = if self.chess960 {
+            match self.find_king(color) {
+                Some(square) if square.rank() == king_rank => square,
+                _ => return false,
+            }
         } else {
-            // Queenside castling
-            let king_end = Square::new(2, king_rank); // c1 or c8
-            let rook_start = Square::new(0, king_rank); // a1 or a8
-            let squares = vec![
-                Square::new(1, king_rank), // b1 or b8
-                Square::new(2, king_rank), // c1 or c8
-                Square::new(3, king_rank), // d1 or d8
-            ];
-            (king_end, rook_start, squares)
+            Square::new(4, king_rank) // e1 or e8
         };
+        let (king_end, rook_start, rook_end) = self.castling_squares(Move::new(king_start, self.castling_target(color, kingside)));
 
         // Check if king and rook are in correct positions
         let king_piece = self.get_piece(king_start);
@@ -222,65 +295,36 @@ impl Board {
             return false;
         }
 
-        // Check if path is clear
-        for &square in &squares_to_check {
-            if !is_empty(self.get_piece(square)) {
-                return false;
-            }
-        }
-
-        // Check if king is currently in check
-        let opponent_color = opposite_color(color);
-        if self.is_under_threat(king_start, opponent_color) {
-            return false;
-        }
-
-        // Check if king passes through or ends in check
-        if self.is_under_threat(king_end, opponent_color) {
-            return false;
-        }
-
-        // For castling, also check the square king passes through
-        let king_path_square = if kingside {
-            Square::new(5, king_rank) // f1 or f8
-        } else {
-            Square::new(3, king_rank) // d1 or d8
+        // Every square the king or rook crosses must be empty, bar the two of them
+        let span = |a: Square, b: Square| -> Bitboard {
+            (a.file().min(b.file())..=a.file().max(b.file())).fold(0, |mask, file| mask | (1u64 << Square::new(file, king_rank).0))
         };
-
-        if self.is_under_threat(king_path_square, opponent_color) {
+        let castling_pieces = (1u64 << king_start.0) | (1u64 << rook_start.0);
+        let path = span(king_start, king_end) | span(rook_start, rook_end);
+        if self.bitboards.all_pieces & path & !castling_pieces != 0 {
             return false;
         }
 
-        true
+        // The king may not start in, pass through or land in check. Both castling pieces are
+        // lifted first, as a rook shielding the king's path no longer does once it has moved
+        let occupancy = self.bitboards.all_pieces & !castling_pieces;
+        let enemies = self.bitboards.get_all_pieces(opposite_color(color));
+        iterate_bits(span(king_start, king_end))
+            .all(|square| self.get_attackers_to(Square(square), occupancy) & enemies == 0)
     }
 
     /// Execute a castling move
-    fn execute_castling(&mut self, color: u8, kingside: bool) {
-        let king_rank = if color == WHITE { 0 } else { 7 };
-        let king_start = Square::new(4, king_rank);
-
-        let (king_end, rook_start, rook_end) = if kingside {
-            (
-                Square::new(6, king_rank), // King to g1/g8
-                Square::new(7, king_rank), // Rook from h1/h8
-                Square::new(5, king_rank), // Rook to f1/f8
-            )
-        } else {
-            (
-                Square::new(2, king_rank), // King to c1/c8
-                Square::new(0, king_rank), // Rook from a1/a8
-                Square::new(3, king_rank), // Rook to d1/d8
-            )
-        };
-
-        // Move the pieces
-        let king_piece = self.get_piece(king_start);
+    fn execute_castling(&mut self, mv: Move) {
+        let (king_end, rook_start, rook_end) = self.castling_squares(mv);
+        let king_piece = self.get_piece(mv.from);
         let rook_piece = self.get_piece(rook_start);
+        let color = piece_color(king_piece);
 
+        // Lift both pieces first: in Chess960 either may land where the other started
+        self.set_piece(mv.from, EMPTY);
+        self.set_piece(rook_start, EMPTY);
         self.set_piece(king_end, king_piece);
         self.set_piece(rook_end, rook_piece);
-        self.set_piece(king_start, EMPTY);
-        self.set_piece(rook_start, EMPTY);
 
         // Remove all castling rights for this color
         if color == WHITE {
@@ -311,6 +355,13 @@ impl Board {
             return None;
         }
 
+        // Chess960: the king takes its own castling rook
+        if self.chess960 {
+            let kingside = to_file > from_file;
+            let is_own_rook = self.get_piece(mv.to) == make_piece(ROOK, piece_color(from_piece));
+            return (is_own_rook && mv.to == self.castling_target(piece_color(from_piece), kingside)).then_some(kingside);
+        }
+
         // Must be from e-file
         if from_file != 4 {
             return None;
@@ -325,31 +376,16 @@ impl Board {
     }
 
     /// Undo castling move
-    fn undo_castling(&mut self, mv: Move) {
-        let king_rank = mv.to.rank();
-
-        // Determine if it was kingside or queenside castling
-        let is_kingside = mv.to.file() == 6; // g-file
-
-        if is_kingside {
-            // Undo kingside castling
-            let king = self.get_piece(Square::new(6, king_rank));
-            let rook = self.get_piece(Square::new(5, king_rank));
-
-            self.set_piece(Square::new(4, king_rank), king); // King back to e-file
-            self.set_piece(Square::new(7, king_rank), rook); // Rook back to h-file
-            self.set_piece(Square::new(6, king_rank), EMPTY);
-            self.set_piece(Square::new(5, king_rank), EMPTY);
-        } else {
-            // Undo queenside castling
-            let king = self.get_piece(Square::new(2, king_rank));
-            let rook = self.get_piece(Square::new(3, king_rank));
-
-            self.set_piece(Square::new(4, king_rank), king); // King back to e-file
-            self.set_piece(Square::new(0, king_rank), rook); // Rook back to a-file
-            self.set_piece(Square::new(2, king_rank), EMPTY);
-            self.set_piece(Square::new(3, king_rank), EMPTY);
-        }
+    fn undo_castling(&mut self, game_move: &GameMove) {
+        let mv = game_move.mv;
+        let king = game_move.moved_piece;
+        let rook = make_piece(ROOK, piece_color(king));
+        let (king_end, rook_start, rook_end) = self.castling_squares(mv);
+
+        self.set_piece(king_end, EMPTY);
+        self.set_piece(rook_end, EMPTY);
+        self.set_piece(mv.from, king);
+        self.set_piece(rook_start, rook);
     }
 
     /// Update castling rights after a move
@@ -371,15 +407,9 @@ impl Board {
 
         // If rook moves or is captured, remove corresponding castling right
         if piece_type(from_piece) == ROOK || piece_type(to_piece) == ROOK {
-            let squares_to_check = [mv.from, mv.to];
-
-            for square in squares_to_check {
-                match (square.file(), square.rank()) {
-                    (0, 0) => remove_castling_right(&mut self.castling_rights, WHITE_QUEENSIDE), // a1
-                    (7, 0) => remove_castling_right(&mut self.castling_rights, WHITE_KINGSIDE),  // h1
-                    (0, 7) => remove_castling_right(&mut self.castling_rights, BLACK_QUEENSIDE), // a8
-                    (7, 7) => remove_castling_right(&mut self.castling_rights, BLACK_KINGSIDE),  // h8
-                    _ => {}
+            for square in [mv.from, mv.to] {
+                if let Some(right) = self.castling_right_at(square) {
+                    remove_castling_right(&mut self.castling_rights, right);
                 }
             }
         }
@@ -524,29 +554,319 @@ impl Board {
         let mv = game_move.mv;
 
         // Move our pawn back
-        let our_pawn = self.get_piece(mv.to);
-        self.set_piece(mv.from, our_pawn);
+        self.set_piece(mv.from, game_move.moved_piece);
         self.set_piece(mv.to, EMPTY);
 
-        // Restore the captured pawn
-        let captured_pawn_square = if piece_color(our_pawn) == WHITE {
-            Square::new(mv.to.file(), mv.to.rank() - 1) // Black pawn was one rank below
+        // The captured pawn stood beside ours: the target's file on the starting rank
+        self.set_piece(Square::new(mv.to.file(), mv.from.rank()), game_move.captured_piece);
+    }
+
+    /// Update game status (basic implementation for now)
+    pub fn update_game_status(&mut self) {
+        let in_check = self.is_in_check();
+
+        self.game_status = if !self.can_player_move() {
+            if in_check {
+                GameStatus::Checkmate(self.current_turn)
+            } else {
+                GameStatus::Stalemate
+            }
+        } else if let Some(reason) = self.draw_reason() {
+            GameStatus::Draw(reason)
+        } else if in_check {
+            GameStatus::Check(self.current_turn)
         } else {
-            Square::new(mv.to.file(), mv.to.rank() + 1) // White pawn was one rank above
+            GameStatus::InProgress
         };
+    }
 
-        self.set_piece(captured_pawn_square, game_move.captured_piece);
+    /// Side to move is in check and has no legal moves
+    pub fn is_checkmate(&self) -> bool {
+        self.is_in_check() && self.get_all_legal_moves().is_empty()
     }
 
-    /// Update game status (basic implementation for now)
-    pub fn update_game_status(&mut self) {
-        // For now, just set to InProgress
-        // In a more complete implementation, we'd add proper check/checkmate detection
-        self.game_status = GameStatus::InProgress;
+    /// Side to move is not in check but has no legal moves
+    pub fn is_stalemate(&self) -> bool {
+        !self.is_in_check() && self.get_all_legal_moves().is_empty()
+    }
+
+    /// 50 moves by each side without a capture or pawn move
+    pub fn is_fifty_move_draw(&self) -> bool {
+        self.half_move_clock >= 100
+    }
+
+    /// The fifty-move draw is within 20 half-moves
+    pub fn fifty_move_warning(&self) -> bool {
+        self.half_move_clock >= 80
+    }
+
+    /// Full moves left before the fifty-move rule ends the game
+    pub fn moves_until_fifty_draw(&self) -> u16 {
+        100u16.saturating_sub(self.half_move_clock) / 2
+    }
+
+    /// True if the current position has already occurred at least `count` times
+    /// Only positions since the last capture or pawn move can repeat
+    pub fn is_repetition(&self, count: usize) -> bool {
+        let current_key = self.position_key();
+        let reversible_plies = (self.half_move_clock as usize).min(self.position_history.len());
+
+        // Same side to move means every second ply back
+        let occurrences = self.position_history.iter()
+            .rev()
+            .take(reversible_plies)
+            .skip(1)
+            .step_by(2)
+            .filter(|&&key| key == current_key)
+            .count();
+
+        occurrences >= count
+    }
+
+    /// Neither side has enough material to force checkmate
+    /// K vs K, K+minor vs K, K+N+N vs K, and bishops that all stand on one square color
+    pub fn is_insufficient_material(&self) -> bool {
+        for color in [WHITE, BLACK] {
+            if self.num_pieces(color, PAWN) > 0 || self.num_pieces(color, ROOK) > 0 || self.num_pieces(color, QUEEN) > 0 {
+                return false;
+            }
+        }
+
+        let white_minors = self.num_pieces(WHITE, KNIGHT) + self.num_pieces(WHITE, BISHOP);
+        let black_minors = self.num_pieces(BLACK, KNIGHT) + self.num_pieces(BLACK, BISHOP);
+
+        // K vs K, K+B vs K, K+N vs K
+        if white_minors + black_minors <= 1 {
+            return true;
+        }
+
+        // K+N+N vs K can't be forced
+        if (white_minors == 0 && self.num_pieces(BLACK, KNIGHT) == 2 && black_minors == 2) ||
+           (black_minors == 0 && self.num_pieces(WHITE, KNIGHT) == 2 && white_minors == 2) {
+            return true;
+        }
+
+        // Only bishops left, all on the same square color (e.g. K+B vs K+B with same-colored bishops)
+        let knights = self.bitboards.white_knights | self.bitboards.black_knights;
+        let bishops = self.bitboards.white_bishops | self.bitboards.black_bishops;
+        knights == 0 && ((bishops & crate::bitboard::LIGHT_SQUARES) == 0 || (bishops & crate::bitboard::DARK_SQUARES) == 0)
+    }
+
+    /// Drawn with best play although mate isn't ruled out by the material alone:
+    /// K+N+N vs K, and bishops with rook pawns against a bare king holding the corner
+    /// the bishops can't cover
+    pub fn is_theoretical_draw(&self) -> bool {
+        use crate::bitboard::{DARK_SQUARES, FILE_MASKS, LIGHT_SQUARES};
+
+        for (strong, weak) in [(WHITE, BLACK), (BLACK, WHITE)] {
+            if self.total_piece_count(weak) != 1 || self.num_pieces(strong, ROOK) > 0 || self.num_pieces(strong, QUEEN) > 0 {
+                continue;
+            }
+
+            let knights = self.num_pieces(strong, KNIGHT);
+            let bishops = self.bitboards.get_pieces(strong, BISHOP);
+            let pawns = self.bitboards.get_pieces(strong, PAWN);
+
+            // The knight pair can't force mate
+            if knights == 2 && bishops == 0 && pawns == 0 {
+                return true;
+            }
+
+            if knights > 0 || bishops == 0 || pawns == 0 {
+                continue;
+            }
+            let weak_king = self.bitboards.get_pieces(weak, KING);
+            let (promotion_rank, next_rank) = if strong == WHITE { (7, 6) } else { (0, 1) };
+            for (file, inner_file) in [(0, 1), (7, 6)] {
+                if pawns & !FILE_MASKS[file as usize] != 0 {
+                    continue;
+                }
+                // Wrong bishop: none of them stands on the promotion square's color
+                let promotion_square = 1u64 << Square::new(file, promotion_rank).0;
+                let promotion_color = if promotion_square & LIGHT_SQUARES != 0 { LIGHT_SQUARES } else { DARK_SQUARES };
+                if bishops & promotion_color != 0 {
+                    continue;
+                }
+                // The corner and its diagonal neighbour are out of the bishops' reach
+                let corner = promotion_square | 1u64 << Square::new(inner_file, next_rank).0;
+                if weak_king & corner != 0 {
+                    return true;
+                }
+            }
+        }
+
+        false
+    }
+
+    /// Drawn by the fifty-move rule, repetition, or material, whether insufficient or theoretical
+    pub fn is_drawn(&self) -> bool {
+        self.draw_reason().is_some()
+    }
+
+    fn draw_reason(&self) -> Option<DrawReason> {
+        if self.is_fifty_move_draw() {
+            Some(DrawReason::FiftyMove)
+        } else if self.is_repetition(2) {
+            Some(DrawReason::Repetition)
+        } else if self.is_insufficient_material() {
+            Some(DrawReason::InsufficientMaterial)
+        } else if self.is_theoretical_draw() {
+            Some(DrawReason::Theoretical)
+        } else {
+            None
+        }
+    }
+
+    /// True if the game is over for any reason
+    pub fn is_terminal(&self) -> bool {
+        self.terminal_status().is_some()
+    }
+
+    /// The final status if the game is over
+    pub fn terminal_status(&self) -> Option<GameStatus> {
+        match self.get_game_result() {
+            GameResult::InProgress => None,
+            GameResult::WhiteWins => Some(GameStatus::Checkmate(BLACK)),
+            GameResult::BlackWins => Some(GameStatus::Checkmate(WHITE)),
+            GameResult::Draw(DrawReason::Stalemate) => Some(GameStatus::Stalemate),
+            GameResult::Draw(reason) => Some(GameStatus::Draw(reason)),
+        }
+    }
+
+    /// The result of the game, or None while it is still in progress
+    pub fn game_result(&self) -> Option<GameResult> {
+        match self.get_game_result() {
+            GameResult::InProgress => None,
+            result => Some(result),
+        }
+    }
+
+    /// Checkmate, stalemate, any drawing rule, or `InProgress`
+    /// Conditions are checked cheapest first, legal move generation last
+    pub fn get_game_result(&self) -> GameResult {
+        if self.is_fifty_move_draw() {
+            // Checkmate on the hundredth half-move still counts as mate
+            return match self.result_without_moves() {
+                Some(GameResult::Draw(_)) | None => GameResult::Draw(DrawReason::FiftyMove),
+                Some(mate) => mate,
+            };
+        }
+
+        if self.is_repetition(2) {
+            return GameResult::Draw(DrawReason::Repetition);
+        }
+
+        if self.is_insufficient_material() {
+            return GameResult::Draw(DrawReason::InsufficientMaterial);
+        }
+
+        if self.is_theoretical_draw() {
+            return GameResult::Draw(DrawReason::Theoretical);
+        }
+
+        self.result_without_moves().unwrap_or(GameResult::InProgress)
+    }
+
+    /// Checkmate or stalemate when the side to move has no legal moves, from a single
+    /// move generation
+    fn result_without_moves(&self) -> Option<GameResult> {
+        if !self.get_all_legal_moves().is_empty() {
+            return None;
+        }
+
+        Some(if !self.is_in_check() {
+            GameResult::Draw(DrawReason::Stalemate)
+        } else if self.current_turn == WHITE {
+            GameResult::BlackWins
+        } else {
+            GameResult::WhiteWins
+        })
+    }
+
+    /// True if `color` is nearly out of moves and the opponent has a move that stalemates it
+    pub fn is_stalemate_trap(&self, color: u8) -> bool {
+        const MAX_TRAP_MOVES: usize = 3;
+
+        let as_color = if self.current_turn == color { self.clone() } else { self.with_flipped_turn() };
+        // Handing the move to the opponent of a side in check would let them take the king
+        if as_color.is_in_check() || as_color.get_all_legal_moves().len() > MAX_TRAP_MOVES {
+            return false;
+        }
+
+        let opponent_board = if self.current_turn == color { self.with_flipped_turn() } else { self.clone() };
+        for mv in opponent_board.get_all_legal_moves() {
+            let mut test_board = opponent_board.clone();
+            if test_board.try_make_move(mv).is_ok() && test_board.is_stalemate() {
+                return true;
+            }
+        }
+
+        false
+    }
+
+    /// Null-move pruning is only sound when passing can't be the best move:
+    /// not in check, and the side to move still has pieces (no zugzwang-prone pawn endings)
+    pub fn null_move_is_safe(&self) -> bool {
+        !self.is_in_check() && self.non_pawn_material(self.current_turn) > 0
+    }
+
+    /// Late move reduction eligibility: a quiet move searched late, at depth >= 3, not in check.
+    /// The first moves in the ordered list (hash move and killers) are never reduced
+    pub fn can_apply_lmr(&self, mv: Move, depth: u8, move_index: usize) -> bool {
+        const LMR_MIN_DEPTH: u8 = 3;
+        const LMR_FULL_DEPTH_MOVES: usize = 3;
+
+        let is_quiet = (is_empty(self.get_piece(mv.to)) || self.is_castling_move(mv).is_some()) &&
+            !mv.is_promotion() && !self.is_en_passant_move(mv);
+
+        is_quiet &&
+        depth >= LMR_MIN_DEPTH &&
+        move_index >= LMR_FULL_DEPTH_MOVES &&
+        !self.is_in_check()
     }
 
     /// Get the last move made
     pub fn get_last_move(&self) -> Option<&GameMove> {
         self.move_history.last()
     }
+
+    /// Return a copy of the board with the side to move flipped
+    /// Unlike a null move this leaves the clocks and history untouched; only
+    /// the en passant target is cleared since it belongs to the original side
+    pub fn with_flipped_turn(&self) -> Board {
+        let mut flipped = self.clone();
+        flipped.current_turn = opposite_color(self.current_turn);
+        flipped.en_passant_target = None;
+        flipped.en_passant_pawn = None;
+        flipped.position_hash = flipped.compute_hash();
+        flipped.update_game_status();
+        flipped
+    }
+
+    /// The same position with the colors swapped: rank 1 becomes rank 8, White's pieces become
+    /// Black's and the other side is to move. The game history is dropped
+    pub fn mirror_vertical(&self) -> Board {
+        let mirror = |square: Square| Square(square.0 ^ 56);
+
+        let mut mirrored = self.clone();
+        mirrored.move_history.clear();
+        mirrored.position_history.clear();
+        for index in 0..64 {
+            let piece = self.squares[index];
+            let swapped = if is_empty(piece) { EMPTY } else { piece ^ WHITE };
+            mirrored.set_piece(mirror(Square(index as u8)), swapped);
+        }
+
+        mirrored.current_turn = opposite_color(self.current_turn);
+        // White's rights sit in the low two bits, Black's in the next two
+        mirrored.castling_rights = ((self.castling_rights & (WHITE_KINGSIDE | WHITE_QUEENSIDE)) << 2) |
+            ((self.castling_rights & (BLACK_KINGSIDE | BLACK_QUEENSIDE)) >> 2);
+        let rook_files = self.castling_rook_files;
+        mirrored.castling_rook_files = [rook_files[2], rook_files[3], rook_files[0], rook_files[1]];
+        mirrored.en_passant_target = self.en_passant_target.map(mirror);
+        mirrored.en_passant_pawn = self.en_passant_pawn.map(mirror);
+        mirrored.position_hash = mirrored.compute_hash();
+        mirrored.update_game_status();
+        mirrored
+    }
 }
@@ -1,7 +1,7 @@
 use crate::types::*;
 use super::{Board};
 use std::collections::HashSet;
-use crate::bitboard::{get_knight_attacks, get_king_attacks, index_to_square};
+use crate::bitboard::{Bitboard, BITBOARD_EMPTY, get_knight_attacks, get_king_attacks, get_pawn_attacks, get_rook_attacks, get_bishop_attacks, index_to_square, iterate_bits};
 
 
 impl Board {
@@ -38,6 +38,103 @@ impl Board {
         true
     }
 
+    /// Legality check for a single move without generating the piece's move list,
+    /// e.g. for a hash move. Agrees with `get_all_legal_moves().contains(&mv)`
+    pub fn is_legal_move_fast(&self, mv: Move) -> bool {
+        let color = self.current_turn;
+        let piece = self.squares[mv.from.0 as usize];
+        let target = self.squares[mv.to.0 as usize];
+        if !is_piece_color(piece, color) || mv.from == mv.to {
+            return false;
+        }
+        if piece_type(piece) == KING && mv.promotion.is_none() {
+            if let Some(kingside) = self.is_castling_move(mv) {
+                // can_castle already covers the empty path and every square the king crosses
+                return self.can_castle(color, kingside);
+            }
+        }
+        if is_piece_color(target, color) {
+            return false;
+        }
+
+        // Promotion piece present exactly when a pawn reaches the last rank
+        let promotion_rank = if color == WHITE { 7 } else { 0 };
+        let reaches_last_rank = piece_type(piece) == PAWN && mv.to.rank() == promotion_rank;
+        match mv.promotion {
+            Some(KNIGHT | BISHOP | ROOK | QUEEN) if reaches_last_rank => {}
+            None if !reaches_last_rank => {}
+            _ => return false,
+        }
+
+        // (1) and (2): the piece reaches the target, sliders along a clear line
+        let occupancy = self.bitboards.all_pieces;
+        let to_bit = 1u64 << mv.to.0;
+        let mut captured_square = if is_empty(target) { None } else { Some(mv.to) };
+        let reachable = match piece_type(piece) {
+            PAWN => {
+                let forward: i8 = if color == WHITE { 1 } else { -1 };
+                let rank_step = mv.to.rank() as i8 - mv.from.rank() as i8;
+                let file_step = (mv.to.file() as i8 - mv.from.file() as i8).abs();
+                let start_rank = if color == WHITE { 1 } else { 6 };
+                let between = Square((mv.from.0 as i8 + 8 * forward) as u8);
+
+                if file_step == 0 && rank_step == forward {
+                    is_empty(target)
+                } else if file_step == 0 && rank_step == 2 * forward {
+                    mv.from.rank() == start_rank && is_empty(target) && is_empty(self.squares[between.0 as usize])
+                } else if file_step == 1 && rank_step == forward {
+                    if self.is_en_passant_move(mv) {
+                        captured_square = self.en_passant_pawn;
+                        true
+                    } else {
+                        !is_empty(target)
+                    }
+                } else {
+                    false
+                }
+            }
+            KNIGHT => get_knight_attacks(mv.from.0) & to_bit != 0,
+            BISHOP => get_bishop_attacks(mv.from.0, occupancy) & to_bit != 0,
+            ROOK => get_rook_attacks(mv.from.0, occupancy) & to_bit != 0,
+            QUEEN => (get_rook_attacks(mv.from.0, occupancy) | get_bishop_attacks(mv.from.0, occupancy)) & to_bit != 0,
+            KING => get_king_attacks(mv.from.0) & to_bit != 0,
+            _ => false,
+        };
+        if !reachable {
+            return false;
+        }
+
+        // (3): our king is not attacked once the move is on the board. The position after
+        // the move only differs in occupancy and the captured piece, so no board copy is needed
+        let king_square = if piece_type(piece) == KING {
+            mv.to
+        } else {
+            match self.find_king(color) {
+                Some(square) => square,
+                None => return false,
+            }
+        };
+
+        let mut removed = to_bit;
+        if let Some(square) = captured_square {
+            removed |= 1u64 << square.0;
+        }
+        let occupancy_after = (occupancy & !(1u64 << mv.from.0) & !removed) | to_bit;
+
+        let opponent = opposite_color(color);
+        let enemy = |kind: u8| self.bitboards.get_pieces(opponent, kind) & !removed;
+        let rook_attackers = enemy(ROOK) | enemy(QUEEN);
+        let bishop_attackers = enemy(BISHOP) | enemy(QUEEN);
+
+        let attacked = get_knight_attacks(king_square.0) & enemy(KNIGHT) != 0
+            || get_pawn_attacks(color, king_square.0) & enemy(PAWN) != 0
+            || get_king_attacks(king_square.0) & enemy(KING) != 0
+            || get_rook_attacks(king_square.0, occupancy_after) & rook_attackers != 0
+            || get_bishop_attacks(king_square.0, occupancy_after) & bishop_attackers != 0;
+
+        !attacked
+    }
+
     /// Check if a square is under threat by the specified color using ray tracing
     pub fn is_under_threat(&self, square: Square, by_color: u8) -> bool {
         
@@ -130,30 +227,12 @@ impl Board {
 
     /// Check for pawn threats
     fn check_pawn_threats(&self, square: Square, by_color: u8) -> bool {
-        let file = square.file() as i8;
-        let rank = square.rank() as i8;
-        
-        // Pawn attack direction (opposite of movement direction)
-        let attack_direction = if by_color == WHITE { -1 } else { 1 };
-        
-        // Check both diagonal attack squares
-        for df in [-1, 1] {
-            let pawn_file = file + df;
-            let pawn_rank = rank + attack_direction;
-            
-            if pawn_file >= 0 && pawn_file < 8 && pawn_rank >= 0 && pawn_rank < 8 {
-                let pawn_square = Square::new(pawn_file as u8, pawn_rank as u8);
-                let piece = self.get_piece(pawn_square);
-                
-                if !is_empty(piece) &&
-                   piece_color(piece) == by_color &&
-                   piece_type(piece) == PAWN {
-                    return true;
-                }
-            }
-        }
+        // A by_color pawn attacks this square exactly when a pawn of the other
+        // color standing here would attack that pawn's square
+        let attack_mask = get_pawn_attacks(opposite_color(by_color), square.0);
+        let attacking_pawns = self.bitboards.get_pieces(by_color, PAWN);
         
-        false
+        (attack_mask & attacking_pawns) != 0
     }
 
     /// Check for king threats (adjacent squares)
@@ -167,6 +246,150 @@ impl Board {
     }
     
 
+    /// Bitboard of every piece (of either color) that attacks the square
+    pub fn attacked_by_bb(&self, square: Square) -> Bitboard {
+        self.get_attackers_to(square, self.bitboards.all_pieces)
+    }
+
+    /// Pieces of both colors among `occupancy` that attack `square`. Passing an occupancy with
+    /// pieces removed uncovers the sliders behind them, as exchange evaluation needs
+    pub fn get_attackers_to(&self, square: Square, occupancy: Bitboard) -> Bitboard {
+        let pieces = |kind: u8| self.bitboards.get_pieces(WHITE, kind) | self.bitboards.get_pieces(BLACK, kind);
+        let rook_sliders = pieces(ROOK) | pieces(QUEEN);
+        let bishop_sliders = pieces(BISHOP) | pieces(QUEEN);
+
+        let attackers = (get_knight_attacks(square.0) & pieces(KNIGHT))
+            | (get_king_attacks(square.0) & pieces(KING))
+            // A pawn attacks the square from where an opposing pawn on it would attack
+            | (get_pawn_attacks(BLACK, square.0) & self.bitboards.get_pieces(WHITE, PAWN))
+            | (get_pawn_attacks(WHITE, square.0) & self.bitboards.get_pieces(BLACK, PAWN))
+            | (get_rook_attacks(square.0, occupancy) & rook_sliders)
+            | (get_bishop_attacks(square.0, occupancy) & bishop_sliders);
+
+        attackers & occupancy
+    }
+
+    /// Squares of the `color` pieces that attack `square`, x-rays not included
+    pub fn attackers_bitboard(&self, square: Square, color: u8) -> Bitboard {
+        self.get_attackers_to(square, self.bitboards.all_pieces) & self.bitboards.get_all_pieces(color)
+    }
+
+    /// How many `color` pieces attack `square`; nonzero exactly when `is_under_threat` holds
+    pub fn count_attackers(&self, square: Square, color: u8) -> u32 {
+        self.attackers_bitboard(square, color).count_ones()
+    }
+
+    /// Bitboard of all squares attacked by the given color
+    pub fn get_all_attacks(&self, color: u8) -> Bitboard {
+        self.attacked_squares(color)
+    }
+
+    /// Bitboard of every square attacked by `color`, built from each piece's attack mask
+    pub fn attacked_squares(&self, color: u8) -> Bitboard {
+        let occupancy = self.bitboards.all_pieces;
+        let mut attacks = BITBOARD_EMPTY;
+
+        for square in iterate_bits(self.bitboards.get_pieces(color, PAWN)) {
+            attacks |= get_pawn_attacks(color, square);
+        }
+        for square in iterate_bits(self.bitboards.get_pieces(color, KNIGHT)) {
+            attacks |= get_knight_attacks(square);
+        }
+        for square in iterate_bits(self.bitboards.get_pieces(color, KING)) {
+            attacks |= get_king_attacks(square);
+        }
+
+        let rook_sliders = self.bitboards.get_pieces(color, ROOK) | self.bitboards.get_pieces(color, QUEEN);
+        let bishop_sliders = self.bitboards.get_pieces(color, BISHOP) | self.bitboards.get_pieces(color, QUEEN);
+        for square in iterate_bits(rook_sliders) {
+            attacks |= get_rook_attacks(square, occupancy);
+        }
+        for square in iterate_bits(bishop_sliders) {
+            attacks |= get_bishop_attacks(square, occupancy);
+        }
+
+        attacks
+    }
+
+    /// Check if the current player is in check
+    pub fn is_in_check(&self) -> bool {
+        match self.find_king(self.current_turn) {
+            Some(king_square) => self.is_under_threat(king_square, opposite_color(self.current_turn)),
+            None => false,
+        }
+    }
+
+    /// Side to move is checkmated, answered from the cached game status
+    pub fn is_in_checkmate(&self) -> bool {
+        matches!(self.game_status, GameStatus::Checkmate(_))
+    }
+
+    /// Side to move is stalemated, answered from the cached game status
+    pub fn is_in_stalemate(&self) -> bool {
+        self.game_status == GameStatus::Stalemate
+    }
+
+    /// Check that the board's state agrees with itself, to catch move generation bugs where
+    /// they happen instead of as a wrong perft count later
+    pub fn validate(&self) -> Result<(), BoardError> {
+        if !self.check_consistency_with_bitboards() {
+            return Err(BoardError::BitboardMismatch);
+        }
+
+        for color in [WHITE, BLACK] {
+            let count = self.count_pieces(color, KING);
+            if count != 1 {
+                return Err(BoardError::KingCount { color, count });
+            }
+        }
+
+        // Each right needs its king and rook still on their starting squares
+        for index in 0..4 {
+            let right = 1u8 << index;
+            if !has_castling_right(self.castling_rights, right) {
+                continue;
+            }
+            let (color, back_rank) = if index < 2 { (WHITE, 0) } else { (BLACK, 7) };
+            let rook_file = self.castling_rook_files[index];
+            let king_in_place = match self.find_king(color) {
+                Some(king) if king.rank() == back_rank => {
+                    let kingside = right & (WHITE_KINGSIDE | BLACK_KINGSIDE) != 0;
+                    if self.chess960 { (rook_file > king.file()) == kingside } else { king.file() == 4 }
+                }
+                _ => false,
+            };
+            if !king_in_place || self.get_piece(Square::new(rook_file, back_rank)) != make_piece(ROOK, color) {
+                return Err(BoardError::CastlingRight { right });
+            }
+        }
+
+        // The target is behind a pawn of the side that just moved
+        match (self.en_passant_target, self.en_passant_pawn) {
+            (None, None) => {}
+            (Some(target), Some(pawn)) => {
+                let (pawn_color, pawn_rank) = match target.rank() {
+                    2 => (WHITE, 3),
+                    5 => (BLACK, 4),
+                    _ => return Err(BoardError::EnPassant),
+                };
+                if pawn != Square::new(target.file(), pawn_rank) ||
+                    self.get_piece(pawn) != make_piece(PAWN, pawn_color) ||
+                    self.current_turn == pawn_color {
+                    return Err(BoardError::EnPassant);
+                }
+            }
+            _ => return Err(BoardError::EnPassant),
+        }
+
+        let back_ranks = 0xFF00_0000_0000_00FFu64;
+        let pawns = self.bitboards.get_pieces(WHITE, PAWN) | self.bitboards.get_pieces(BLACK, PAWN);
+        if let Some(square) = iterate_bits(pawns & back_ranks).next() {
+            return Err(BoardError::PawnOnBackRank { square: Square(square) });
+        }
+
+        Ok(())
+    }
+
     /// Find the king of the specified color
     pub fn find_king(&self, color: u8) -> Option<Square> {
         let king_pieces = self.bitboards.find_pieces(color, KING);
@@ -383,9 +606,11 @@ impl Board {
     }
 
     /// Filter king moves when in check
+    /// A target holding one of our own pieces is a Chess960 castling move, already vetted by `can_castle`
     pub fn filter_king_moves_in_check(&self, moves: Vec<Square>, opponent_color: u8) -> Vec<Square> {
+        let our_pieces = self.bitboards.get_all_pieces(opposite_color(opponent_color));
         moves.into_iter()
-            .filter(|&square| !self.is_under_threat(square, opponent_color))
+            .filter(|&square| our_pieces & (1u64 << square.0) != 0 || !self.is_under_threat(square, opponent_color))
             .collect()
     }
 
@@ -461,6 +686,56 @@ impl Board {
         file_diff <= 1 && rank_diff <= 1 && (file_diff != 0 || rank_diff != 0)
     }
 
+    /// Bitboard of `color`'s pieces pinned to their king, found with one ray per direction from the king
+    pub fn pinned_pieces(&self, color: u8) -> Bitboard {
+        let king_square = match self.find_king(color) {
+            Some(square) => square,
+            None => return BITBOARD_EMPTY,
+        };
+
+        let directions = [
+            (0, 1), (0, -1), (1, 0), (-1, 0), // Rook directions
+            (1, 1), (1, -1), (-1, 1), (-1, -1) // Bishop directions
+        ];
+
+        let mut pinned = BITBOARD_EMPTY;
+        for (i, &(df, dr)) in directions.iter().enumerate() {
+            let mut file = king_square.file() as i8 + df;
+            let mut rank = king_square.rank() as i8 + dr;
+            let mut friendly_square = None;
+
+            while (0..8).contains(&file) && (0..8).contains(&rank) {
+                let square = Square::new(file as u8, rank as u8);
+                let piece = self.squares[square.0 as usize];
+
+                if !is_empty(piece) {
+                    if piece_color(piece) == color {
+                        if friendly_square.is_some() {
+                            break; // Two of our pieces shield the king
+                        }
+                        friendly_square = Some(square);
+                    } else {
+                        let slides_this_way = match piece_type(piece) {
+                            QUEEN => true,
+                            ROOK => i < 4,
+                            BISHOP => i >= 4,
+                            _ => false,
+                        };
+                        if let (true, Some(friendly)) = (slides_this_way, friendly_square) {
+                            pinned |= 1u64 << friendly.0;
+                        }
+                        break;
+                    }
+                }
+
+                file += df;
+                rank += dr;
+            }
+        }
+
+        pinned
+    }
+
     /// Check if a piece at the given square is pinned
     pub fn is_piece_pinned(&self, square: Square) -> Option<(i8, i8)> {
         let piece = self.get_piece(square);
@@ -3,6 +3,7 @@ pub mod types;
 pub mod perft; 
 pub mod bitboard;  
 pub mod logger;
+pub mod pst;
 
 pub use board::*;
 pub use types::*;
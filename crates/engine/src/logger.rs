@@ -87,6 +87,20 @@ impl ChessLogger {
         ));
     }
 
+    /// Move by one of two engines playing each other, tagged with its color
+    pub fn log_engine_move(&mut self, color: u8, mv: Move, time_ms: u64, eval: i32) {
+        self.move_count += 1;
+        self.log(&format!(
+            "{}. {} ({} engine - {}ms) Eval: {} {}",
+            self.move_count,
+            move_to_string(mv),
+            if color == WHITE { "White" } else { "Black" },
+            time_ms,
+            eval,
+            if eval > 0 { "📈" } else { "📉" }
+        ));
+    }
+
     pub fn log_undo(&mut self, mv: Move) {
         self.log(&format!("↩️ UNDO: {}", move_to_string(mv)));
     }
@@ -1,4 +1,4 @@
-use crate::{Board, Move, Square, square_to_algebraic};
+use crate::{Board, Move, Square};
 use std::time::Instant;
 
 #[derive(Debug, Clone)]
@@ -40,6 +40,35 @@ pub struct PerftTestCase {
     pub name: &'static str,
     pub fen: &'static str,
     pub expected_results: &'static [(u32, u64)], // (depth, expected_nodes)
+    pub expected_details: &'static [PerftDetails], // Leaf move types, where a reference is known
+    pub expected_divide: &'static [(&'static str, u64)], // Nodes under each root move at DIVIDE_DEPTH, by UCI move
+}
+
+/// Depth of the stored `expected_divide` counts
+pub const DIVIDE_DEPTH: u32 = 4;
+
+/// Reference breakdown of the leaf moves at one depth, as reported by `perft_detailed`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PerftDetails {
+    pub depth: u32,
+    pub captures: u64, // En passant included
+    pub en_passant: u64,
+    pub castles: u64,
+    pub promotions: u64,
+    pub checks: u64,
+    pub checkmates: u64,
+}
+
+impl PerftDetails {
+    /// Compare against a `perft_detailed` result at the same depth
+    pub fn matches(&self, result: &PerftResult) -> bool {
+        self.captures == result.captures &&
+        self.en_passant == result.en_passant &&
+        self.castles == result.castles &&
+        self.promotions == result.promotions &&
+        self.checks == result.checks &&
+        self.checkmates == result.checkmates
+    }
 }
 
 // Standard perft test positions
@@ -55,6 +84,18 @@ pub const PERFT_POSITIONS: &[PerftTestCase] = &[
             (5, 4_865_609),
             (6, 119_060_324),
         ],
+        expected_details: &[
+            PerftDetails { depth: 1, captures: 0, en_passant: 0, castles: 0, promotions: 0, checks: 0, checkmates: 0 },
+            PerftDetails { depth: 2, captures: 0, en_passant: 0, castles: 0, promotions: 0, checks: 0, checkmates: 0 },
+            PerftDetails { depth: 3, captures: 34, en_passant: 0, castles: 0, promotions: 0, checks: 12, checkmates: 0 },
+            PerftDetails { depth: 4, captures: 1_576, en_passant: 0, castles: 0, promotions: 0, checks: 469, checkmates: 8 },
+        ],
+        expected_divide: &[
+            ("a2a3", 8_457), ("a2a4", 9_329), ("b1a3", 8_885), ("b1c3", 9_755), ("b2b3", 9_345), ("b2b4", 9_332),
+            ("c2c3", 9_272), ("c2c4", 9_744), ("d2d3", 11_959), ("d2d4", 12_435), ("e2e3", 13_134), ("e2e4", 13_160),
+            ("f2f3", 8_457), ("f2f4", 8_929), ("g1f3", 9_748), ("g1h3", 8_881), ("g2g3", 9_345), ("g2g4", 9_328),
+            ("h2h3", 8_457), ("h2h4", 9_329),
+        ],
     },
     PerftTestCase {
         name: "Kiwipete",
@@ -66,6 +107,21 @@ pub const PERFT_POSITIONS: &[PerftTestCase] = &[
             (4, 4_085_603),
             (5, 193_690_690),
         ],
+        expected_details: &[
+            PerftDetails { depth: 1, captures: 8, en_passant: 0, castles: 2, promotions: 0, checks: 0, checkmates: 0 },
+            PerftDetails { depth: 2, captures: 351, en_passant: 1, castles: 91, promotions: 0, checks: 3, checkmates: 0 },
+            PerftDetails { depth: 3, captures: 17_102, en_passant: 45, castles: 3_162, promotions: 0, checks: 993, checkmates: 1 },
+        ],
+        expected_divide: &[
+            ("a1b1", 83_348), ("a1c1", 83_263), ("a1d1", 79_695), ("a2a3", 94_405), ("a2a4", 90_978), ("b2b3", 81_066),
+            ("c3a4", 91_447), ("c3b1", 84_773), ("c3b5", 81_498), ("c3d1", 84_782), ("d2c1", 83_037), ("d2e3", 90_274),
+            ("d2f4", 84_869), ("d2g5", 87_951), ("d2h6", 82_323), ("d5d6", 79_551), ("d5e6", 97_464), ("e1c1", 79_803),
+            ("e1d1", 79_989), ("e1f1", 77_887), ("e1g1", 86_975), ("e2a6", 69_334), ("e2b5", 79_739), ("e2c4", 84_835),
+            ("e2d1", 74_963), ("e2d3", 85_119), ("e2f1", 88_728), ("e5c4", 77_752), ("e5c6", 83_885), ("e5d3", 77_431),
+            ("e5d7", 93_913), ("e5f7", 88_799), ("e5g4", 79_912), ("e5g6", 83_866), ("f3d3", 83_727), ("f3e3", 92_505),
+            ("f3f4", 90_488), ("f3f5", 104_992), ("f3f6", 77_838), ("f3g3", 94_461), ("f3g4", 92_037), ("f3h3", 98_524),
+            ("f3h5", 95_034), ("g2g3", 77_468), ("g2g4", 75_677), ("g2h3", 82_759), ("h1f1", 81_563), ("h1g1", 84_876),
+        ],
     },
     PerftTestCase {
         name: "Position 3",
@@ -78,6 +134,17 @@ pub const PERFT_POSITIONS: &[PerftTestCase] = &[
             (5, 674_624),
             (6, 11_030_083),
         ],
+        expected_details: &[
+            PerftDetails { depth: 1, captures: 1, en_passant: 0, castles: 0, promotions: 0, checks: 2, checkmates: 0 },
+            PerftDetails { depth: 2, captures: 14, en_passant: 0, castles: 0, promotions: 0, checks: 10, checkmates: 0 },
+            PerftDetails { depth: 3, captures: 209, en_passant: 2, castles: 0, promotions: 0, checks: 267, checkmates: 0 },
+            PerftDetails { depth: 4, captures: 3_348, en_passant: 123, castles: 0, promotions: 0, checks: 1_680, checkmates: 17 },
+        ],
+        expected_divide: &[
+            ("a5a4", 3_394), ("a5a6", 3_653), ("b4a4", 3_019), ("b4b1", 4_199), ("b4b2", 3_328), ("b4b3", 3_658),
+            ("b4c4", 3_797), ("b4d4", 3_622), ("b4e4", 3_391), ("b4f4", 606), ("e2e3", 3_107), ("e2e4", 2_748),
+            ("g2g3", 1_014), ("g2g4", 3_702),
+        ],
     },
     PerftTestCase {
         name: "Position 4",
@@ -89,6 +156,14 @@ pub const PERFT_POSITIONS: &[PerftTestCase] = &[
             (4, 422_333),
             (5, 15_833_292),
         ],
+        expected_details: &[
+            PerftDetails { depth: 1, captures: 0, en_passant: 0, castles: 0, promotions: 0, checks: 0, checkmates: 0 },
+            PerftDetails { depth: 2, captures: 87, en_passant: 0, castles: 6, promotions: 48, checks: 10, checkmates: 0 },
+            PerftDetails { depth: 3, captures: 1_021, en_passant: 4, castles: 0, promotions: 120, checks: 38, checkmates: 22 },
+        ],
+        expected_divide: &[
+            ("b4c5", 58_167), ("c4c5", 60_769), ("d2d4", 72_051), ("f1f2", 73_972), ("f3d4", 75_736), ("g1h1", 81_638),
+        ],
     },
     PerftTestCase {
         name: "Position 5",
@@ -100,6 +175,17 @@ pub const PERFT_POSITIONS: &[PerftTestCase] = &[
             (4, 2_103_487),
             (5, 89_941_194),
         ],
+        expected_details: &[],
+        expected_divide: &[
+            ("a2a3", 46_833), ("a2a4", 48_882), ("b1a3", 44_378), ("b1c3", 50_303), ("b1d2", 40_560), ("b2b3", 46_497),
+            ("b2b4", 46_696), ("c1d2", 46_881), ("c1e3", 53_637), ("c1f4", 52_350), ("c1g5", 45_601), ("c1h6", 40_913),
+            ("c2c3", 49_406), ("c4a6", 41_884), ("c4b3", 43_453), ("c4b5", 45_559), ("c4d3", 43_565), ("c4d5", 48_002),
+            ("c4e6", 49_872), ("c4f7", 43_289), ("d1d2", 48_843), ("d1d3", 57_153), ("d1d4", 57_744), ("d1d5", 56_899),
+            ("d1d6", 43_766), ("d7c8b", 65_053), ("d7c8n", 62_009), ("d7c8q", 44_226), ("d7c8r", 38_077), ("e1d2", 33_423),
+            ("e1f1", 49_775), ("e1f2", 36_783), ("e1g1", 47_054), ("e2c3", 54_792), ("e2d4", 52_109), ("e2f4", 51_127),
+            ("e2g1", 48_844), ("e2g3", 51_892), ("g2g3", 44_509), ("g2g4", 45_506), ("h1f1", 46_101), ("h1g1", 44_668),
+            ("h2h3", 46_762), ("h2h4", 47_811),
+        ],
     },
     PerftTestCase {
         name: "Position 6",
@@ -111,6 +197,17 @@ pub const PERFT_POSITIONS: &[PerftTestCase] = &[
             (4, 3_894_594),
             (5, 164_075_551),
         ],
+        expected_details: &[],
+        expected_divide: &[
+            ("a1a2", 80_000), ("a1b1", 83_917), ("a1c1", 81_943), ("a1d1", 79_961), ("a1e1", 76_017), ("a3a4", 89_719),
+            ("b2b3", 83_959), ("b2b4", 85_907), ("c3a2", 81_990), ("c3a4", 83_785), ("c3b1", 74_196), ("c3b5", 86_102),
+            ("c3d1", 72_167), ("c3d5", 84_310), ("c4a2", 83_990), ("c4a6", 87_691), ("c4b3", 84_078), ("c4b5", 84_686),
+            ("c4d5", 82_111), ("c4e6", 88_890), ("c4f7", 8_203), ("d3d4", 93_464), ("e2d1", 82_176), ("e2d2", 89_961),
+            ("e2e1", 84_117), ("e2e3", 90_128), ("f1b1", 83_966), ("f1c1", 85_946), ("f1d1", 87_918), ("f1e1", 87_928),
+            ("f3d2", 87_558), ("f3d4", 98_744), ("f3e1", 77_246), ("f3e5", 111_761), ("f3h4", 89_355), ("g1h1", 95_870),
+            ("g2g3", 89_798), ("g5c1", 81_175), ("g5d2", 89_433), ("g5e3", 91_743), ("g5f4", 99_547), ("g5f6", 79_097),
+            ("g5h4", 83_607), ("g5h6", 89_238), ("h2h3", 93_404), ("h2h4", 87_792),
+        ],
     },
 ];
 
@@ -134,8 +231,160 @@ pub fn perft(board: &mut Board, depth: u32) -> u64 {
     nodes
 }
 
+/// Perft with bulk counting: at depth 1 the legal move count is the node count, so
+/// the last ply is never made. Only node totals are available; use `perft_detailed` for move types
+pub fn perft_bulk(board: &mut Board, depth: u32) -> u64 {
+    if depth == 0 {
+        return 1;
+    }
+    
+    let moves = board.get_all_legal_moves();
+    if depth == 1 {
+        return moves.len() as u64;
+    }
+    
+    let mut nodes = 0;
+    for mv in moves {
+        let mut test_board = board.clone();
+        if test_board.try_make_move(mv).is_ok() {
+            nodes += perft_bulk(&mut test_board, depth - 1);
+        }
+    }
+    
+    nodes
+}
+
+/// Captures among the leaf moves, counted with `get_all_captures` at the last ply. Should equal
+/// `perft_detailed(board, depth).captures`
+pub fn perft_captures(board: &mut Board, depth: u32) -> u64 {
+    if depth == 0 {
+        return 0;
+    }
+    if depth == 1 {
+        return board.get_all_captures().len() as u64;
+    }
+
+    let mut captures = 0;
+    for mv in board.get_all_legal_moves() {
+        let mut test_board = board.clone();
+        if test_board.try_make_move(mv).is_ok() {
+            captures += perft_captures(&mut test_board, depth - 1);
+        }
+    }
+
+    captures
+}
+
+/// Perft-only transposition table of (position key, depth, node count), always-replace
+pub struct PerftTable {
+    entries: Vec<PerftEntry>,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct PerftEntry {
+    hash: u64,
+    depth: u32,
+    count: u64,
+}
+
+impl PerftTable {
+    pub fn new(size_mb: usize) -> Self {
+        let entry_count = (size_mb * 1024 * 1024 / std::mem::size_of::<PerftEntry>()).max(1);
+        Self {
+            entries: vec![PerftEntry::default(); entry_count],
+        }
+    }
+    
+    fn index(&self, hash: u64) -> usize {
+        (hash % self.entries.len() as u64) as usize
+    }
+    
+    fn probe(&self, hash: u64, depth: u32) -> Option<u64> {
+        let entry = &self.entries[self.index(hash)];
+        if entry.hash == hash && entry.depth == depth {
+            Some(entry.count)
+        } else {
+            None
+        }
+    }
+    
+    fn store(&mut self, hash: u64, depth: u32, count: u64) {
+        let index = self.index(hash);
+        self.entries[index] = PerftEntry { hash, depth, count };
+    }
+}
+
+/// Perft that reuses counts for positions reached by different move orders
+pub fn perft_with_table(board: &mut Board, depth: u32, table: &mut PerftTable) -> u64 {
+    // Shallow counts are cheaper to recompute than to look up
+    if depth <= 1 {
+        return perft_bulk(board, depth);
+    }
+    
+    let hash = board.position_key();
+    if let Some(count) = table.probe(hash, depth) {
+        return count;
+    }
+    
+    let mut nodes = 0;
+    for mv in board.get_all_legal_moves() {
+        let mut test_board = board.clone();
+        if test_board.try_make_move(mv).is_ok() {
+            nodes += perft_with_table(&mut test_board, depth - 1, table);
+        }
+    }
+    
+    table.store(hash, depth, nodes);
+    nodes
+}
+
+/// `perft_with_table` with a fresh 64 MB table
+pub fn perft_hashed(board: &mut Board, depth: u32) -> u64 {
+    let mut table = PerftTable::new(64);
+    perft_with_table(board, depth, &mut table)
+}
+
+/// Perft variant that XORs the position keys of all leaf nodes instead of counting them
+pub fn perft_signature(board: &mut Board, depth: u32) -> u64 {
+    if depth == 0 {
+        return board.position_key();
+    }
+    
+    let mut signature = 0;
+    let moves = board.get_all_legal_moves();
+    
+    for mv in moves {
+        let mut test_board = board.clone();
+        if test_board.try_make_move(mv).is_ok() {
+            signature ^= perft_signature(&mut test_board, depth - 1);
+        }
+    }
+    
+    signature
+}
 
-/// Detailed perft that tracks different move types
+/// Perft that calls `progress(completed_root_moves, total_root_moves)` after each root move
+pub fn perft_with_progress<F: Fn(u64, u64)>(board: &mut Board, depth: u32, progress: F) -> u64 {
+    if depth == 0 {
+        return 1;
+    }
+    
+    let moves = board.get_all_legal_moves();
+    let total = moves.len() as u64;
+    let mut nodes = 0;
+    
+    for (completed, mv) in moves.into_iter().enumerate() {
+        let mut test_board = board.clone();
+        if test_board.try_make_move(mv).is_ok() {
+            nodes += perft(&mut test_board, depth - 1);
+        }
+        progress(completed as u64 + 1, total);
+    }
+    
+    nodes
+}
+
+/// Detailed perft that tracks different move types of the leaf moves
 pub fn perft_detailed(board: &mut Board, depth: u32) -> PerftResult {
     let start_time = Instant::now();
     let mut result = PerftResult::new();
@@ -149,14 +398,12 @@ pub fn perft_detailed(board: &mut Board, depth: u32) -> PerftResult {
     let moves = board.get_all_legal_moves();
     
     for mv in moves {
-        if let Ok(game_move) = board.try_make_move(mv) {
-            let sub_result = perft_detailed(board, depth - 1);
-            
-            result.nodes += sub_result.nodes;
-            
+        let mut test_board = board.clone();
+        if let Ok(game_move) = test_board.try_make_move(mv) {
             // Count move types at depth 1
             if depth == 1 {
-                if game_move.captured_piece != 0 {
+                result.nodes += 1;
+                if game_move.captured_piece != 0 || game_move.is_en_passant {
                     result.captures += 1;
                 }
                 if game_move.is_en_passant {
@@ -165,9 +412,19 @@ pub fn perft_detailed(board: &mut Board, depth: u32) -> PerftResult {
                 if game_move.is_castling {
                     result.castles += 1;
                 }
-                // TODO: Add promotion counting when implemented
-                // TODO: Add check/checkmate counting when implemented
+                if game_move.mv.promotion.is_some() {
+                    result.promotions += 1;
+                }
+                // It's the opponent's turn now, so this asks whether their king is attacked
+                if test_board.is_in_check() {
+                    result.checks += 1;
+                    if test_board.get_all_legal_moves().is_empty() {
+                        result.checkmates += 1;
+                    }
+                }
             } else {
+                let sub_result = perft_detailed(&mut test_board, depth - 1);
+                result.nodes += sub_result.nodes;
                 result.captures += sub_result.captures;
                 result.en_passant += sub_result.en_passant;
                 result.castles += sub_result.castles;
@@ -175,8 +432,6 @@ pub fn perft_detailed(board: &mut Board, depth: u32) -> PerftResult {
                 result.checks += sub_result.checks;
                 result.checkmates += sub_result.checkmates;
             }
-            
-            board.undo_move().expect("Failed to undo move");
         }
     }
     
@@ -184,7 +439,7 @@ pub fn perft_detailed(board: &mut Board, depth: u32) -> PerftResult {
     result
 }
 
-/// Divide perft - shows per-move breakdown for debugging
+/// Divide perft - shows per-move breakdown for debugging, sorted by UCI move string like Stockfish
 pub fn perft_divide(board: &mut Board, depth: u32) -> Vec<(Move, u64)> {
     let mut results = Vec::new();
     let moves = board.get_all_legal_moves();
@@ -201,34 +456,82 @@ pub fn perft_divide(board: &mut Board, depth: u32) -> Vec<(Move, u64)> {
         }
     }
     
-    results.sort_by(|a, b| b.1.cmp(&a.1)); // Sort by node count descending
+    // UCI strings carry the promotion piece, so the four promotions stay apart
+    results.sort_by_cached_key(|(mv, _)| mv.to_string());
     results
 }
 
-/// Run a single perft test
-pub fn run_perft_test(board: &mut Board, depth: u32, expected: u64) -> bool {
+impl Board {
+    /// Leaf node count at `depth`, see `perft`
+    pub fn perft(&mut self, depth: u32) -> u64 {
+        perft(self, depth)
+    }
+
+    /// Node count under each legal move, see `perft_divide`
+    pub fn perft_divide(&self, depth: u32) -> Vec<(Move, u64)> {
+        perft_divide(&mut self.clone(), depth)
+    }
+}
+
+/// Every position of a random game from `start`, `start` included, for tests that want many
+/// realistic positions. The same nonzero `seed` always plays the same game; it stops early
+/// after `plies` moves or once the game is over
+pub fn random_game_positions(start: &Board, seed: u64, plies: usize) -> Vec<Board> {
+    let mut state = seed;
+    let mut board = start.clone();
+    let mut positions = vec![board.clone()];
+    for _ in 0..plies {
+        let moves = board.get_all_legal_moves();
+        if moves.is_empty() || board.is_terminal() {
+            break;
+        }
+        let mv = moves[(crate::bitboard::next_random(&mut state) % moves.len() as u64) as usize];
+        board.try_make_move(mv).expect("Generated move is legal");
+        positions.push(board.clone());
+    }
+    positions
+}
+
+/// Run a single perft test, optionally with bulk counting at the leaves. Without bulk counting
+/// the leaf move types, checks and checkmates are also compared against `details` when given
+pub fn run_perft_test(board: &mut Board, depth: u32, expected: u64, details: Option<&PerftDetails>, bulk: bool) -> bool {
     println!("Running perft depth {} (expected: {})", depth, expected);
     
     let start_time = Instant::now();
-    let nodes = perft(board, depth);
+    let detailed = if bulk { None } else { Some(perft_detailed(board, depth)) };
+    let nodes = match &detailed {
+        Some(result) => result.nodes,
+        None => perft_bulk(board, depth),
+    };
     let elapsed = start_time.elapsed();
     
-    let success = nodes == expected;
+    let details_match = match (details, &detailed) {
+        (Some(details), Some(result)) => details.matches(result),
+        _ => true,
+    };
+    let success = nodes == expected && details_match;
     let status = if success { "✅ PASS" } else { "❌ FAIL" };
     
     println!("{} - Depth {}: {} nodes in {:.3}s ({:.0} nodes/sec)", 
              status, depth, nodes, elapsed.as_secs_f64(), 
              nodes as f64 / elapsed.as_secs_f64());
+    if let Some(result) = &detailed {
+        println!("  captures {}, e.p. {}, castles {}, promotions {}, checks {}, checkmates {}",
+                 result.captures, result.en_passant, result.castles, result.promotions, result.checks, result.checkmates);
+    }
     
-    if !success {
+    if nodes != expected {
         println!("Expected: {}, Got: {}", expected, nodes);
     }
+    if !details_match {
+        println!("Expected details: {:?}", details.unwrap());
+    }
     
     success
 }
 
 /// Run all perft tests for a position
-pub fn run_position_tests(test_case: &PerftTestCase, max_depth: Option<u32>) -> bool {
+pub fn run_position_tests(test_case: &PerftTestCase, max_depth: Option<u32>, bulk: bool) -> bool {
     println!("\n🏁 Testing: {}", test_case.name);
     println!("FEN: {}", test_case.fen);
     
@@ -242,7 +545,8 @@ pub fn run_position_tests(test_case: &PerftTestCase, max_depth: Option<u32>) ->
             }
         }
         
-        let passed = run_perft_test(&mut board, depth, expected);
+        let details = test_case.expected_details.iter().find(|details| details.depth == depth);
+        let passed = run_perft_test(&mut board, depth, expected, details, bulk);
         all_passed &= passed;
         
     }
@@ -250,30 +554,6 @@ pub fn run_position_tests(test_case: &PerftTestCase, max_depth: Option<u32>) ->
     all_passed
 }
 
-/// Run all standard perft tests
-pub fn run_all_tests(max_depth: Option<u32>) {
-    println!("🚀 Starting Perft Tests");
-    println!("Max depth: {:?}", max_depth.unwrap_or(99));
-    
-    let mut passed = 0;
-    let mut total = 0;
-    
-    for test_case in PERFT_POSITIONS {
-        total += 1;
-        if run_position_tests(test_case, max_depth) {
-            passed += 1;
-        }
-    }
-    
-    println!("\n📊 Results: {}/{} positions passed", passed, total);
-    
-    if passed == total {
-        println!("🎉 All tests passed! Your move generation is correct.");
-    } else {
-        println!("❌ Some tests failed. Check move generation implementation.");
-    }
-}
-
 /// Debug perft differences
 pub fn debug_perft_starting_position() {
     let board = Board::new();
@@ -314,10 +594,10 @@ pub fn debug_starting_position_issues() {
     println!("\n📍 White Pawn Moves:");
     let pawn_moves = board.debug_pawn_moves();
     for (square, moves) in pawn_moves {
-        let square_name = square_to_algebraic(square);
+        let square_name = square.to_string();
         println!("  {}: {} moves", square_name, moves.len());
         for mv in moves {
-            println!("    -> {}", square_to_algebraic(mv));
+            println!("    -> {}", mv);
         }
     }
     
@@ -636,8 +916,8 @@ pub fn test_promotion_undo_cycles() {
     for mv in moves {
         if mv.is_promotion() {
             println!("Testing promotion move: {}{} -> {:?}", 
-                     square_to_algebraic(mv.from), 
-                     square_to_algebraic(mv.to), 
+                     mv.from, 
+                     mv.to, 
                      mv.promotion);
             
             // Make the move
@@ -0,0 +1,174 @@
+use crate::types::*;
+use crate::Board;
+
+// Phase weights per piece type, indexed by piece type
+pub const PHASE_WEIGHTS: [i32; 7] = [0, 0, 1, 1, 2, 4, 0];
+pub const MAX_PHASE: i32 = 24;
+
+// Opening PST - Scaled to 5-15% of piece material values
+pub const OPENING_PAWN_PST: [i32; 64] = [
+     0,  0,  0,  0,  0,  0,  0,  0,
+    12, 12, 12, 12, 12, 12, 12, 12,  // Reduced from 50 to 12 (12% of 100)
+     2,  2,  4,  6,  6,  4,  2,  2,  // Reduced from 10-30 to 2-6
+     1,  1,  2,  5,  5,  2,  1,  1,  // Reduced from 5-25 to 1-5
+     0,  0,  0,  4,  4,  0,  0,  0,  // Reduced from 20 to 4
+     1, -1, -2,  0,  0, -2, -1,  1,
+     1,  2,  2, -4, -4,  2,  2,  1,  // Reduced penalty from -20 to -4
+     0,  0,  0,  0,  0,  0,  0,  0,
+];
+
+pub const OPENING_KNIGHT_PST: [i32; 64] = [
+   -12,-10, -6, -6, -6, -6,-10,-12,  // Reduced from -50 to -12 (4% of 320)
+   -10, -4,  0,  0,  0,  0, -4,-10,  // Reduced from -20 to -4
+    -6,  0,  2,  4,  4,  2,  0, -6,  // Reduced from 10-15 to 2-4
+    -6,  1,  4,  5,  5,  4,  1, -6,  // Reduced from 15-20 to 4-5 (1.5% of 320)
+    -6,  0,  4,  5,  5,  4,  0, -6,
+    -6,  1,  2,  4,  4,  2,  1, -6,
+   -10, -4,  0,  1,  1,  0, -4,-10,
+   -12,-10, -6, -6, -6, -6,-10,-12,
+];
+
+pub const OPENING_BISHOP_PST: [i32; 64] = [
+    -4, -2, -2, -2, -2, -2, -2, -4,  // Reduced from -20 to -4 (1% of 330)
+    -2,  0,  0,  0,  0,  0,  0, -2,
+    -2,  0,  1,  2,  2,  1,  0, -2,  // Reduced from 5-10 to 1-2
+    -2,  1,  1,  2,  2,  1,  1, -2,
+    -2,  0,  2,  2,  2,  2,  0, -2,
+    -2,  2,  2,  2,  2,  2,  2, -2,
+    -2,  1,  0,  0,  0,  0,  1, -2,
+    -4, -2, -2, -2, -2, -2, -2, -4,
+];
+
+pub const OPENING_ROOK_PST: [i32; 64] = [
+     0,  0,  0,  0,  0,  0,  0,  0,
+     1,  2,  2,  2,  2,  2,  2,  1,  // Reduced from 5-10 to 1-2 (0.4% of 500)
+    -1,  0,  0,  0,  0,  0,  0, -1,  // Reduced from -5 to -1
+    -1,  0,  0,  0,  0,  0,  0, -1,
+    -1,  0,  0,  0,  0,  0,  0, -1,
+    -1,  0,  0,  0,  0,  0,  0, -1,
+    -1,  0,  0,  0,  0,  0,  0, -1,
+     0,  0,  0,  1,  1,  0,  0,  0,  // Reduced from 5 to 1
+];
+
+pub const OPENING_QUEEN_PST: [i32; 64] = [
+    -4, -2, -2, -1, -1, -2, -2, -4,  // Reduced from -20 to -4 (0.4% of 900)
+    -2,  0,  0,  0,  0,  0,  0, -2,
+    -2,  0,  1,  1,  1,  1,  0, -2,  // Reduced from 5 to 1
+    -1,  0,  1,  1,  1,  1,  0, -1,
+     0,  0,  1,  1,  1,  1,  0, -1,
+    -2,  1,  1,  1,  1,  1,  0, -2,
+    -2,  0,  1,  0,  0,  0,  0, -2,
+    -4, -2, -2, -1, -1, -2, -2, -4,
+];
+
+pub const OPENING_KING_PST: [i32; 64] = [
+    -6, -8, -8,-10,-10, -8, -8, -6,  // Reduced from -30 to -6
+    -6, -8, -8,-10,-10, -8, -8, -6,  // King safety still important but scaled
+    -6, -8, -8,-10,-10, -8, -8, -6,
+    -6, -8, -8,-10,-10, -8, -8, -6,
+    -4, -6, -6, -8, -8, -6, -6, -4,
+    -2, -4, -4, -4, -4, -4, -4, -2,
+     4,  4,  0,  0,  0,  0,  4,  4,  // Reduced from 20 to 4
+     4,  6,  2,  0,  0,  2,  6,  4,  // Reduced from 30 to 6
+];
+
+// Endgame PST - Keep higher values as endgame positioning is more critical
+pub const ENDGAME_PAWN_PST: [i32; 64] = [
+     0,  0,  0,  0,  0,  0,  0,  0,
+    80, 80, 80, 80, 80, 80, 80, 80,  // Keep high - pawn promotion is huge
+    60, 60, 60, 60, 60, 60, 60, 60,
+    40, 40, 40, 40, 40, 40, 40, 40,
+    20, 20, 20, 20, 20, 20, 20, 20,
+    10, 10, 10, 10, 10, 10, 10, 10,
+    10, 10, 10, 10, 10, 10, 10, 10,
+     0,  0,  0,  0,  0,  0,  0,  0,
+];
+
+pub const ENDGAME_KING_PST: [i32; 64] = [
+   -50,-40,-30,-20,-20,-30,-40,-50,  // Keep high - king activity crucial in endgame
+   -30,-20,-10,  0,  0,-10,-20,-30,
+   -30,-10, 20, 30, 30, 20,-10,-30,
+   -30,-10, 30, 40, 40, 30,-10,-30,
+   -30,-10, 30, 40, 40, 30,-10,-30,
+   -30,-10, 20, 30, 30, 20,-10,-30,
+   -30,-30,  0,  0,  0,  0,-30,-30,
+   -50,-30,-30,-30,-30,-30,-30,-50,
+];
+
+pub const ENDGAME_KNIGHT_PST: [i32; 64] = [
+   -20,-15,-10,-10,-10,-10,-15,-20,  // Knights lose value on the rim as the board empties
+   -15, -5,  0,  0,  0,  0, -5,-15,
+   -10,  0,  5,  8,  8,  5,  0,-10,
+   -10,  0,  8, 10, 10,  8,  0,-10,
+   -10,  0,  8, 10, 10,  8,  0,-10,
+   -10,  0,  5,  8,  8,  5,  0,-10,
+   -15, -5,  0,  0,  0,  0, -5,-15,
+   -20,-15,-10,-10,-10,-10,-15,-20,
+];
+
+pub const ENDGAME_BISHOP_PST: [i32; 64] = [
+    -8, -4, -4, -4, -4, -4, -4, -8,
+    -4,  0,  0,  0,  0,  0,  0, -4,
+    -4,  0,  4,  4,  4,  4,  0, -4,
+    -4,  0,  4,  6,  6,  4,  0, -4,
+    -4,  0,  4,  6,  6,  4,  0, -4,
+    -4,  0,  4,  4,  4,  4,  0, -4,
+    -4,  0,  0,  0,  0,  0,  0, -4,
+    -8, -4, -4, -4, -4, -4, -4, -8,
+];
+
+pub const ENDGAME_ROOK_PST: [i32; 64] = [
+     4,  4,  4,  4,  4,  4,  4,  4,
+     8,  8,  8,  8,  8,  8,  8,  8,  // Seventh rank cuts off the enemy king
+     2,  2,  2,  2,  2,  2,  2,  2,
+     0,  0,  0,  0,  0,  0,  0,  0,
+     0,  0,  0,  0,  0,  0,  0,  0,
+     0,  0,  0,  0,  0,  0,  0,  0,
+     0,  0,  0,  0,  0,  0,  0,  0,
+     0,  0,  0,  0,  0,  0,  0,  0,
+];
+
+pub const ENDGAME_QUEEN_PST: [i32; 64] = [
+   -10, -5, -5, -5, -5, -5, -5,-10,
+    -5,  0,  0,  0,  0,  0,  0, -5,
+    -5,  0,  5,  5,  5,  5,  0, -5,
+    -5,  0,  5, 10, 10,  5,  0, -5,
+    -5,  0,  5, 10, 10,  5,  0, -5,
+    -5,  0,  5,  5,  5,  5,  0, -5,
+    -5,  0,  0,  0,  0,  0,  0, -5,
+   -10, -5, -5, -5, -5, -5, -5,-10,
+];
+
+/// One set of piece-square tables, indexed [piece_type - 1][square]
+/// Squares are laid out from White's point of view with a8 first
+pub type PstTables = [[i32; 64]; 6];
+
+pub const MIDDLEGAME_PST: PstTables = [
+    OPENING_PAWN_PST, OPENING_KNIGHT_PST, OPENING_BISHOP_PST,
+    OPENING_ROOK_PST, OPENING_QUEEN_PST, OPENING_KING_PST,
+];
+
+pub const ENDGAME_PST: PstTables = [
+    ENDGAME_PAWN_PST, ENDGAME_KNIGHT_PST, ENDGAME_BISHOP_PST,
+    ENDGAME_ROOK_PST, ENDGAME_QUEEN_PST, ENDGAME_KING_PST,
+];
+
+/// Sum of PST values for all pieces from White's perspective, using one table set
+pub fn pst_score_with(board: &Board, tables: &PstTables) -> i32 {
+    let mut score = 0;
+
+    for piece_type in [PAWN, KNIGHT, BISHOP, ROOK, QUEEN, KING] {
+        let table = &tables[(piece_type - 1) as usize];
+
+        for square in board.bitboards.find_pieces(WHITE, piece_type) {
+            score += table[((7 - square.rank()) * 8 + square.file()) as usize];
+        }
+
+        // Black uses the same table mirrored by rank
+        for square in board.bitboards.find_pieces(BLACK, piece_type) {
+            score -= table[(square.rank() * 8 + square.file()) as usize];
+        }
+    }
+
+    score
+}
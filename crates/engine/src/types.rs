@@ -1,6 +1,7 @@
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct Square(pub u8);
 use crate::Board;
+use serde::{Deserialize, Serialize};
 
 impl Square {
     pub fn new(file: u8, rank: u8) -> Self {
@@ -71,7 +72,7 @@ pub fn is_empty(piece: Piece) -> bool {
     piece == EMPTY
 }
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct Move {
     pub from: Square,
     pub to: Square,
@@ -90,21 +91,165 @@ impl Move {
     pub fn is_promotion(&self) -> bool {
         self.promotion.is_some()
     }
+
+    /// Takes a piece on the given board, en passant included
+    pub fn is_capture(&self, board: &Board) -> bool {
+        !is_empty(board.get_piece(self.to)) || board.is_en_passant_move(*self)
+    }
+    
+    /// Parse a move in UCI long algebraic form, e.g. "e2e4" or "e7e8q"
+    pub fn from_uci(uci: &str) -> Option<Self> {
+        Self::parse_uci(uci).ok()
+    }
+
+    /// Parse a UCI move and check that it is legal on the given board
+    pub fn from_uci_str(s: &str, board: &Board) -> Result<Self, MoveError> {
+        let mv = Self::parse_uci(s)?;
+        if board.get_all_legal_moves().contains(&mv) {
+            Ok(mv)
+        } else {
+            Err(MoveError::IllegalMove(s.to_string()))
+        }
+    }
+
+    fn parse_uci(uci: &str) -> Result<Self, MoveError> {
+        let bytes = uci.as_bytes();
+        if bytes.len() != 4 && bytes.len() != 5 {
+            return Err(MoveError::InvalidString(uci.to_string()));
+        }
+
+        let parse_square = |file: u8, rank: u8| -> Result<Square, MoveError> {
+            if (b'a'..=b'h').contains(&file) && (b'1'..=b'8').contains(&rank) {
+                Ok(Square::new(file - b'a', rank - b'1'))
+            } else {
+                Err(MoveError::OutOfBounds(uci.to_string()))
+            }
+        };
+
+        let from = parse_square(bytes[0], bytes[1])?;
+        let to = parse_square(bytes[2], bytes[3])?;
+
+        if bytes.len() == 5 {
+            let promotion = match bytes[4] {
+                b'q' => QUEEN,
+                b'r' => ROOK,
+                b'b' => BISHOP,
+                b'n' => KNIGHT,
+                _ => return Err(MoveError::InvalidString(uci.to_string())),
+            };
+            Ok(Self::new_promotion(from, to, promotion))
+        } else {
+            Ok(Self::new(from, to))
+        }
+    }
 }
 
+/// UCI long algebraic form, e.g. "e2e4" or "a7a8q"
+impl std::fmt::Display for Move {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}{}", self.from, self.to)?;
+        match self.promotion {
+            Some(QUEEN) => write!(f, "q"),
+            Some(ROOK) => write!(f, "r"),
+            Some(BISHOP) => write!(f, "b"),
+            Some(KNIGHT) => write!(f, "n"),
+            Some(_) => write!(f, "?"),
+            None => Ok(()),
+        }
+    }
+}
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Errors from parsing positions and moves, and from making or undoing moves
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MoveError {
+    InvalidFen { reason: String },
+    InvalidString(String), // Wrong length or unknown promotion piece
+    OutOfBounds(String),   // File or rank outside a-h / 1-8
+    IllegalMove(String),   // Well formed but not legal in the position
+    NoMoveToUndo,
+    GameAlreadyOver,
+}
+
+impl std::fmt::Display for MoveError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MoveError::InvalidFen { reason } => write!(f, "Invalid FEN: {}", reason),
+            MoveError::InvalidString(s) => write!(f, "Invalid move string: {}", s),
+            MoveError::OutOfBounds(s) => write!(f, "Square out of bounds in move: {}", s),
+            MoveError::IllegalMove(s) => write!(f, "Illegal move: {}", s),
+            MoveError::NoMoveToUndo => write!(f, "No moves to undo"),
+            MoveError::GameAlreadyOver => write!(f, "Game is over"),
+        }
+    }
+}
+
+impl std::error::Error for MoveError {}
+
+/// Internal inconsistencies found by `Board::validate`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BoardError {
+    KingCount { color: u8, count: u32 },  // Each side needs exactly one king
+    CastlingRight { right: u8 },          // Set although the king or rook has left its square
+    EnPassant,                            // Target off rank 3/6, or the pawn isn't behind it
+    BitboardMismatch,                     // Bitboards and the squares array disagree
+    PawnOnBackRank { square: Square },
+}
+
+impl std::fmt::Display for BoardError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BoardError::KingCount { color, count } => {
+                write!(f, "{} has {} kings", if *color == WHITE { "White" } else { "Black" }, count)
+            }
+            BoardError::CastlingRight { right } => write!(f, "Castling right {:#06b} without its king and rook in place", right),
+            BoardError::EnPassant => write!(f, "En passant square doesn't match a pawn that just moved two squares"),
+            BoardError::BitboardMismatch => write!(f, "Bitboards don't match the squares array"),
+            BoardError::PawnOnBackRank { square } => write!(f, "Pawn on {}", square),
+        }
+    }
+}
+
+impl std::error::Error for BoardError {}
+
+// Lets `?` keep working in callers that still report errors as strings
+impl From<MoveError> for String {
+    fn from(error: MoveError) -> Self {
+        error.to_string()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum GameStatus {
     InProgress,
     Check(u8), // Which color is in check
     Checkmate(u8), // Which color is checkmated (other color wins)
     Stalemate,
-    Draw,
+    Draw(DrawReason),
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DrawReason {
+    Stalemate,
+    FiftyMove,
+    Repetition,
+    InsufficientMaterial,
+    Theoretical, // Drawn with best play, see `Board::is_theoretical_draw`
+    Agreement,
+}
+
+/// Outcome of the game so far
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameResult {
+    InProgress,
+    WhiteWins,
+    BlackWins,
+    Draw(DrawReason),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GameMove {
     pub mv: Move,
+    pub moved_piece: Piece, // What stood on `mv.from`; the pawn for promotions, the king for castling
     pub captured_piece: Piece,
     pub promotion: Option<u8>,
     pub is_castling: bool,
@@ -116,12 +261,16 @@ pub struct GameMove {
     pub previous_en_passant_pawn: Option<Square>,
     pub previous_half_move_clock: u16,
     pub previous_full_move_number: u16,
+    pub previous_game_status: GameStatus,
+    #[serde(default)]
+    pub is_null_move: bool, // A pass from `Board::apply_null_move`; `mv` is meaningless
 }
 
 impl GameMove {
     pub fn new(mv: Move) -> Self {
         Self {
             mv,
+            moved_piece: EMPTY,
             captured_piece: EMPTY,
             promotion: None,
             is_castling: false,
@@ -131,6 +280,8 @@ impl GameMove {
             previous_en_passant_pawn: None,
             previous_half_move_clock: 0,
             previous_full_move_number: 0,
+            previous_game_status: GameStatus::InProgress,
+            is_null_move: false,
         }
     }
     
@@ -143,6 +294,7 @@ impl GameMove {
     pub fn new_with_state(mv: Move, board: &Board) -> Self {
         Self {
             mv,
+            moved_piece: board.get_piece(mv.from),
             captured_piece: EMPTY,
             promotion: None,
             is_castling: false,
@@ -152,9 +304,19 @@ impl GameMove {
             previous_en_passant_pawn: board.en_passant_pawn,
             previous_half_move_clock: board.half_move_clock,
             previous_full_move_number: board.full_move_number,
+            previous_game_status: board.game_status,
+            is_null_move: false,
         }
     }
 
+    /// Pass by the side to move, remembering the state the pass clears
+    pub fn null_move(board: &Board) -> Self {
+        let mut game_move = Self::new_with_state(Move::new(Square(0), Square(0)), board);
+        game_move.moved_piece = EMPTY;
+        game_move.is_null_move = true;
+        game_move
+    }
+
     pub fn with_capture_and_state(mv: Move, captured: Piece, board: &Board) -> Self {
         let mut game_move = Self::new_with_state(mv, board);
         game_move.captured_piece = captured;
@@ -162,6 +324,22 @@ impl GameMove {
     }
 }
 
+/// Detailed description of a single move, produced by `Board::annotate_move`
+#[derive(Debug, Clone, PartialEq)]
+pub struct MoveAnnotation {
+    pub is_capture: bool,
+    pub captured_piece: Option<u8>,    // Piece type of the captured piece
+    pub is_en_passant: bool,
+    pub is_castling: Option<bool>,     // Some(true) = kingside, Some(false) = queenside
+    pub is_promotion: Option<u8>,      // Piece type promoted to
+    pub is_check: bool,
+    pub is_checkmate: bool,
+    pub is_double_check: bool,
+    pub gives_discovered_check: bool,
+    pub san: String,
+    pub uci: String,
+}
+
 // Helper function to get opposite color
 pub fn opposite_color(color: u8) -> u8 {
     color ^ WHITE
@@ -179,6 +357,18 @@ pub const WHITE_QUEENSIDE: u8 = 0b0010;
 pub const BLACK_KINGSIDE: u8 = 0b0100;
 pub const BLACK_QUEENSIDE: u8 = 0b1000;
 pub const ALL_CASTLING_RIGHTS: u8 = 0b1111;
+/// Castling rook files in the bit order of the rights above (white kingside first)
+pub const STANDARD_CASTLING_ROOK_FILES: [u8; 4] = [7, 0, 7, 0];
+
+/// The castling right bit for one side of the board
+pub fn castling_right(color: u8, kingside: bool) -> u8 {
+    match (color == WHITE, kingside) {
+        (true, true) => WHITE_KINGSIDE,
+        (true, false) => WHITE_QUEENSIDE,
+        (false, true) => BLACK_KINGSIDE,
+        (false, false) => BLACK_QUEENSIDE,
+    }
+}
 
 // Helper functions for castling rights
 pub fn has_castling_right(castling_rights: u8, right: u8) -> bool {
@@ -210,11 +400,28 @@ pub fn piece_type_name(piece_type: u8) -> &'static str {
 }
 
 impl Square {
-    // Add this if it doesn't exist
-    pub fn from_algebraic(algebraic: &str) -> Self {
-        let chars: Vec<char> = algebraic.chars().collect();
-        let file = (chars[0] as u8) - b'a';
-        let rank = (chars[1] as u8) - b'1';
-        Self::new(file, rank)
+    /// Parse a square name such as "e4"
+    pub fn from_algebraic(algebraic: &str) -> Result<Self, MoveError> {
+        let bytes = algebraic.as_bytes();
+        if bytes.len() != 2 {
+            return Err(MoveError::InvalidString(algebraic.to_string()));
+        }
+        if !(b'a'..=b'h').contains(&bytes[0]) || !(b'1'..=b'8').contains(&bytes[1]) {
+            return Err(MoveError::OutOfBounds(algebraic.to_string()));
+        }
+        Ok(Self::new(bytes[0] - b'a', bytes[1] - b'1'))
+    }
+}
+
+impl std::fmt::Display for Square {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}{}", (b'a' + self.file()) as char, (b'1' + self.rank()) as char)
+    }
+}
+
+// Square names read better than raw indices in test failures and logs
+impl std::fmt::Debug for Square {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        std::fmt::Display::fmt(self, f)
     }
 }
\ No newline at end of file
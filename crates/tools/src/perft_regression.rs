@@ -0,0 +1,158 @@
+// Perft regression runner for CI
+// Usage: cargo run --release --bin perft_regression -- [--max-depth N] [--tt] [--verify]
+//   --tt      count with the perft transposition table
+//   --verify  run with and without the table and fail if they disagree
+// Exits with code 0 if every node count matches, 1 otherwise.
+
+use engine::perft::*;
+use engine::{Board, Move};
+use std::process;
+use std::time::Instant;
+
+struct RegressionRow {
+    name: &'static str,
+    depth: u32,
+    expected: u64,
+    actual: u64,
+    time_ms: u128,
+}
+
+impl RegressionRow {
+    fn passed(&self) -> bool {
+        self.expected == self.actual
+    }
+
+    fn nodes_per_second(&self) -> u64 {
+        if self.time_ms == 0 {
+            return 0;
+        }
+        (self.actual * 1000) / (self.time_ms as u64)
+    }
+}
+
+#[derive(Default)]
+struct Options {
+    max_depth: Option<u32>,
+    use_tt: bool,
+    verify: bool,
+}
+
+fn parse_args() -> Result<Options, String> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let mut options = Options::default();
+    let mut i = 0;
+
+    while i < args.len() {
+        match args[i].as_str() {
+            "--max-depth" => {
+                let value = args.get(i + 1).ok_or("--max-depth needs a value")?;
+                options.max_depth = Some(value.parse().map_err(|_| format!("Invalid depth: {}", value))?);
+                i += 2;
+            }
+            "--tt" => {
+                options.use_tt = true;
+                i += 1;
+            }
+            "--verify" => {
+                options.verify = true;
+                i += 1;
+            }
+            other => return Err(format!("Unknown argument: {}", other)),
+        }
+    }
+
+    Ok(options)
+}
+
+// Compare each root move's subtree against the stored reference divide; a move that is missing,
+// unexpected or off by some nodes is where move generation goes wrong
+fn report_first_divergence(test_case: &PerftTestCase) {
+    let mut board = Board::from_fen(test_case.fen).expect("Perft position has invalid FEN");
+    let divide = perft_divide(&mut board, DIVIDE_DEPTH);
+    for &(uci, expected) in test_case.expected_divide {
+        let actual = divide.iter().find(|(mv, _)| mv.to_string() == uci).map(|&(_, nodes)| nodes);
+        if actual != Some(expected) {
+            let actual = actual.map_or("missing".to_string(), |nodes| format!("{} nodes", nodes));
+            println!("      first diverging move at depth {}: {} ({}, {} expected)", DIVIDE_DEPTH, uci, actual, expected);
+            return;
+        }
+    }
+    let is_expected = |mv: &Move| test_case.expected_divide.iter().any(|&(uci, _)| mv.to_string() == uci);
+    if let Some((mv, nodes)) = divide.iter().find(|(mv, _)| !is_expected(mv)) {
+        println!("      first diverging move at depth {}: {} ({} nodes, not a legal move)", DIVIDE_DEPTH, mv, nodes);
+        return;
+    }
+    println!("      every root move matches the reference divide at depth {}, so the divergence is deeper", DIVIDE_DEPTH);
+}
+
+fn main() {
+    let options = match parse_args() {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("{}", e);
+            eprintln!("Usage: perft_regression [--max-depth N] [--tt] [--verify]");
+            process::exit(2);
+        }
+    };
+
+    engine::bitboard::initialize_engine();
+
+    let mut rows = Vec::new();
+
+    for test_case in PERFT_POSITIONS {
+        for &(depth, expected) in test_case.expected_results {
+            if options.max_depth.is_some_and(|max| depth > max) {
+                continue;
+            }
+
+            let mut board = Board::from_fen(test_case.fen).expect("Perft position has invalid FEN");
+            let start_time = Instant::now();
+            let mut actual = if options.use_tt { perft_hashed(&mut board, depth) } else { perft(&mut board, depth) };
+            let time_ms = start_time.elapsed().as_millis();
+
+            if options.verify {
+                let other = if options.use_tt { perft(&mut board, depth) } else { perft_hashed(&mut board, depth) };
+                if other != actual {
+                    eprintln!("{} depth {}: perft with TT and without disagree ({} vs {})",
+                        test_case.name, depth, actual, other);
+                    actual = u64::MAX; // Report as a failure
+                }
+            }
+
+            let row = RegressionRow {
+                name: test_case.name,
+                depth,
+                expected,
+                actual,
+                time_ms,
+            };
+
+            let failed = !row.passed();
+            rows.push(row);
+
+            // Deeper counts can't match once a shallower one is wrong
+            if failed {
+                report_first_divergence(test_case);
+                break;
+            }
+        }
+    }
+
+    println!("{:<20} {:>5} {:>14} {:>14} {:>6} {:>10} {:>12}",
+        "Position", "Depth", "Expected", "Actual", "Result", "Time (ms)", "NPS");
+    println!("{}", "-".repeat(87));
+
+    for row in &rows {
+        println!("{:<20} {:>5} {:>14} {:>14} {:>6} {:>10} {:>12}",
+            row.name, row.depth, row.expected, row.actual,
+            if row.passed() { "PASS" } else { "FAIL" },
+            row.time_ms, row.nodes_per_second());
+    }
+
+    let failures = rows.iter().filter(|row| !row.passed()).count();
+    println!("\n{}/{} perft counts matched", rows.len() - failures, rows.len());
+
+    if failures > 0 {
+        process::exit(1);
+    }
+}
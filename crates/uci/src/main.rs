@@ -0,0 +1,219 @@
+// Universal Chess Interface front end for the engine
+// Usage: cargo run --release --bin uci, then drive it from a GUI or cutechess-cli.
+// Searches run synchronously except `go ponder`, which thinks on a background thread until
+// `ponderhit` (search on for the move's budget) or `stop` (answer straight away).
+// `bench [depth]` is a non-standard extra that times a fixed search over the perft positions.
+
+use ai::{mate_in_moves, BenchResult, SearchEngine, SearchResult};
+use engine::{Board, Move, MoveError, PERFT_POSITIONS};
+use std::io::{self, BufRead, Write};
+use std::time::Instant;
+
+const ENGINE_NAME: &str = "ChaseChess";
+const ENGINE_AUTHOR: &str = "MayankSandh";
+const DEFAULT_DEPTH: u32 = 5;
+const MAX_DEPTH: u32 = 64;
+// Time budgets leave this much for sending the move back to the GUI
+const MOVE_OVERHEAD_MS: u64 = 50;
+// Assumed moves left when the GUI only sends the remaining clock
+const DEFAULT_MOVES_TO_GO: u64 = 30;
+// Search time after `ponderhit` when `go ponder` came without a clock
+const DEFAULT_PONDER_HIT_MS: u64 = 1000;
+// `bench` without a depth searches each position this deep
+const BENCH_DEPTH: u32 = 12;
+
+/// Why a command from the GUI couldn't be carried out
+#[derive(Debug)]
+enum UciError {
+    Syntax(String), // The command's arguments don't follow the protocol
+    Move(MoveError),
+}
+
+impl std::fmt::Display for UciError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            UciError::Syntax(reason) => write!(f, "Malformed command: {}", reason),
+            UciError::Move(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl From<MoveError> for UciError {
+    fn from(error: MoveError) -> Self {
+        UciError::Move(error)
+    }
+}
+
+struct UciState {
+    board: Board,
+    search_engine: SearchEngine,
+    ponder_budget_ms: Option<u64>, // What `ponderhit` leaves the ponder search, from the `go ponder` clock
+    ponder_start: Instant,
+}
+
+impl UciState {
+    fn new() -> Self {
+        Self {
+            board: Board::new(),
+            search_engine: SearchEngine::new(),
+            ponder_budget_ms: None,
+            ponder_start: Instant::now(),
+        }
+    }
+
+    /// Handle one command line; returns false once the GUI asks us to quit
+    fn handle_command(&mut self, line: &str, out: &mut impl Write) -> io::Result<bool> {
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        match tokens.first().copied() {
+            Some("uci") => {
+                writeln!(out, "id name {}", ENGINE_NAME)?;
+                writeln!(out, "id author {}", ENGINE_AUTHOR)?;
+                writeln!(out, "uciok")?;
+            }
+            Some("isready") => writeln!(out, "readyok")?,
+            Some("ucinewgame") => *self = Self::new(),
+            Some("position") => {
+                if let Err(e) = self.set_position(&tokens[1..]) {
+                    writeln!(out, "info string {}", e)?;
+                }
+            }
+            Some("go") => self.go(&tokens[1..], out)?,
+            Some("ponderhit") => {
+                let budget = self.ponder_budget_ms.take();
+                let result = match budget {
+                    Some(ms) => self.search_engine.ponder_hit(ms.saturating_sub(MOVE_OVERHEAD_MS)),
+                    None => self.search_engine.ponder_hit(DEFAULT_PONDER_HIT_MS),
+                };
+                self.report(result, out)?;
+            }
+            Some("stop") if self.search_engine.is_pondering() => {
+                let result = self.search_engine.stop_ponder();
+                self.report(result, out)?;
+            }
+            Some("stop") => {}
+            Some("bench") => {
+                let depth = tokens.get(1).and_then(|depth| depth.parse().ok()).unwrap_or(BENCH_DEPTH);
+                bench(depth.clamp(1, MAX_DEPTH), out)?;
+            }
+            Some("quit") => return Ok(false),
+            Some(other) => writeln!(out, "info string Unknown command: {}", other)?,
+            None => {}
+        }
+        out.flush()?;
+        Ok(true)
+    }
+
+    /// `position startpos moves ...` or `position fen <fen> moves ...`
+    fn set_position(&mut self, args: &[&str]) -> Result<(), UciError> {
+        let moves_index = args.iter().position(|&token| token == "moves");
+        let setup = &args[..moves_index.unwrap_or(args.len())];
+
+        let mut board = match setup.first() {
+            Some(&"startpos") => Board::new(),
+            Some(&"fen") => Board::from_fen(&setup[1..].join(" "))?,
+            _ => return Err(UciError::Syntax("position needs startpos or fen".to_string())),
+        };
+
+        if let Some(index) = moves_index {
+            for uci in &args[index + 1..] {
+                let mv = Move::from_uci_str(uci, &board)?;
+                board.try_make_move(mv)?;
+            }
+        }
+
+        self.board = board;
+        Ok(())
+    }
+
+    /// `go depth N` searches to a fixed depth; `go movetime N` and `go wtime/btime` search on the clock
+    fn go(&mut self, args: &[&str], out: &mut impl Write) -> io::Result<()> {
+        let value_of = |name: &str| -> Option<u64> {
+            let index = args.iter().position(|&token| token == name)?;
+            args.get(index + 1)?.parse().ok()
+        };
+
+        let (time_left, increment) = if self.board.current_turn == engine::WHITE {
+            (value_of("wtime"), value_of("winc").unwrap_or(0))
+        } else {
+            (value_of("btime"), value_of("binc").unwrap_or(0))
+        };
+        // Spread the remaining clock over the moves to go, never planning to use all of it
+        let clock_budget = time_left.map(|remaining| {
+            let moves_to_go = value_of("movestogo").unwrap_or(DEFAULT_MOVES_TO_GO).max(1);
+            (remaining / moves_to_go + increment).min(remaining.saturating_sub(MOVE_OVERHEAD_MS))
+        });
+
+        if args.contains(&"ponder") {
+            self.ponder_budget_ms = value_of("movetime").or(clock_budget);
+            self.ponder_start = Instant::now();
+            self.search_engine.start_ponder(self.board.clone());
+            return Ok(());
+        }
+
+        let start = Instant::now();
+        let mut board = self.board.clone();
+        let result = match (value_of("depth"), value_of("movetime").or(clock_budget)) {
+            (Some(depth), _) => self.search_engine.search(&mut board, (depth as u32).clamp(1, MAX_DEPTH)),
+            (None, Some(ms)) => self.search_engine.search_timed(&mut board, ms, MOVE_OVERHEAD_MS),
+            (None, None) => self.search_engine.search(&mut board, DEFAULT_DEPTH),
+        };
+        self.print_result(&result, start, out)
+    }
+
+    /// Answer for a ponder search that was stopped or hit
+    fn report(&self, result: Option<SearchResult>, out: &mut impl Write) -> io::Result<()> {
+        match result {
+            Some(result) => self.print_result(&result, self.ponder_start, out),
+            None => writeln!(out, "bestmove 0000"),
+        }
+    }
+
+    fn print_result(&self, result: &SearchResult, start: Instant, out: &mut impl Write) -> io::Result<()> {
+        let score = match mate_in_moves(result.evaluation) {
+            Some(moves) => format!("mate {}", moves),
+            None => format!("cp {}", result.evaluation),
+        };
+        writeln!(out, "info depth {} score {} nodes {} time {}",
+                 result.depth_reached, score, result.nodes_searched, start.elapsed().as_millis())?;
+
+        match result.best_move {
+            Some(mv) => writeln!(out, "bestmove {}", mv),
+            None => writeln!(out, "bestmove 0000"),
+        }
+    }
+}
+
+/// Search every perft position to `depth` with a fresh engine, then report the totals
+fn bench(depth: u32, out: &mut impl Write) -> io::Result<()> {
+    let mut total_nodes = 0;
+    let mut total_ms = 0;
+    let mut total_razored = 0;
+    for (index, test_case) in PERFT_POSITIONS.iter().enumerate() {
+        let mut board = Board::from_fen(test_case.fen).expect("Perft position has invalid FEN");
+        let result = SearchEngine::new().bench(&mut board, depth);
+        writeln!(out, "info string bench {}/{} {}: {} nodes {} razored {} ms",
+                 index + 1, PERFT_POSITIONS.len(), test_case.name, result.nodes, result.razored_nodes, result.time_ms)?;
+        total_nodes += result.nodes;
+        total_ms += result.time_ms;
+        total_razored += result.razored_nodes;
+    }
+
+    let total = BenchResult::new(total_nodes, total_ms, total_razored);
+    writeln!(out, "info string bench total: {} nodes {} razored {} ms {} nps",
+             total.nodes, total.razored_nodes, total.time_ms, total.nps)
+}
+
+fn main() -> io::Result<()> {
+    engine::bitboard::initialize_engine();
+    let mut state = UciState::new();
+    let stdin = io::stdin();
+    let mut stdout = io::stdout();
+
+    for line in stdin.lock().lines() {
+        if !state.handle_command(&line?, &mut stdout)? {
+            break;
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,83 @@
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+fn run_uci(commands: &str) -> String {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_uci"))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Failed to start uci binary");
+
+    child.stdin.take().unwrap().write_all(commands.as_bytes()).unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn test_uci_handshake_and_search() {
+    let output = run_uci("uci\nisready\nucinewgame\nposition startpos moves e2e4 e7e5\ngo depth 3\nquit\n");
+    let lines: Vec<&str> = output.lines().collect();
+
+    assert!(lines.contains(&"id name ChaseChess"));
+    assert!(lines.iter().any(|line| line.starts_with("id author ")));
+    assert!(lines.contains(&"uciok"));
+    assert!(lines.contains(&"readyok"));
+    assert!(lines.iter().any(|line| line.starts_with("info depth 3 ")));
+
+    let bestmove = lines.last().unwrap();
+    assert!(bestmove.starts_with("bestmove "), "Unexpected last line: {}", bestmove);
+    assert_eq!(bestmove.len(), "bestmove e2e4".len());
+}
+
+#[test]
+fn test_uci_position_fen_finds_mate() {
+    let output = run_uci("position fen 6k1/5ppp/8/8/8/8/5PPP/R5K1 w - - 0 1\ngo movetime 200\nquit\n");
+    assert!(output.contains(" score mate 1 "), "Mate not reported: {}", output);
+    assert_eq!(output.lines().last(), Some("bestmove a1a8"));
+}
+
+#[test]
+fn test_uci_reports_illegal_moves() {
+    let output = run_uci("position startpos moves e2e5\nquit\n");
+    assert!(output.contains("info string Illegal move: e2e5"));
+}
+
+#[test]
+fn test_uci_go_with_clock() {
+    let start = std::time::Instant::now();
+    let output = run_uci("position startpos\ngo wtime 3000 btime 3000 winc 0 binc 0\nquit\n");
+    assert!(output.lines().last().unwrap().starts_with("bestmove "));
+    assert_ne!(output.lines().last(), Some("bestmove 0000"));
+    // 3000ms over 30 moves is a 100ms budget
+    assert!(start.elapsed().as_millis() < 2000);
+}
+
+#[test]
+fn test_uci_go_ponder() {
+    // Nothing is printed until the ponder search is stopped
+    let output = run_uci("position startpos moves e2e4\ngo ponder wtime 3000 btime 3000\nisready\nstop\nquit\n");
+    let lines: Vec<&str> = output.lines().collect();
+    let ready = lines.iter().position(|&line| line == "readyok").unwrap();
+    assert!(lines[..ready].iter().all(|line| !line.starts_with("bestmove ")));
+    assert!(lines.last().unwrap().starts_with("bestmove "));
+    assert_ne!(lines.last(), Some(&"bestmove 0000"));
+
+    // ponderhit turns it into a normal search on the clock from `go ponder`
+    let start = std::time::Instant::now();
+    let output = run_uci("position startpos moves e2e4\ngo ponder wtime 3000 btime 3000\nponderhit\nquit\n");
+    assert!(output.lines().last().unwrap().starts_with("bestmove "));
+    assert_ne!(output.lines().last(), Some("bestmove 0000"));
+    assert!(start.elapsed().as_millis() < 2000);
+}
+
+#[test]
+fn test_uci_bench() {
+    let output = run_uci("bench 2\nquit\n");
+    let lines: Vec<&str> = output.lines().filter(|line| line.starts_with("info string bench")).collect();
+
+    assert_eq!(lines.len(), 7);
+    assert!(lines[0].starts_with("info string bench 1/6 "));
+    assert!(lines[6].starts_with("info string bench total: "));
+    assert!(lines[6].ends_with(" nps"));
+}
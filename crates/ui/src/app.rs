@@ -1,33 +1,146 @@
 use egui::{Color32, Rect, Sense, Vec2}; 
-use engine::{Board, Move, Square, piece_type, piece_color, is_empty}; // Removed unused is_white, is_black
+use engine::{Board, Move, Square, GameResult, DrawReason, PgnMetadata, piece_type, piece_color, is_empty, opposite_color}; // Removed unused is_white, is_black
 use engine::{PAWN, KNIGHT, BISHOP, ROOK, QUEEN, KING, WHITE, BLACK};
-use ai::SearchEngine;
+use ai::{BestMoveCandidate, SearchEngine};
 use std::time::Instant;
 use engine::ChessLogger;
 use std::rc::Rc;
 use std::cell::RefCell;
+use std::sync::{mpsc, Arc, Mutex};
 
 // Add these lines after your imports
 const FILES: [char; 8] = ['a', 'b', 'c', 'd', 'e', 'f', 'g', 'h'];
 const RANKS: [char; 8] = ['1', '2', '3', '4', '5', '6', '7', '8'];
 
+/// Clocks turn red below this much time
+const LOW_TIME_MS: u64 = 30_000;
+/// Share of the remaining clock the AI spends on one move
+const AI_MOVES_TO_GO: u64 = 30;
+/// Analysis mode searches this deep for this many candidate moves
+const ANALYSIS_DEPTH: u32 = 4;
+const ANALYSIS_LINES: usize = 3;
+/// Pause before an AI move in AI vs AI games, so the game can be followed
+const DEFAULT_AI_MOVE_DELAY_MS: u64 = 500;
+const MAX_AI_MOVE_DELAY_MS: u64 = 3_000;
+/// Saved games live here; the game in progress is kept in `LAST_GAME_PATH` after every move
+const SAVES_DIR: &str = "saves";
+const LAST_GAME_PATH: &str = "saves/last_game.json";
+/// Puzzle mode positions; the expected answer is whatever the engine finds at `PUZZLE_DEPTH`
+const PUZZLES: [(&str, &str); 5] = [
+    ("Back rank mate", "6k1/5ppp/8/8/8/8/5PPP/3R2K1 w - - 0 1"),
+    ("Scholar's mate", "r1bqkb1r/pppp1ppp/2n2n2/4p2Q/2B1P3/8/PPPP1PPP/RNB1K1NR w KQkq - 4 4"),
+    ("Smothered mate", "6rk/6pp/7N/8/8/8/1Q6/6K1 w - - 0 1"),
+    ("Legal's mate", "r2qkb1r/pp2nppp/3p4/2pNN1B1/2BnP3/3P4/PPP2PPP/R2bK2R w KQkq - 1 1"),
+    ("Black's back rank mate", "6k1/5ppp/8/8/8/8/r4PPP/6K1 b - - 0 1"),
+];
+const PUZZLE_DEPTH: u32 = 8;
+/// Wrong tries before the solution is shown
+const PUZZLE_ATTEMPTS: u32 = 3;
+
+/// Who controls each side
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameMode {
+    HumanVsAI,
+    AIVsAI,
+    HumanVsHuman,
+    Puzzle, // Find the engine's best move in a set position
+}
+
+impl GameMode {
+    /// Choices offered in the game mode settings
+    const ALL: [GameMode; 4] = [GameMode::HumanVsAI, GameMode::AIVsAI, GameMode::HumanVsHuman, GameMode::Puzzle];
+
+    fn label(&self) -> &'static str {
+        match self {
+            GameMode::HumanVsAI => "Human vs AI",
+            GameMode::AIVsAI => "AI vs AI",
+            GameMode::HumanVsHuman => "Human vs Human",
+            GameMode::Puzzle => "Puzzle",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeControl {
+    Unlimited,
+    Classic(u64, u64), // (initial_ms, increment_ms)
+}
+
+impl TimeControl {
+    /// Choices offered in the time control settings
+    const PRESETS: [TimeControl; 6] = [
+        TimeControl::Unlimited,
+        TimeControl::Classic(60_000, 0),
+        TimeControl::Classic(180_000, 2_000),
+        TimeControl::Classic(300_000, 0),
+        TimeControl::Classic(600_000, 5_000),
+        TimeControl::Classic(900_000, 10_000),
+    ];
+
+    fn label(&self) -> String {
+        match self {
+            TimeControl::Unlimited => "Unlimited".to_string(),
+            TimeControl::Classic(initial_ms, increment_ms) => {
+                format!("{} + {}", initial_ms / 60_000, increment_ms / 1000)
+            }
+        }
+    }
+}
 
-#[derive(Default)]
 pub struct ChessApp {
     board: Board,
     selected_square: Option<Square>,
-    legal_moves: Vec<Square>,
-    ai_engine: SearchEngine,
-    ai_enabled: bool,
+    legal_moves: Vec<Move>,
+    ai_white: SearchEngine, // Each side keeps its own engine and transposition table
+    ai_black: SearchEngine,
+    game_mode: GameMode,
+    ai_paused: bool, // AI vs AI games hold between moves while set
+    ai_move_delay_ms: u64,
+    ai_difficulty: u8, // Search depth
     is_ai_thinking: bool,
     ai_move_scheduled: Option<Instant>,  
     last_ai_move: Option<Move>,
     game_over: bool,
     promotion_pending: Option<PendingPromotion>,
     show_promotion_dialog: bool,
+    show_opponent_moves: bool,
+    perft_depth: u32,
+    perft_progress: Arc<Mutex<(u64, u64)>>, // (completed root moves, total root moves)
+    perft_receiver: Option<mpsc::Receiver<(u32, u64, u128)>>, // (depth, nodes, time_ms)
+    perft_result: Option<String>,
     move_history: Vec<Move>,     
     redo_history: Vec<Move>,
     logger: Rc<RefCell<ChessLogger>>,    
+    board_flipped: bool, // Black at the bottom; the human plays Black
+    dragging: Option<(Square, u8)>, // Source square and piece of the drag in progress
+    selection_before_drag: Option<(Option<Square>, Vec<Move>)>, // Restored if the drag ends off the board
+    evaluation: i32, // Centipawns from White's point of view, shown in the eval bar
+    fen_input: String,
+    fen_error: Option<String>, // Why the last FEN in the input failed to load
+    time_control: TimeControl,
+    next_time_control: TimeControl, // Picked in the settings, applied by the next new game
+    white_time_ms: u64, // Clock time left as of `last_move_instant`
+    black_time_ms: u64,
+    last_move_instant: Instant, // When the side to move's clock started running
+    clock_paused_at: Option<Instant>, // Set while the promotion dialog is open
+    flagged: Option<u8>, // Color that ran out of time
+    show_analysis: bool,
+    analysis: Option<(u64, Vec<String>)>, // Position key and the lines found for it
+    analysis_receiver: Option<(u64, mpsc::Receiver<Vec<String>>)>, // Position key of the search still running
+    saved_games: Option<Vec<String>>, // File names listed while the Load Game window is open
+    load_error: Option<String>,
+    puzzle: Option<Puzzle>,
+    puzzle_streak: u32, // Puzzles solved in a row without a wrong try
+}
+
+/// Puzzle in progress
+struct Puzzle {
+    index: usize, // Into `PUZZLES`
+    solution: Option<Move>,
+    solver: Option<mpsc::Receiver<Option<Move>>>, // Set until the engine has found the solution
+    failed_attempts: u32,
+    solved: bool,
+    last_attempt_correct: Option<bool>,
 }
 
 #[derive(Clone, Debug)]
@@ -43,35 +156,216 @@ impl ChessApp {
         engine::bitboard::initialize_engine();
         
         let logger = Rc::new(RefCell::new(ChessLogger::new()));
-        let mut board = Board::new();
+        // Pick up the game that was in progress when the app last closed
+        let mut board = std::fs::read_to_string(LAST_GAME_PATH).ok()
+            .and_then(|json| Board::from_json(&json).ok())
+            .unwrap_or_default();
         board.set_logger(logger.clone());
+        let move_history = board.move_history.iter().map(|game_move| game_move.mv).collect();
+        let game_over = board.game_result().is_some();
         
-        let mut ai_engine = SearchEngine::new();
-        ai_engine.set_logger(logger.clone());
+        let mut ai_white = SearchEngine::new();
+        ai_white.set_logger(logger.clone());
+        let mut ai_black = SearchEngine::new();
+        ai_black.set_logger(logger.clone());
         
         // Test that logging works
         logger.borrow_mut().log("🎮 Chess application initialized successfully");
         
-        Self {
+        let mut app = Self {
             board,
             selected_square: None,
             legal_moves: Vec::new(),
-            ai_engine,
-            ai_enabled: true,
+            ai_white,
+            ai_black,
+            game_mode: GameMode::HumanVsAI,
+            ai_paused: false,
+            ai_move_delay_ms: DEFAULT_AI_MOVE_DELAY_MS,
+            ai_difficulty: 4,
             is_ai_thinking: false,
             ai_move_scheduled: None,
             last_ai_move: None,
-            game_over: false,
+            game_over,
             promotion_pending: None,
             show_promotion_dialog: false,
-            move_history: Vec::new(),
+            show_opponent_moves: false,
+            perft_depth: 4,
+            perft_progress: Arc::new(Mutex::new((0, 0))),
+            perft_receiver: None,
+            perft_result: None,
+            move_history,
             redo_history: Vec::new(),
             logger,
-        }
+            board_flipped: false,
+            dragging: None,
+            selection_before_drag: None,
+            evaluation: 0,
+            fen_input: String::new(),
+            fen_error: None,
+            time_control: TimeControl::Unlimited,
+            next_time_control: TimeControl::Unlimited,
+            white_time_ms: 0,
+            black_time_ms: 0,
+            last_move_instant: Instant::now(),
+            clock_paused_at: None,
+            flagged: None,
+            show_analysis: false,
+            analysis: None,
+            analysis_receiver: None,
+            saved_games: None,
+            load_error: None,
+            puzzle: None,
+            puzzle_streak: 0,
+        };
+        app.update_evaluation();
+        app.schedule_ai_move();
+        app
     }
     
 
 
+    /// The human plays the side at the bottom of the board
+    fn human_color(&self) -> u8 {
+        if self.board_flipped { BLACK } else { WHITE }
+    }
+
+    fn is_ai_turn(&self) -> bool {
+        match self.game_mode {
+            GameMode::HumanVsAI => self.board.current_turn != self.human_color(),
+            GameMode::AIVsAI => true,
+            GameMode::HumanVsHuman | GameMode::Puzzle => false,
+        }
+    }
+
+    /// Queue the AI's move if it has the side to move and the game isn't over or paused
+    fn schedule_ai_move(&mut self) {
+        if self.is_ai_turn() && !self.game_over && !self.is_ai_thinking && !self.ai_paused {
+            self.ai_move_scheduled = Some(Instant::now());
+        }
+    }
+
+    /// How long a scheduled AI move waits before the search starts
+    fn ai_move_delay_ms(&self) -> u64 {
+        if self.game_mode == GameMode::AIVsAI { self.ai_move_delay_ms } else { 10 }
+    }
+
+    /// Map between board squares and screen squares; flipping rotates the board 180 degrees
+    fn oriented(&self, square: Square) -> Square {
+        if self.board_flipped { Square(63 - square.0) } else { square }
+    }
+
+    /// Static evaluation of the current position for the eval bar
+    fn update_evaluation(&mut self) {
+        let eval = ai::evaluate_position(&self.board);
+        self.evaluation = if self.board.current_turn == WHITE { eval } else { -eval };
+    }
+
+    /// Time left on `color`'s clock, counting the running move
+    fn remaining_time_ms(&self, color: u8) -> u64 {
+        let banked = if color == WHITE { self.white_time_ms } else { self.black_time_ms };
+        if color != self.board.current_turn || self.game_over {
+            return banked;
+        }
+        let now = self.clock_paused_at.unwrap_or_else(Instant::now);
+        let elapsed = now.duration_since(self.last_move_instant).as_millis() as u64;
+        banked.saturating_sub(elapsed)
+    }
+
+    /// Bank the running move's time, e.g. before undo takes the turn back
+    fn settle_clock(&mut self) {
+        let side_to_move = self.board.current_turn;
+        let remaining_ms = self.remaining_time_ms(side_to_move);
+        if side_to_move == WHITE { self.white_time_ms = remaining_ms } else { self.black_time_ms = remaining_ms }
+        self.last_move_instant = Instant::now();
+    }
+
+    /// Charge the move just played to the mover's clock and start the opponent's
+    fn punch_clock(&mut self) {
+        if let TimeControl::Classic(_, increment_ms) = self.time_control {
+            // The move has been made, so the mover is the side not to move
+            let mover = opposite_color(self.board.current_turn);
+            let now = self.clock_paused_at.unwrap_or_else(Instant::now);
+            let elapsed = now.duration_since(self.last_move_instant).as_millis() as u64;
+            let clock = if mover == WHITE { &mut self.white_time_ms } else { &mut self.black_time_ms };
+            *clock = clock.saturating_sub(elapsed) + increment_ms;
+        }
+        self.last_move_instant = Instant::now();
+        self.clock_paused_at = None;
+    }
+
+    /// Stop the clock while the promotion dialog is up and end the game when a flag falls
+    fn update_clock(&mut self, ctx: &egui::Context) {
+        match (self.show_promotion_dialog, self.clock_paused_at) {
+            (true, None) => self.clock_paused_at = Some(Instant::now()),
+            (false, Some(paused_at)) => {
+                self.last_move_instant += paused_at.elapsed();
+                self.clock_paused_at = None;
+            }
+            _ => {}
+        }
+
+        if self.time_control == TimeControl::Unlimited || self.game_over || self.clock_paused_at.is_some() {
+            return;
+        }
+
+        let side_to_move = self.board.current_turn;
+        if self.remaining_time_ms(side_to_move) == 0 {
+            self.flag(side_to_move);
+        } else {
+            ctx.request_repaint_after(std::time::Duration::from_millis(100));
+        }
+    }
+
+    fn flag(&mut self, color: u8) {
+        if color == WHITE { self.white_time_ms = 0 } else { self.black_time_ms = 0 }
+        self.flagged = Some(color);
+        self.game_over = true;
+        self.ai_move_scheduled = None;
+
+        let result = self.game_over_text().unwrap_or_default();
+        self.logger.borrow_mut().log(&format!("🏁 Game Over: {}", result));
+        if let Ok(filename) = self.logger.borrow_mut().save_to_file(&result) {
+            println!("✅ Game log saved to: {}", filename);
+        }
+    }
+
+    /// Result of the game, including losses on time
+    fn outcome(&self) -> GameResult {
+        match self.flagged {
+            Some(WHITE) => GameResult::BlackWins,
+            Some(_) => GameResult::WhiteWins,
+            None => self.board.get_game_result(),
+        }
+    }
+
+    fn game_over_text(&self) -> Option<String> {
+        match self.flagged {
+            Some(WHITE) => Some("White ran out of time - Black wins!".to_string()),
+            Some(_) => Some("Black ran out of time - White wins!".to_string()),
+            None => self.board.game_result().map(game_result_text),
+        }
+    }
+
+    /// One player's clock in large text, red when low and bold while running
+    fn draw_clock(&self, ui: &mut egui::Ui, color: u8) {
+        if self.time_control == TimeControl::Unlimited {
+            return;
+        }
+
+        let remaining_ms = self.remaining_time_ms(color);
+        let name = if color == WHITE { "White" } else { "Black" };
+        let mut text = egui::RichText::new(format!("{}  {}", name, format_clock(remaining_ms)))
+            .size(28.0)
+            .monospace();
+        if remaining_ms < LOW_TIME_MS {
+            text = text.color(Color32::RED);
+        }
+        if color == self.board.current_turn && !self.game_over {
+            text = text.strong();
+        }
+        ui.label(text);
+    }
+
     fn is_ai_last_move_square(&self, square: Square) -> bool {
         if let Some(last_move) = self.last_ai_move {
             square == last_move.from || square == last_move.to
@@ -83,8 +377,17 @@ impl ChessApp {
 
 impl eframe::App for ChessApp {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        self.update_clock(ctx);
+
+        if ctx.input(|i| i.key_pressed(egui::Key::Z) && i.modifiers.ctrl) && self.can_undo() {
+            self.take_back();
+        }
+
+        // Side panels have to be added before the central panel
+        self.show_move_list(ctx);
+
         egui::CentralPanel::default().show(ctx, |ui| {
-            ui.heading("Chess Engine - Human vs AI");
+            ui.heading(format!("Chess Engine - {}", self.game_mode.label()));
             
             // Status display
             ui.horizontal(|ui| {
@@ -100,7 +403,10 @@ impl eframe::App for ChessApp {
 
                 let current_player = if self.board.current_turn == WHITE { "White" } else { "Black" };
                 let status = if self.game_over {
-                    "Game Over".to_string()
+                    match self.game_over_text() {
+                        Some(result) => format!("Game Over - {}", result),
+                        None => "Game Over".to_string(),
+                    }
                 } else {
                     format!("{}'s turn", current_player)
                 };
@@ -110,6 +416,11 @@ impl eframe::App for ChessApp {
                 if !self.game_over && self.board.is_in_check() {
                     ui.colored_label(Color32::RED, "CHECK!");
                 }
+
+                if !self.game_over && self.board.fifty_move_warning() {
+                    ui.colored_label(Color32::YELLOW, format!("50-move draw approaching ({} moves left)",
+                                                              self.board.moves_until_fifty_draw()));
+                }
                 
                 // Add some spacing
                 ui.add_space(20.0);
@@ -126,45 +437,56 @@ impl eframe::App for ChessApp {
                 // Push New Game button to the right
                 ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
                     if ui.button("New Game").clicked() {
-                        // Save current game log
-                        if let Ok(filename) = self.logger.borrow_mut().save_to_file("New game button pressed") {
-                            println!("Game log saved to: {}", filename);
-                        }
-                        
-                        // Create new logger
-                        let new_logger = std::rc::Rc::new(std::cell::RefCell::new(engine::ChessLogger::new()));
-                        
-                        // Reset everything
-                        let mut new_board = Board::new();
-                        new_board.set_logger(new_logger.clone());
-                        
-                        self.board = new_board;
-                        self.ai_engine.set_logger(new_logger.clone());
-                        self.selected_square = None;
-                        self.legal_moves.clear();
-                        self.last_ai_move = None;
-                        self.game_over = false;
-                        self.is_ai_thinking = false;
-                        self.ai_move_scheduled = None;
-                        self.promotion_pending = None;
-                        self.show_promotion_dialog = false;
-                        self.move_history.clear();
-                        self.redo_history.clear();
-                        
-                        // Update logger reference
-                        self.logger = new_logger;
+                        self.start_new_game(Board::new(), "New game button pressed");
                     }
                     
                     
+                    if ui.button("Flip Board").clicked() {
+                        self.board_flipped = !self.board_flipped;
+                        self.selected_square = None;
+                        self.legal_moves.clear();
+                        // The AI takes over the side the human just gave up
+                        self.schedule_ai_move();
+                    }
+
+                    if self.game_mode == GameMode::AIVsAI && !self.game_over {
+                        let label = if self.ai_paused { "Resume" } else { "Pause" };
+                        if ui.button(label).clicked() {
+                            self.ai_paused = !self.ai_paused;
+                            if self.ai_paused {
+                                self.ai_move_scheduled = None;
+                            } else {
+                                self.schedule_ai_move();
+                            }
+                        }
+                    }
+
+                    if ui.button("Load Game").clicked() {
+                        self.open_load_dialog();
+                    }
+
+                    if ui.button("Save Game").clicked() {
+                        match self.save_game() {
+                            Ok(filename) => println!("Game saved to: {}", filename),
+                            Err(e) => println!("Saving the game failed: {}", e),
+                        }
+                    }
+
+                    if ui.button("Export PGN").clicked() {
+                        match self.export_pgn() {
+                            Ok(filename) => println!("PGN saved to: {}", filename),
+                            Err(e) => println!("PGN export failed: {}", e),
+                        }
+                    }
 
                     // ADD: Redo button
                     if ui.add_enabled(self.can_redo(), egui::Button::new("Redo")).clicked() {
                         self.redo_move();
                     }
                     
-                    // ADD: Undo button  
+                    // Takes back the AI's reply together with the move before it
                     if ui.add_enabled(self.can_undo(), egui::Button::new("Undo")).clicked() {
-                        self.undo_move();
+                        self.take_back();
                     }
                 });
             });
@@ -181,53 +503,162 @@ impl eframe::App for ChessApp {
                 ui.separator();
                 
                 ui.label(format!("Moves logged: {}", self.move_history.len()));
+
+                ui.separator();
+
+                ui.checkbox(&mut self.show_opponent_moves, "Show opponent's moves");
+                ui.checkbox(&mut self.show_analysis, "Analysis");
+
+                ui.separator();
+
+                let previous_mode = self.game_mode;
+                egui::ComboBox::from_label("Mode")
+                    .selected_text(self.game_mode.label())
+                    .show_ui(ui, |ui| {
+                        for mode in GameMode::ALL {
+                            ui.selectable_value(&mut self.game_mode, mode, mode.label());
+                        }
+                    });
+                if self.game_mode != previous_mode {
+                    if self.game_mode == GameMode::Puzzle {
+                        self.next_puzzle();
+                    } else {
+                        self.puzzle = None;
+                    }
+
+                    // The new mode takes over from the current position
+                    self.ai_paused = false;
+                    self.ai_move_scheduled = None;
+                    self.selected_square = None;
+                    self.legal_moves.clear();
+                    self.schedule_ai_move();
+                }
+
+                match self.game_mode {
+                    GameMode::HumanVsAI => {
+                        let human_side = if self.human_color() == WHITE { "White" } else { "Black" };
+                        ui.label(format!("You play: {}", human_side));
+                    }
+                    GameMode::AIVsAI => {
+                        ui.add(egui::Slider::new(&mut self.ai_move_delay_ms, 0..=MAX_AI_MOVE_DELAY_MS)
+                            .text("Move delay (ms)"));
+                    }
+                    GameMode::HumanVsHuman => {}
+                    GameMode::Puzzle => self.show_puzzle_status(ui),
+                }
+
+                ui.separator();
+
+                egui::ComboBox::from_label("Time control (next game)")
+                    .selected_text(self.next_time_control.label())
+                    .show_ui(ui, |ui| {
+                        for preset in TimeControl::PRESETS {
+                            ui.selectable_value(&mut self.next_time_control, preset, preset.label());
+                        }
+                    });
             });
 
+            // The clock of the side at the top of the board
+            let (top_color, bottom_color) = if self.board_flipped { (WHITE, BLACK) } else { (BLACK, WHITE) };
+            self.draw_clock(ui, top_color);
+
+            let clock_space = if self.time_control == TimeControl::Unlimited { 0.0 } else { 80.0 };
             let available_size = ui.available_size();
-            let board_size = (available_size.x.min(available_size.y) - 80.0).max(400.0);
+            let board_size = (available_size.x.min(available_size.y) - 80.0 - clock_space).max(400.0);
             let square_size = board_size / 8.0;
 
+            // Room for the rank labels left of the board
             let board_rect = Rect::from_min_size(
-                ui.cursor().min,
+                ui.cursor().min + Vec2::new(square_size * 0.3, 0.0),
                 Vec2::splat(board_size),
             );
 
-            let response = ui.allocate_rect(board_rect, Sense::click());
+            let response = ui.allocate_rect(board_rect, Sense::click_and_drag());
+            let can_move = !self.is_ai_thinking && self.ai_move_scheduled.is_none() && !self.is_ai_turn();
 
             // Handle clicks
-            if response.clicked() && !self.is_ai_thinking && self.ai_move_scheduled.is_none() &&
-               (self.board.current_turn == WHITE || !self.ai_enabled) {
+            if response.clicked() && can_move {
                 if let Some(pos) = response.interact_pointer_pos() {
-                    let relative_pos = pos - board_rect.min;
-                    if let Some(clicked_square) = Square::from_coords(
-                        relative_pos.x,
-                        relative_pos.y,
-                        square_size,
-                    ) {
+                    if let Some(clicked_square) = self.square_at(board_rect, square_size, pos) {
                         self.handle_square_click(clicked_square);
                     }
                 }
             }
 
+            // Drag and drop: picking a piece up selects it, dropping it plays the move
+            if response.drag_started() && can_move {
+                // The press origin is where the piece was grabbed, before the pointer moved off it
+                if let Some(pos) = ctx.input(|input| input.pointer.press_origin()) {
+                    if let Some(source) = self.square_at(board_rect, square_size, pos) {
+                        self.start_drag(source);
+                    }
+                }
+            }
+
+            if response.drag_stopped() {
+                if let Some((source, _)) = self.dragging.take() {
+                    let restore = self.selection_before_drag.take();
+                    match response.interact_pointer_pos().and_then(|pos| self.square_at(board_rect, square_size, pos)) {
+                        Some(target) if target != source => self.handle_square_click(target),
+                        Some(_) => {} // Dropped back where it started: stays selected
+                        None => {
+                            if let Some((selected_square, legal_moves)) = restore {
+                                self.selected_square = selected_square;
+                                self.legal_moves = legal_moves;
+                            }
+                        }
+                    }
+                }
+            }
+
             // Draw the board
             self.draw_board(ui, board_rect, square_size);
+
+            // Leave room for the file labels under the board
+            ui.add_space(30.0);
+            self.draw_clock(ui, bottom_color);
+            ui.horizontal(|ui| {
+                ui.label("Load FEN:");
+                let input = ui.add(egui::TextEdit::singleline(&mut self.fen_input).desired_width(420.0));
+                let enter_pressed = input.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                if ui.button("Load").clicked() || enter_pressed {
+                    self.load_fen_input();
+                }
+
+                if ui.button("Starting Position").clicked() {
+                    self.fen_error = None;
+                    self.start_new_game(Board::new(), "Starting position loaded");
+                }
+
+                if ui.button("Copy FEN").clicked() {
+                    let fen = self.board.to_fen();
+                    ctx.output_mut(|o| o.copied_text = fen);
+                }
+
+                if let Some(error) = &self.fen_error {
+                    ui.colored_label(Color32::RED, error);
+                }
+            });
         });
         
         // Handle AI move timing outside the panel
         if let Some(scheduled_time) = self.ai_move_scheduled {
-            let elapsed = scheduled_time.elapsed();
-            if elapsed.as_millis() >= 10 { // 500ms delay
+            let elapsed = scheduled_time.elapsed().as_millis() as u64;
+            let delay_ms = self.ai_move_delay_ms();
+            if elapsed >= delay_ms {
                 self.ai_move_scheduled = None;
                 if !self.game_over && !self.is_ai_thinking {
                     self.trigger_ai_move();
                 }
             } else {
                 // Use egui's proper timing system to schedule the next check
-                let remaining = 10 - elapsed.as_millis() as u64;
-                ctx.request_repaint_after(std::time::Duration::from_millis(remaining.min(10)));
+                ctx.request_repaint_after(std::time::Duration::from_millis(delay_ms - elapsed));
             }
         }
         self.show_promotion_dialog(ctx);
+        self.show_perft_panel(ctx);
+        self.show_load_dialog(ctx);
+        self.poll_puzzle_solver(ctx);
     }
 
     // ADD this new method to handle app shutdown
@@ -254,38 +685,42 @@ impl ChessApp {
             return;
         }
         
-        // Only allow human moves on White's turn
-        if self.board.current_turn == BLACK && self.ai_enabled {
+        // Only allow human moves on the human's turn
+        if self.is_ai_turn() {
+            return;
+        }
+
+        // Puzzles wait for the engine's solution and end once solved
+        if self.puzzle.as_ref().is_some_and(|puzzle| puzzle.solved || puzzle.solution.is_none()) {
             return;
         }
         
         if let Some(selected) = self.selected_square {
+            let candidate_moves: Vec<Move> = self.legal_moves.iter()
+                .copied()
+                .filter(|mv| mv.to == clicked_square)
+                .collect();
+
             if selected == clicked_square {
                 self.selected_square = None;
                 self.legal_moves.clear();
-            } else if self.legal_moves.contains(&clicked_square) {
-                let piece = self.board.get_piece(selected);
-                let piece_type_val = piece_type(piece);
-                let piece_color_val = piece_color(piece);
-                
-                // Check if this is a pawn promotion move
-                if piece_type_val == PAWN {
-                    let promotion_rank = if piece_color_val == WHITE { 7 } else { 0 };
-                    
-                    if clicked_square.rank() == promotion_rank {
-                        // This is a promotion move - show dialog instead of executing immediately
-                        self.promotion_pending = Some(PendingPromotion {
-                            from_square: selected,
-                            to_square: clicked_square,
-                            player_color: piece_color_val,
-                        });
-                        self.show_promotion_dialog = true;
-                        self.selected_square = None;
-                        self.legal_moves.clear();
-                        return; // Don't execute the move yet
-                    }
+            } else if candidate_moves.iter().any(|mv| mv.is_promotion()) {
+                // Promotion move - show dialog instead of executing immediately
+                self.promotion_pending = Some(PendingPromotion {
+                    from_square: selected,
+                    to_square: clicked_square,
+                    player_color: piece_color(self.board.get_piece(selected)),
+                });
+                self.show_promotion_dialog = true;
+                self.selected_square = None;
+                self.legal_moves.clear();
+            } else if let Some(&mv) = candidate_moves.first() {
+                if !self.accept_puzzle_attempt(mv) {
+                    self.selected_square = None;
+                    self.legal_moves.clear();
+                    return;
                 }
-                let mv = Move::new(selected, clicked_square);
+
                 let start_time = std::time::Instant::now();
                 if self.board.try_make_move(mv).is_ok() {
                     let move_time = start_time.elapsed().as_millis() as u64;
@@ -295,21 +730,22 @@ impl ChessApp {
 
                     self.move_history.push(mv);
                     self.redo_history.clear();
+                    self.punch_clock();
+                    self.save_last_game();
 
                     self.selected_square = None;
                     self.legal_moves.clear();
-                    
-                    // Schedule AI move with proper timing
-                    if self.board.current_turn == BLACK && self.ai_enabled {
-                        self.ai_move_scheduled = Some(Instant::now());
-                    }
+                    self.update_evaluation();
                     
                     self.check_game_over();
+
+                    // Schedule AI move with proper timing
+                    self.schedule_ai_move();
                 }
             } else if !is_empty(self.board.get_piece(clicked_square)) && 
                      piece_color(self.board.get_piece(clicked_square)) == self.board.current_turn {
                 self.selected_square = Some(clicked_square);
-                self.legal_moves = self.board.get_legal_moves(clicked_square);
+                self.legal_moves = self.board.legal_moves_from(clicked_square);
             } else {
                 self.selected_square = None;
                 self.legal_moves.clear();
@@ -317,9 +753,38 @@ impl ChessApp {
         } else if !is_empty(self.board.get_piece(clicked_square)) && 
                  piece_color(self.board.get_piece(clicked_square)) == self.board.current_turn {
             self.selected_square = Some(clicked_square);
-            self.legal_moves = self.board.get_legal_moves(clicked_square);
+            self.legal_moves = self.board.legal_moves_from(clicked_square);
         }
     } 
+
+    /// Board square under a screen position, if it is on the board
+    fn square_at(&self, board_rect: Rect, square_size: f32, pos: egui::Pos2) -> Option<Square> {
+        if !board_rect.contains(pos) {
+            return None;
+        }
+        let relative_pos = pos - board_rect.min;
+        Square::from_coords(relative_pos.x, relative_pos.y, square_size).map(|square| self.oriented(square))
+    }
+
+    fn start_drag(&mut self, source: Square) {
+        if self.game_over {
+            return;
+        }
+
+        let piece = self.board.get_piece(source);
+        if is_empty(piece) || piece_color(piece) != self.board.current_turn {
+            return;
+        }
+
+        self.selection_before_drag = Some((self.selected_square, self.legal_moves.clone()));
+        self.selected_square = Some(source);
+        self.legal_moves = self.board.legal_moves_from(source);
+        self.dragging = Some((source, piece));
+    }
+
+    fn is_legal_move_target(&self, square: Square) -> bool {
+        self.legal_moves.iter().any(|mv| mv.to == square)
+    }
     
     fn trigger_ai_move(&mut self) {
         if self.game_over {
@@ -328,57 +793,94 @@ impl ChessApp {
     
         self.is_ai_thinking = true;
         let start_time = std::time::Instant::now();
-        let result = self.ai_engine.search(&mut self.board, 4);
+        let ai_color = self.board.current_turn;
+        let best_move = match self.time_control {
+            TimeControl::Unlimited => self.board.best_move_candidate(self.ai_difficulty),
+            TimeControl::Classic(_, increment_ms) => {
+                // Thinking time comes off the AI's own clock, and each side's engine keeps its
+                // transposition table between timed moves
+                let remaining_ms = self.remaining_time_ms(ai_color);
+                let budget_ms = (remaining_ms / AI_MOVES_TO_GO + increment_ms).min(remaining_ms / 2);
+                let engine = if ai_color == WHITE { &mut self.ai_white } else { &mut self.ai_black };
+                engine.search_timed(&mut self.board, budget_ms, 0).best_move
+            }
+        };
         let search_time = start_time.elapsed().as_millis() as u64;
+        self.is_ai_thinking = false;
+
+        if self.time_control != TimeControl::Unlimited && self.remaining_time_ms(ai_color) == 0 {
+            self.flag(ai_color);
+            return;
+        }
     
-        if let Some(ai_move) = result.best_move {
+        if let Some(ai_move) = best_move {
             if self.board.try_make_move(ai_move).is_ok() {
-                // LOG: AI move
-                self.logger.borrow_mut().log_ai_move(ai_move, search_time, result.evaluation);
+                self.punch_clock();
+                self.update_evaluation();
+                // The log scores the move from the AI's side
+                let evaluation = if ai_color == WHITE { self.evaluation } else { -self.evaluation };
+
+                // LOG: AI move, naming the engine when both sides are played by one
+                if self.game_mode == GameMode::AIVsAI {
+                    self.logger.borrow_mut().log_engine_move(ai_color, ai_move, search_time, evaluation);
+                } else {
+                    self.logger.borrow_mut().log_ai_move(ai_move, search_time, evaluation);
+                }
                 
                 // Track AI move in history and clear redo stack
                 self.move_history.push(ai_move);
                 self.redo_history.clear();
+                self.save_last_game();
                 
                 self.last_ai_move = Some(ai_move);
             }
         }
-        self.is_ai_thinking = false;
         self.check_game_over();
+
+        // In AI vs AI the other engine replies next
+        self.schedule_ai_move();
     }
     
     
     
     fn check_game_over(&mut self) {
-        let legal_moves = self.board.get_all_legal_moves();
-        if legal_moves.is_empty() {
-            self.game_over = true;
-            
-            // Determine game result and save log
-            let result = if self.board.is_in_check() {
-                let winner = if self.board.current_turn == engine::WHITE { "Black" } else { "White" };
-                format!("Checkmate - {} wins!", winner)
-            } else {
-                "Stalemate - Draw".to_string()
-            };
-            
-            self.logger.borrow_mut().log(&format!("🏁 Game Over: {}", result));
-            
-            // Save game log
-            if let Ok(filename) = self.logger.borrow_mut().save_to_file(&result) {
-                println!("✅ Game log saved to: {}", filename);
-            }
+        let game_result = self.board.get_game_result();
+        if game_result == GameResult::InProgress {
+            return;
+        }
+        self.game_over = true;
+
+        // Determine game result and save log
+        let result = game_result_text(game_result);
+
+        self.logger.borrow_mut().log(&format!("🏁 Game Over: {}", result));
+
+        // Save game log
+        if let Ok(filename) = self.logger.borrow_mut().save_to_file(&result) {
+            println!("✅ Game log saved to: {}", filename);
         }
     }
     
     
     fn draw_board(&self, ui: &mut egui::Ui, board_rect: Rect, square_size: f32) {
         let painter = ui.painter();
+
+        // Squares the side not to move could reach if it were their turn
+        let opponent_targets: Vec<Square> = if self.show_opponent_moves {
+            self.board.with_flipped_turn()
+                .get_all_legal_moves()
+                .iter()
+                .map(|mv| mv.to)
+                .collect()
+        } else {
+            Vec::new()
+        };
         
         // Draw squares
         for rank in 0..8 {
             for file in 0..8 {
-                let square = Square::new(file, rank);
+                // (file, rank) is the screen position; `square` is what sits there
+                let square = self.oriented(Square::new(file, rank));
                 let is_light = (file + rank) % 2 == 0;
                 let square_rect = Rect::from_min_size(
                     board_rect.min + Vec2::new(file as f32 * square_size, (7 - rank) as f32 * square_size),
@@ -408,8 +910,13 @@ impl ChessApp {
 
                 painter.rect_filled(square_rect, 0.0, square_color);
 
+                // Outline squares reachable by the opponent
+                if opponent_targets.contains(&square) {
+                    painter.rect_stroke(square_rect.shrink(2.0), 0.0, egui::Stroke::new(2.0, Color32::from_rgb(200, 60, 60)));
+                }
+
                 // Draw legal move indicators (same as before)
-                if self.legal_moves.contains(&square) {
+                if self.is_legal_move_target(square) {
                     let center = square_rect.center();
                     if !is_empty(self.board.get_piece(square)) {
                         // Capture square - draw donut
@@ -424,26 +931,43 @@ impl ChessApp {
                     }
                 }
 
-                // Draw piece
+                // Draw piece, as a faint ghost while it is being dragged
                 let piece = self.board.get_piece(square);
                 if !is_empty(piece) {
-                    self.draw_piece(painter, piece, square_rect);
+                    let is_drag_source = self.dragging.is_some_and(|(source, _)| source == square);
+                    self.draw_piece(painter, piece, square_rect, if is_drag_source { 0.3 } else { 1.0 });
                 }
             }
         }
 
+        // The dragged piece follows the cursor
+        if let (Some((_, piece)), Some(pointer)) = (self.dragging, ui.ctx().pointer_hover_pos()) {
+            let drag_rect = Rect::from_center_size(pointer, Vec2::splat(square_size));
+            self.draw_piece(painter, piece, drag_rect, 0.7);
+        }
+
         // Draw board border
         painter.rect_stroke(board_rect, 0.0, egui::Stroke::new(2.0, Color32::BLACK));
 
-        // Draw coordinate labels  
-        let label_font = egui::FontId::new(16.0, egui::FontFamily::Monospace);
-        let label_color = Color32::DARK_GRAY;
+        self.draw_eval_bar(painter, board_rect);
 
+        // Puzzle solution, once the tries have run out
+        let revealed = self.puzzle.as_ref()
+            .filter(|puzzle| puzzle.failed_attempts >= PUZZLE_ATTEMPTS && !puzzle.solved)
+            .and_then(|puzzle| puzzle.solution);
+        if let Some(solution) = revealed {
+            self.draw_arrow(painter, board_rect, square_size, solution, Color32::from_rgba_unmultiplied(40, 160, 40, 200));
+        }
+
+        // Draw coordinate labels, scaled with the board
+        let label_font = egui::FontId::proportional(square_size * 0.2);
+        let label_color = Color32::from_rgb(160, 100, 60);
 
         // Draw file labels (a-h) at the bottom
         for (file, &file_char) in FILES.iter().enumerate() {
-            let x = board_rect.min.x + (file as f32 * square_size) + (square_size / 2.0);
-            let y = board_rect.max.y + 8.0;
+            let column = if self.board_flipped { 7 - file } else { file };
+            let x = board_rect.min.x + (column as f32 * square_size) + (square_size / 2.0);
+            let y = board_rect.max.y + 4.0;
             
             painter.text(
                 egui::Pos2::new(x, y),
@@ -454,14 +978,15 @@ impl ChessApp {
             );
         }
 
-        // Draw rank labels (1-8) at the right edge  
+        // Draw rank labels (1-8) at the left edge
         for (rank_index, &rank_char) in RANKS.iter().enumerate() {
-            let x = board_rect.max.x + 8.0;
-            let y = board_rect.min.y + ((7 - rank_index) as f32 * square_size) + (square_size / 2.0);
+            let x = board_rect.min.x - 4.0;
+            let row = if self.board_flipped { rank_index } else { 7 - rank_index };
+            let y = board_rect.min.y + (row as f32 * square_size) + (square_size / 2.0);
             
             painter.text(
                 egui::Pos2::new(x, y),
-                egui::Align2::LEFT_CENTER,
+                egui::Align2::RIGHT_CENTER,
                 rank_char.to_string(),
                 label_font.clone(),
                 label_color,
@@ -470,7 +995,55 @@ impl ChessApp {
 
     }
     
-    fn draw_piece(&self, painter: &egui::Painter, piece: u8, square_rect: Rect) {
+    /// Arrow from the center of the move's source square to the center of its target
+    fn draw_arrow(&self, painter: &egui::Painter, board_rect: Rect, square_size: f32, mv: Move, color: Color32) {
+        let center = |square: Square| {
+            let screen = self.oriented(square);
+            board_rect.min + Vec2::new(
+                (screen.file() as f32 + 0.5) * square_size,
+                (7.5 - screen.rank() as f32) * square_size,
+            )
+        };
+        let origin = center(mv.from);
+        painter.arrow(origin, center(mv.to) - origin, egui::Stroke::new(square_size * 0.12, color));
+    }
+
+    /// Vertical bar right of the board, White's share growing from White's side of the board
+    fn draw_eval_bar(&self, painter: &egui::Painter, board_rect: Rect) {
+        const LABEL_HEIGHT: f32 = 20.0;
+        let bar_rect = Rect::from_min_max(
+            egui::Pos2::new(board_rect.max.x + 28.0, board_rect.min.y + LABEL_HEIGHT),
+            egui::Pos2::new(board_rect.max.x + 48.0, board_rect.max.y),
+        );
+
+        let outcome = if self.game_over { self.outcome() } else { GameResult::InProgress };
+        let (white_share, label) = match outcome {
+            GameResult::WhiteWins => (1.0, "1-0".to_string()),
+            GameResult::BlackWins => (0.0, "0-1".to_string()),
+            GameResult::Draw(_) => (0.5, "½-½".to_string()),
+            GameResult::InProgress => (1.0 / (1.0 + (-self.evaluation as f32 / 400.0).exp()), format_evaluation(self.evaluation)),
+        };
+
+        painter.rect_filled(bar_rect, 0.0, Color32::from_gray(30));
+        let white_height = bar_rect.height() * white_share;
+        let white_rect = if self.board_flipped {
+            Rect::from_min_size(bar_rect.min, Vec2::new(bar_rect.width(), white_height))
+        } else {
+            Rect::from_min_max(egui::Pos2::new(bar_rect.min.x, bar_rect.max.y - white_height), bar_rect.max)
+        };
+        painter.rect_filled(white_rect, 0.0, Color32::from_gray(235));
+        painter.rect_stroke(bar_rect, 0.0, egui::Stroke::new(1.0, Color32::BLACK));
+
+        painter.text(
+            egui::Pos2::new(bar_rect.center().x, board_rect.min.y),
+            egui::Align2::CENTER_TOP,
+            label,
+            egui::FontId::new(12.0, egui::FontFamily::Monospace),
+            Color32::DARK_GRAY,
+        );
+    }
+
+    fn draw_piece(&self, painter: &egui::Painter, piece: u8, square_rect: Rect, opacity: f32) {
         let center = square_rect.center();
         let size = square_rect.size() * 0.8;
         
@@ -495,10 +1068,241 @@ impl ChessApp {
             egui::Align2::CENTER_CENTER,
             piece_char,
             egui::FontId::proportional(size.x),
-            Color32::BLACK,
+            Color32::BLACK.gamma_multiply(opacity),
         );
     }
 
+    /// Scrollable list of the game's moves in SAN; clicking one jumps to the position after it
+    fn show_move_list(&mut self, ctx: &egui::Context) {
+        let (start_turn, start_move_number, sans) = self.move_list_san();
+        let current_ply = self.move_history.len();
+        let can_jump = !self.is_ai_thinking && self.ai_move_scheduled.is_none();
+        let mut jump_to = None;
+        if self.show_analysis && can_jump {
+            self.update_analysis(ctx);
+        }
+        let analysing = self.analysis_receiver.is_some();
+
+        egui::SidePanel::right("move_list").resizable(false).min_width(180.0).show(ctx, |ui| {
+            if self.show_analysis {
+                ui.heading("Analysis");
+                ui.separator();
+                match &self.analysis {
+                    _ if analysing => { ui.label("Analysing..."); }
+                    Some((_, lines)) if !lines.is_empty() => {
+                        for line in lines {
+                            ui.label(line);
+                        }
+                    }
+                    _ => { ui.label("No moves to analyse"); }
+                }
+                ui.separator();
+            }
+
+            ui.heading("Moves");
+            ui.separator();
+
+            egui::ScrollArea::vertical().auto_shrink([false, false]).show(ui, |ui| {
+                if ui.selectable_label(current_ply == 0, "Start").clicked() && can_jump {
+                    jump_to = Some(0);
+                }
+
+                // A game starting with Black to move opens with "1... e5"
+                let offset = if start_turn == BLACK { 1 } else { 0 };
+                let mut ply = 0;
+                while ply < sans.len() {
+                    let line_start = ply;
+                    ui.horizontal(|ui| {
+                        let move_number = start_move_number as usize + (ply + offset) / 2;
+                        if (ply + offset) % 2 == 1 {
+                            ui.label(format!("{}...", move_number));
+                        } else {
+                            ui.label(format!("{}.", move_number));
+                        }
+
+                        // White's move and Black's reply share a line
+                        while ply < sans.len() && (ply == line_start || (ply + offset) % 2 == 1) {
+                            if ui.selectable_label(current_ply == ply + 1, &sans[ply]).clicked() && can_jump {
+                                jump_to = Some(ply + 1);
+                            }
+                            ply += 1;
+                        }
+                    });
+                }
+            });
+        });
+
+        if let Some(target) = jump_to {
+            self.jump_to_ply(target);
+        }
+    }
+
+    /// Keep the analysis lines for the current position, searching on a background thread
+    /// and picking up the result once it is done
+    fn update_analysis(&mut self, ctx: &egui::Context) {
+        let key = self.board.position_key();
+        if let Some((analysing, receiver)) = &self.analysis_receiver {
+            match receiver.try_recv() {
+                Ok(lines) => {
+                    self.analysis = Some((*analysing, lines));
+                    self.analysis_receiver = None;
+                }
+                // A search for a position we've since left is dropped and runs out on its own
+                Err(mpsc::TryRecvError::Empty) if *analysing == key => {
+                    ctx.request_repaint_after(std::time::Duration::from_millis(100));
+                    return;
+                }
+                Err(_) => self.analysis_receiver = None,
+            }
+        }
+        if self.analysis.as_ref().is_some_and(|(analysed, _)| *analysed == key) {
+            return;
+        }
+
+        // Board holds an Rc logger, so the thread gets its own copy through JSON, history included
+        let json = self.board.to_json();
+        let (sender, receiver) = mpsc::channel();
+        std::thread::spawn(move || {
+            let Ok(mut board) = Board::from_json(&json) else {
+                return;
+            };
+            let results = SearchEngine::new().search_multi_pv(&mut board, ANALYSIS_DEPTH, ANALYSIS_LINES);
+
+            // "+0.3 e4 e5 Nf3", scored from White's point of view like the eval bar
+            let lines = results.iter().map(|result| {
+                let evaluation = if board.current_turn == WHITE { result.evaluation } else { -result.evaluation };
+                let mut replay = board.clone();
+                let mut sans = Vec::new();
+                for &mv in &result.pv_line {
+                    sans.push(replay.move_to_san(mv));
+                    if replay.try_make_move(mv).is_err() {
+                        break;
+                    }
+                }
+                format!("{} {}", format_evaluation(evaluation), sans.join(" "))
+            }).collect();
+            let _ = sender.send(lines);
+        });
+        self.analysis_receiver = Some((key, receiver));
+        ctx.request_repaint_after(std::time::Duration::from_millis(100));
+    }
+
+    /// SAN for every move in the game, including undone moves that can still be redone,
+    /// along with the side to move and move number of the starting position
+    fn move_list_san(&self) -> (u8, u16, Vec<String>) {
+        let mut replay = self.board.clone();
+        replay.logger = None;
+        if replay.goto_move(0).is_err() {
+            return (WHITE, 1, Vec::new());
+        }
+        let start = (replay.current_turn, replay.full_move_number);
+
+        let mut sans = Vec::new();
+        for &mv in self.move_history.iter().chain(self.redo_history.iter().rev()) {
+            sans.push(replay.move_to_san(mv));
+            if replay.try_make_move(mv).is_err() {
+                break;
+            }
+        }
+
+        (start.0, start.1, sans)
+    }
+
+    /// Show the position after `ply` half-moves, keeping later moves on the redo stack
+    fn jump_to_ply(&mut self, ply: usize) {
+        self.settle_clock();
+        if ply < self.move_history.len() {
+            if let Ok(undone) = self.board.goto_move(ply) {
+                self.move_history.truncate(ply);
+                self.redo_history.extend(undone.iter().rev().map(|game_move| game_move.mv));
+            }
+        } else {
+            while self.move_history.len() < ply && self.can_redo() {
+                self.redo_move();
+            }
+        }
+
+        self.selected_square = None;
+        self.legal_moves.clear();
+        self.last_ai_move = None;
+        self.flagged = None;
+        self.game_over = self.board.game_result().is_some();
+        self.update_evaluation();
+    }
+
+    // Debug panel, only shown with advanced logging enabled
+    fn show_perft_panel(&mut self, ctx: &egui::Context) {
+        if !self.logger.borrow().advanced_logging {
+            return;
+        }
+
+        // Pick up a finished run
+        if let Some(receiver) = &self.perft_receiver {
+            match receiver.try_recv() {
+                Ok((depth, nodes, time_ms)) => {
+                    let nps = (nodes as u128 * 1000).checked_div(time_ms).unwrap_or(0);
+                    self.perft_result = Some(format!("Depth {}: {} nodes in {} ms ({} nps)", depth, nodes, time_ms, nps));
+                    self.perft_receiver = None;
+                }
+                Err(mpsc::TryRecvError::Empty) => ctx.request_repaint(),
+                Err(mpsc::TryRecvError::Disconnected) => {
+                    self.perft_result = Some("Perft thread stopped unexpectedly".to_string());
+                    self.perft_receiver = None;
+                }
+            }
+        }
+
+        let running = self.perft_receiver.is_some();
+        let (completed, total) = *self.perft_progress.lock().unwrap();
+
+        egui::Window::new("Perft")
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("Depth:");
+                    ui.add_enabled(!running, egui::Slider::new(&mut self.perft_depth, 1..=6));
+
+                    if ui.add_enabled(!running, egui::Button::new("Start")).clicked() {
+                        self.start_perft();
+                    }
+                });
+
+                if running {
+                    let fraction = if total > 0 { completed as f32 / total as f32 } else { 0.0 };
+                    ui.add(egui::ProgressBar::new(fraction)
+                        .text(format!("Processing move {}/{}", completed, total)));
+                } else if let Some(result) = &self.perft_result {
+                    ui.label(result);
+                }
+            });
+    }
+
+    fn start_perft(&mut self) {
+        // Board holds an Rc logger, so the thread rebuilds the position from FEN
+        let fen = self.board.to_fen();
+        let depth = self.perft_depth;
+        let progress = self.perft_progress.clone();
+        let (sender, receiver) = mpsc::channel();
+
+        *progress.lock().unwrap() = (0, 0);
+        self.perft_result = None;
+        self.perft_receiver = Some(receiver);
+
+        std::thread::spawn(move || {
+            let board = match Board::from_fen(&fen) {
+                Ok(board) => board,
+                Err(_) => return,
+            };
+
+            let start_time = Instant::now();
+            let nodes = board.perft_with_progress(depth, |completed, total| {
+                *progress.lock().unwrap() = (completed, total);
+            });
+
+            let _ = sender.send((depth, nodes, start_time.elapsed().as_millis()));
+        });
+    }
+
     fn show_promotion_dialog(&mut self, ctx: &egui::Context) {
         if !self.show_promotion_dialog {
             return;
@@ -587,15 +1391,16 @@ impl ChessApp {
     fn execute_promotion_move(&mut self, from: Square, to: Square, promotion_piece: u8) {
         let promotion_move = Move::new_promotion(from, to, promotion_piece);
         
-        if self.board.try_make_move(promotion_move).is_ok() {
+        if self.accept_puzzle_attempt(promotion_move) && self.board.try_make_move(promotion_move).is_ok() {
 
             self.move_history.push(promotion_move);
             self.redo_history.clear();
-            // Schedule AI move if it's now AI's turn
-            if self.board.current_turn == BLACK && self.ai_enabled {
-                self.ai_move_scheduled = Some(Instant::now());
-            }
+            self.punch_clock();
+            self.save_last_game();
+            self.update_evaluation();
             self.check_game_over();
+            // Schedule AI move if it's now AI's turn
+            self.schedule_ai_move();
         }
         
         // Clear promotion state
@@ -603,15 +1408,298 @@ impl ChessApp {
         self.show_promotion_dialog = false;
     }
     
+    /// Write the game so far to games/<timestamp>.pgn and return the file name
+    fn export_pgn(&self) -> std::io::Result<String> {
+        let (white_player, black_player) = match (self.game_mode, self.human_color()) {
+            (GameMode::HumanVsHuman | GameMode::Puzzle, _) => ("Human", "Human"),
+            (GameMode::AIVsAI, _) => ("ChaseChess", "ChaseChess"),
+            (GameMode::HumanVsAI, WHITE) => ("Human", "ChaseChess"),
+            (GameMode::HumanVsAI, _) => ("ChaseChess", "Human"),
+        };
+        let mut metadata = PgnMetadata::new(white_player, black_player);
+        metadata.result = self.board.pgn_result().to_string();
+
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or(0);
+        let filename = format!("games/{}.pgn", timestamp);
+
+        std::fs::create_dir_all("games")?;
+        std::fs::write(&filename, self.board.to_pgn(&metadata))?;
+        Ok(filename)
+    }
+
+    /// Set up the puzzle after the current one, wrapping around at the end of the list
+    fn next_puzzle(&mut self) {
+        let index = self.puzzle.as_ref().map_or(0, |puzzle| (puzzle.index + 1) % PUZZLES.len());
+        self.start_puzzle(index);
+    }
+
+    /// Load a puzzle position and have the engine solve it in the background before it can be played
+    fn start_puzzle(&mut self, index: usize) {
+        let (name, fen) = PUZZLES[index];
+        let board = match Board::from_fen(fen) {
+            Ok(board) => board,
+            Err(e) => {
+                println!("Puzzle {} has a bad FEN: {}", name, e);
+                return;
+            }
+        };
+
+        // The solver sees the puzzle from the bottom of the board
+        self.board_flipped = board.current_turn == BLACK;
+        self.start_new_game(board, "Puzzle started");
+
+        // Board isn't Send, so the solver thread parses the FEN again
+        let (sender, receiver) = mpsc::channel();
+        std::thread::spawn(move || {
+            if let Ok(mut board) = Board::from_fen(fen) {
+                let _ = sender.send(SearchEngine::new().search(&mut board, PUZZLE_DEPTH).best_move);
+            }
+        });
+
+        self.puzzle = Some(Puzzle {
+            index,
+            solution: None,
+            solver: Some(receiver),
+            failed_attempts: 0,
+            solved: false,
+            last_attempt_correct: None,
+        });
+    }
+
+    /// Pick up the engine's solution once the background search is done
+    fn poll_puzzle_solver(&mut self, ctx: &egui::Context) {
+        let Some(puzzle) = self.puzzle.as_mut() else {
+            return;
+        };
+        if let Some(solver) = &puzzle.solver {
+            match solver.try_recv() {
+                Ok(solution) => {
+                    puzzle.solution = solution;
+                    puzzle.solver = None;
+                }
+                Err(mpsc::TryRecvError::Empty) => ctx.request_repaint_after(std::time::Duration::from_millis(100)),
+                Err(mpsc::TryRecvError::Disconnected) => puzzle.solver = None,
+            }
+        }
+    }
+
+    /// Check a move against the puzzle's solution; outside puzzles every move is accepted
+    fn accept_puzzle_attempt(&mut self, mv: Move) -> bool {
+        let Some(puzzle) = self.puzzle.as_mut() else {
+            return true;
+        };
+
+        if puzzle.solution == Some(mv) {
+            puzzle.solved = true;
+            puzzle.last_attempt_correct = Some(true);
+            if puzzle.failed_attempts == 0 {
+                self.puzzle_streak += 1;
+            }
+            true
+        } else {
+            puzzle.failed_attempts += 1;
+            puzzle.last_attempt_correct = Some(false);
+            self.puzzle_streak = 0;
+            false
+        }
+    }
+
+    /// Puzzle name, streak and the verdict on the last try, in the settings row
+    fn show_puzzle_status(&mut self, ui: &mut egui::Ui) {
+        if ui.button("Next Puzzle").clicked() {
+            self.next_puzzle();
+        }
+        ui.label(format!("Streak: {}", self.puzzle_streak));
+
+        let Some(puzzle) = &self.puzzle else {
+            return;
+        };
+        ui.label(PUZZLES[puzzle.index].0);
+
+        if puzzle.solver.is_some() {
+            ui.spinner();
+            ui.label("Solving puzzle...");
+            return;
+        }
+        let Some(solution) = puzzle.solution else {
+            ui.colored_label(Color32::RED, "The engine found no solution");
+            return;
+        };
+
+        match puzzle.last_attempt_correct {
+            Some(true) => ui.colored_label(Color32::GREEN, "✔ Correct!"),
+            Some(false) => ui.colored_label(Color32::RED, "Incorrect — try again"),
+            None => {
+                let side = if self.board.current_turn == WHITE { "White" } else { "Black" };
+                ui.label(format!("{} to play and win", side))
+            }
+        };
+        if puzzle.failed_attempts >= PUZZLE_ATTEMPTS && !puzzle.solved {
+            ui.label(format!("Solution: {}", self.board.move_to_san(solution)));
+        }
+    }
+
+    /// Keep the game in progress on disk so the next start can pick it up
+    fn save_last_game(&self) {
+        let result = std::fs::create_dir_all(SAVES_DIR)
+            .and_then(|_| std::fs::write(LAST_GAME_PATH, self.board.to_json()));
+        if let Err(e) = result {
+            println!("Saving the game failed: {}", e);
+        }
+    }
+
+    /// Write the game so far to saves/<timestamp>.json and return the file name
+    fn save_game(&self) -> std::io::Result<String> {
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or(0);
+        let filename = format!("{}/{}.json", SAVES_DIR, timestamp);
+
+        std::fs::create_dir_all(SAVES_DIR)?;
+        std::fs::write(&filename, self.board.to_json())?;
+        Ok(filename)
+    }
+
+    /// Open the Load Game window on the saved games, newest first
+    fn open_load_dialog(&mut self) {
+        let mut names: Vec<String> = std::fs::read_dir(SAVES_DIR)
+            .map(|entries| {
+                entries
+                    .filter_map(|entry| entry.ok())
+                    .map(|entry| entry.file_name().to_string_lossy().into_owned())
+                    .filter(|name| name.ends_with(".json"))
+                    .collect()
+            })
+            .unwrap_or_default();
+        names.sort_unstable_by(|a, b| b.cmp(a));
+        self.saved_games = Some(names);
+        self.load_error = None;
+    }
+
+    fn show_load_dialog(&mut self, ctx: &egui::Context) {
+        let Some(names) = self.saved_games.clone() else {
+            return;
+        };
+
+        let mut open = true;
+        let mut chosen = None;
+        egui::Window::new("Load Game")
+            .open(&mut open)
+            .resizable(false)
+            .show(ctx, |ui| {
+                if names.is_empty() {
+                    ui.label(format!("No saved games in {}/", SAVES_DIR));
+                }
+                egui::ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+                    for name in &names {
+                        if ui.button(name).clicked() {
+                            chosen = Some(name.clone());
+                        }
+                    }
+                });
+                if let Some(error) = &self.load_error {
+                    ui.colored_label(Color32::RED, error);
+                }
+            });
+
+        if let Some(name) = chosen {
+            self.load_game_file(&name);
+        } else if !open {
+            self.saved_games = None;
+        }
+    }
+
+    /// Replace the current game with one from the saves directory, closing the window on success
+    fn load_game_file(&mut self, name: &str) {
+        let path = format!("{}/{}", SAVES_DIR, name);
+        let loaded = std::fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|json| Board::from_json(&json).map_err(|e| e.to_string()));
+        match loaded {
+            Ok(board) => {
+                self.saved_games = None;
+                self.start_new_game(board, "Game loaded from file");
+            }
+            Err(e) => self.load_error = Some(format!("Could not load {}: {}", name, e)),
+        }
+    }
+
+    /// Save the current log and start over from `board` with a fresh logger
+    fn start_new_game(&mut self, mut board: Board, reason: &str) {
+        // Save current game log
+        if let Ok(filename) = self.logger.borrow_mut().save_to_file(reason) {
+            println!("Game log saved to: {}", filename);
+        }
+        
+        // Create new logger
+        let new_logger = std::rc::Rc::new(std::cell::RefCell::new(engine::ChessLogger::new()));
+        
+        // Reset everything
+        board.set_logger(new_logger.clone());
+        
+        self.board = board;
+        self.ai_white.set_logger(new_logger.clone());
+        self.ai_black.set_logger(new_logger.clone());
+        self.selected_square = None;
+        self.legal_moves.clear();
+        self.last_ai_move = None;
+        self.is_ai_thinking = false;
+        self.ai_move_scheduled = None;
+        self.ai_paused = false;
+        self.puzzle = None;
+        self.promotion_pending = None;
+        self.show_promotion_dialog = false;
+        // A loaded game comes with its history, which stays undoable
+        self.move_history = self.board.move_history.iter().map(|game_move| game_move.mv).collect();
+        self.redo_history.clear();
+
+        // Fresh clocks under the time control picked in the settings
+        self.time_control = self.next_time_control;
+        let initial_ms = match self.time_control {
+            TimeControl::Unlimited => 0,
+            TimeControl::Classic(initial_ms, _) => initial_ms,
+        };
+        self.white_time_ms = initial_ms;
+        self.black_time_ms = initial_ms;
+        self.last_move_instant = Instant::now();
+        self.clock_paused_at = None;
+        self.flagged = None;
+        
+        // Update logger reference
+        self.logger = new_logger;
+        self.game_over = self.board.game_result().is_some();
+        self.update_evaluation();
+        self.save_last_game();
+
+        // The AI moves first if it has the side to move
+        self.schedule_ai_move();
+    }
+
+    /// Load the position typed into the FEN field, keeping the text and showing the error if it fails
+    fn load_fen_input(&mut self) {
+        match Board::from_fen(self.fen_input.trim()) {
+            Ok(board) => {
+                self.fen_error = None;
+                self.start_new_game(board, "Position loaded from FEN");
+            }
+            Err(e) => self.fen_error = Some(e.to_string()),
+        }
+    }
+
     fn can_undo(&self) -> bool {
-        !self.move_history.is_empty() && !self.is_ai_thinking && self.ai_move_scheduled.is_none()
+        !self.move_history.is_empty() && !self.is_ai_thinking && self.ai_move_scheduled.is_none() && self.puzzle.is_none()
     }
     
     fn can_redo(&self) -> bool {
-        !self.redo_history.is_empty() && !self.is_ai_thinking && self.ai_move_scheduled.is_none()
+        !self.redo_history.is_empty() && !self.is_ai_thinking && self.ai_move_scheduled.is_none() && self.puzzle.is_none()
     }
     
     fn undo_move(&mut self) {
+        self.settle_clock();
         if let Some(last_move) = self.move_history.pop() {
             // Use your existing undo function
             if self.board.undo_move().is_ok() {
@@ -624,7 +1712,10 @@ impl ChessApp {
                 self.selected_square = None;
                 self.legal_moves.clear();
                 self.last_ai_move = None;
-                self.game_over = false;
+                self.flagged = None;
+                self.game_over = self.board.game_result().is_some();
+                self.update_evaluation();
+                self.save_last_game();
             } else {
                 // If undo failed, restore the move to history
                 self.move_history.push(last_move);
@@ -632,7 +1723,19 @@ impl ChessApp {
         }
     }
     
+    /// Undo back to the human's turn: the AI's last reply and the human's move, or as many as there are
+    fn take_back(&mut self) {
+        self.undo_move();
+        if self.game_mode == GameMode::HumanVsAI && self.is_ai_turn() && self.can_undo() {
+            self.undo_move();
+        }
+
+        // Only the AI's opening move was taken back, so it plays again
+        self.schedule_ai_move();
+    }
+
     fn redo_move(&mut self) {
+        self.settle_clock();
         if let Some(redo_move) = self.redo_history.pop() {
             if self.board.try_make_move(redo_move).is_ok() {
                 // LOG: Redo
@@ -644,6 +1747,9 @@ impl ChessApp {
                 self.selected_square = None;
                 self.legal_moves.clear();
                 self.game_over = false;
+                self.flagged = None;
+                self.update_evaluation();
+                self.save_last_game();
             } else {
                 // If redo failed, restore the move to redo stack
                 self.redo_history.push(redo_move);
@@ -652,3 +1758,35 @@ impl ChessApp {
     }
     
 }
+
+/// "+1.3" style pawns, or "M3" / "-M3" once a forced mate is found
+fn format_evaluation(evaluation: i32) -> String {
+    match ai::mate_in_moves(evaluation) {
+        Some(moves) => format!("{}M{}", if moves < 0 { "-" } else { "" }, moves.abs()),
+        None => format!("{:+.1}", evaluation as f32 / 100.0),
+    }
+}
+
+/// "m:ss", with tenths once under ten seconds
+fn format_clock(ms: u64) -> String {
+    if ms < 10_000 {
+        format!("0:{:02}.{}", ms / 1000, (ms % 1000) / 100)
+    } else {
+        let seconds = ms.div_ceil(1000);
+        format!("{}:{:02}", seconds / 60, seconds % 60)
+    }
+}
+
+fn game_result_text(result: GameResult) -> String {
+    match result {
+        GameResult::InProgress => "Game in progress".to_string(),
+        GameResult::WhiteWins => "Checkmate - White wins!".to_string(),
+        GameResult::BlackWins => "Checkmate - Black wins!".to_string(),
+        GameResult::Draw(DrawReason::Stalemate) => "Stalemate - Draw".to_string(),
+        GameResult::Draw(DrawReason::FiftyMove) => "Draw by the fifty-move rule".to_string(),
+        GameResult::Draw(DrawReason::Repetition) => "Draw by repetition".to_string(),
+        GameResult::Draw(DrawReason::InsufficientMaterial) => "Draw by insufficient material".to_string(),
+        GameResult::Draw(DrawReason::Theoretical) => "Theoretical draw".to_string(),
+        GameResult::Draw(DrawReason::Agreement) => "Draw by agreement".to_string(),
+    }
+}
@@ -0,0 +1,91 @@
+// JavaScript-callable API for running the engine in the browser.
+// Everything here is only compiled for the wasm32 target.
+
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen::prelude::*;
+
+#[cfg(target_arch = "wasm32")]
+use engine::{Board, Move, Square};
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen(start)]
+pub fn start() {
+    engine::bitboard::initialize_engine();
+}
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+pub struct WasmBoard {
+    board: Board,
+}
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+impl WasmBoard {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> WasmBoard {
+        WasmBoard { board: Board::new() }
+    }
+
+    pub fn from_fen(fen: &str) -> Result<WasmBoard, JsValue> {
+        let board = Board::from_fen(fen).map_err(|e| JsValue::from_str(&e.to_string()))?;
+        Ok(WasmBoard { board })
+    }
+
+    /// Legal moves from a square such as "e2", as UCI strings
+    pub fn get_legal_moves(&self, sq: &str) -> Vec<String> {
+        match parse_square(sq) {
+            Some(square) => self.board.legal_moves_from(square)
+                .into_iter()
+                .map(|mv| mv.to_string())
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Play a move given in UCI notation, returns false if it is not legal
+    pub fn make_move(&mut self, mv: &str) -> bool {
+        let parsed = match Move::from_uci(mv) {
+            Some(parsed) => parsed,
+            None => return false,
+        };
+
+        if !self.board.legal_moves_from(parsed.from).contains(&parsed) {
+            return false;
+        }
+
+        self.board.try_make_move(parsed).is_ok()
+    }
+
+    pub fn undo_move(&mut self) -> bool {
+        self.board.undo_move().is_ok()
+    }
+
+    pub fn to_fen(&self) -> String {
+        self.board.to_fen()
+    }
+
+    pub fn get_best_move(&self, depth: u8) -> Option<String> {
+        let mut board = self.board.clone();
+        let mut search_engine = ai::SearchEngine::new();
+        search_engine.search(&mut board, depth as u32)
+            .best_move
+            .map(|mv| mv.to_string())
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+fn parse_square(sq: &str) -> Option<Square> {
+    let bytes = sq.as_bytes();
+    if bytes.len() != 2 {
+        return None;
+    }
+
+    let file = bytes[0].wrapping_sub(b'a');
+    let rank = bytes[1].wrapping_sub(b'1');
+    if file >= 8 || rank >= 8 {
+        return None;
+    }
+
+    Some(Square::new(file, rank))
+}